@@ -0,0 +1,38 @@
+#!/bin/bash
+set -e
+
+# Adds the SHA-256 of every release archive of the given Pkl versions to src/pkl_checksums.txt.
+# With no versions, it covers the compatible versions in src/pkl_tooling.rs.
+
+CHECKSUMS_FILE="src/pkl_checksums.txt"
+MIRROR="${SPKLR_PKL_MIRROR:-https://github.com/apple/pkl/releases/download}"
+ARCHIVES="pkl-cli-linux-amd64.tar.gz pkl-cli-linux-aarch64.tar.gz pkl-cli-macos-amd64.tar.gz pkl-cli-macos-aarch64.tar.gz pkl-cli-windows-amd64.zip"
+
+if [ ! -f "$CHECKSUMS_FILE" ]; then
+    echo "❌ $CHECKSUMS_FILE not found"
+    exit 1
+fi
+
+VERSIONS="$*"
+if [ -z "$VERSIONS" ]; then
+    VERSIONS=$(grep -A1 "fn get_compatible_pkl_versions" src/pkl_tooling.rs | grep -o '"[0-9][^"]*"' | tr -d '"')
+fi
+
+WORK_DIR=$(mktemp -d)
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+for VERSION in $VERSIONS; do
+    for ARCHIVE in $ARCHIVES; do
+        ENTRY="$VERSION/$ARCHIVE"
+        if grep -q "  $ENTRY\$" "$CHECKSUMS_FILE"; then
+            echo "✅ $ENTRY already listed"
+            continue
+        fi
+
+        echo "Downloading $MIRROR/$ENTRY"
+        curl -fsSL -o "$WORK_DIR/$ARCHIVE" "$MIRROR/$ENTRY"
+        HASH=$(sha256sum "$WORK_DIR/$ARCHIVE" | cut -d' ' -f1)
+        echo "$HASH  $ENTRY" >> "$CHECKSUMS_FILE"
+        echo "✅ $ENTRY: $HASH"
+    done
+done
@@ -49,6 +49,11 @@ else
     exit 1
 fi
 
+# Record the checksums of the new version's release archives
+echo "Updating src/pkl_checksums.txt..."
+./scripts/update-pkl-checksums.sh "$NEW_VERSION"
+echo "✅ Updated src/pkl_checksums.txt"
+
 # Update Cargo.toml if there are version-specific dependencies
 CARGO_FILE="Cargo.toml"
 if [ -f "$CARGO_FILE" ]; then
@@ -62,5 +67,6 @@ echo ""
 echo "Files modified:"
 echo "  - $PKL_TOOLING_FILE"
 echo "  - $WORKFLOW_FILE"
+echo "  - src/pkl_checksums.txt"
 echo ""
 echo "Please review the changes and commit them."
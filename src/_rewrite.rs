@@ -0,0 +1,649 @@
+//! Core Logic Module for Space Pklr
+//!
+//! This module encapsulates the primary business logic for configuration loading, conversion,
+//! rendering, and schema/template generation.
+
+use std::path::Path;
+use std::str::FromStr;
+use moon_config::{ProjectConfig, WorkspaceConfig, TemplateConfig, ToolchainConfig, TaskConfig, InheritedTasksConfig};
+
+use crate::pkl_deserializer::{from_pkl_value, load_pkl_config, parse_pcf};
+use crate::pkl_evaluator::SharedEvaluator;
+use crate::pkl_serializer::PklValue;
+use schematic::schema::{Schema, SchemaType};
+
+use crate::types::{CliError, LoadedConfig, SchemaFormat, MoonConfig, TypeInference, TypeMap, UnknownConfig, validation_error};
+
+/// Load and validate a configuration file
+pub async fn load_config(
+    path: &Path,
+    _config_type: MoonConfig,
+    format: Option<SchemaFormat>,
+) -> Result<(String, SchemaFormat), CliError> {
+    // Read the file content
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| CliError::IoError {
+            context: format!("Reading config file: {}", path.display()),
+            source: e,
+        })?;
+
+    // Determine format
+    let detected_format = if let Some(fmt) = format {
+        fmt
+    } else {
+        detect_format_from_path(path)?
+    };
+
+    Ok((content, detected_format))
+}
+
+/// Parse configuration source into the typed config for `config_type`.
+///
+/// YAML, JSON and TOML go through schematic's `ConfigLoader`. Pkl is read with the static
+/// [PCF parser](crate::pkl_deserializer), so it doesn't need the Pkl CLI, and then loaded the same way.
+/// Either way the config is validated and its defaults filled in.
+pub fn parse_config(
+    source_name: &str,
+    content: &str,
+    config_type: MoonConfig,
+    format: &SchemaFormat,
+) -> Result<LoadedConfig, CliError> {
+    match format {
+        SchemaFormat::Pkl => load_pkl_config(source_name, content, config_type),
+        SchemaFormat::Yaml | SchemaFormat::Json | SchemaFormat::Toml => {
+            LoadedConfig::from_code(config_type, content, format.to_schematic())
+        }
+        SchemaFormat::Typescript => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["yaml", "json", "toml", "pkl"],
+        }),
+    }
+}
+
+/// Parse configuration source into a plain value, without checking it against any config type
+pub fn parse_value(source_name: &str, content: &str, format: &SchemaFormat) -> Result<serde_json::Value, CliError> {
+    match format {
+        SchemaFormat::Pkl => from_pkl_value(parse_pcf(source_name, content)?.value),
+        SchemaFormat::Json => serde_json::from_str(content).map_err(validation_error),
+        SchemaFormat::Yaml => serde_yaml::from_str(content).map_err(validation_error),
+        SchemaFormat::Toml => toml::from_str(content).map_err(validation_error),
+        SchemaFormat::Typescript => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["yaml", "json", "toml", "pkl"],
+        }),
+    }
+}
+
+/// Infer which Moon config a file holds: from its name when that's one Moon knows, otherwise from its settings
+pub fn infer_config_type(path: &Path, content: &str, format: &SchemaFormat) -> Result<TypeInference, CliError> {
+    if let Some(config_type) = MoonConfig::detect_from_path(path) {
+        return Ok(TypeInference::certain(config_type));
+    }
+    let value = parse_value(&path.display().to_string(), content, format)?;
+    let mut config = UnknownConfig::with_format(value, format.clone());
+    config.hint_type().ok_or_else(|| CliError::UnknownConfigType { path: path.to_path_buf() })
+}
+
+/// Read a config file and infer which Moon config it holds
+pub async fn detect_config_type(path: &Path, format: Option<SchemaFormat>) -> Result<TypeInference, CliError> {
+    let (content, format) = load_config(path, MoonConfig::All, format).await?;
+    infer_config_type(path, &content, &format)
+}
+
+/// Load configuration using schematic's ConfigLoader with proper type safety
+///
+/// Pkl that isn't static (it imports, or computes values) is evaluated with the Pkl CLI when one is installed.
+pub async fn load_config_with_schematic(
+    path: &Path,
+    config_type: MoonConfig,
+    format: Option<SchemaFormat>,
+) -> Result<LoadedConfig, CliError> {
+    load_config_with_evaluator(path, config_type, format, None).await
+}
+
+/// Like [`load_config_with_schematic`], but Pkl that isn't static is evaluated with `evaluator` when one is given
+pub async fn load_config_with_evaluator(
+    path: &Path,
+    config_type: MoonConfig,
+    format: Option<SchemaFormat>,
+    evaluator: Option<&SharedEvaluator>,
+) -> Result<LoadedConfig, CliError> {
+    let (content, format) = load_config(path, config_type, format).await?;
+    let source_name = path.display().to_string();
+
+    match parse_config(&source_name, &content, config_type, &format) {
+        Err(error @ CliError::PcfParseError { .. }) => {
+            let Some(json) = evaluate_pkl_to_json(path, evaluator).await else {
+                return Err(error);
+            };
+            parse_config(&source_name, &json?, config_type, &SchemaFormat::Json)
+        }
+        result => result,
+    }
+}
+
+/// Evaluate a Pkl module to JSON with `evaluator`, or with a run of the Pkl CLI without one. `None` when there's
+/// no Pkl CLI installed to do it.
+pub async fn evaluate_pkl_to_json(path: &Path, evaluator: Option<&SharedEvaluator>) -> Option<Result<String, CliError>> {
+    match evaluator {
+        Some(evaluator) => match evaluator.evaluate_to_json(path).await {
+            Err(CliError::PklInstallFailed { .. }) => None,
+            result => Some(result),
+        },
+        None => {
+            let pkl_cli = ensure_pkl_available().await.ok()?;
+            Some(eval_pkl_to_json(&pkl_cli, path).await)
+        }
+    }
+}
+
+/// Evaluate a Pkl module with the Pkl CLI, returning its JSON output
+pub async fn eval_pkl_to_json(pkl_cli: &crate::pkl_tooling::PklCli, path: &Path) -> Result<String, CliError> {
+    let args = ["eval".to_string(), "--format".to_string(), "json".to_string(), path.display().to_string()];
+    crate::pkl_tooling::execute_pkl_command(pkl_cli, &args)
+        .await
+        .map_err(|report| report.downcast::<CliError>().unwrap_or_else(|report| CliError::Generic(report.to_string())))
+}
+
+/// Serialize a loaded config in the given format
+pub fn render_config_with_schematic(
+  config: &LoadedConfig,
+  format: SchemaFormat,
+) -> Result<String, CliError> {
+    match config {
+        LoadedConfig::Project(config) => render_config_file(config, &format),
+        LoadedConfig::Workspace(config) => render_config_file(config, &format),
+        LoadedConfig::Template(config) => render_config_file(config, &format),
+        LoadedConfig::Toolchain(config) => render_config_file(config, &format),
+        LoadedConfig::Task(config) => render_config_file(config, &format),
+        LoadedConfig::InheritedTasks(config) => render_config_file(config, &format),
+        LoadedConfig::Unknown(config) => serialize_config_in_format(&config.content, &format),
+    }
+}
+
+/// Render a Moon config as what its file holds. Settings the loader doesn't accept (internal ones the schema
+/// hides) are dropped, flattened ones go back beside their struct's own settings, and unset (`null`) settings
+/// are left out, which TOML needs anyway. Schematic reads the schema URL from `$schema`, but the struct
+/// serializes it as `schema`; Pkl leaves it out and amends the config type's Pkl schema, `<Root>.pkl`, instead.
+fn render_config_file<T: serde::Serialize + schematic::Schematic>(
+    config: &T,
+    format: &SchemaFormat,
+) -> Result<String, CliError> {
+    use crate::pkl_serializer::{PklSerializerOptions, pkl_value_to_string, to_pkl_value};
+
+    // structs stay objects and only real maps become mappings, which Pkl tells apart
+    let mut value = to_pkl_value(config)?;
+    strip_nulls(&mut value);
+    let types = crate::custom_types::type_map_of::<T>();
+    let root_name = types.last().map(|(name, root)| {
+        prune_to_schema(&mut value, root, &types);
+        name.clone()
+    });
+
+    let mut schema = None;
+    if let PklValue::Object(properties) = &mut value
+        && let Some(index) = properties.iter().position(|(key, _)| key == "schema")
+    {
+        schema = Some(properties.remove(index).1);
+    }
+    if *format == SchemaFormat::Pkl {
+        let options = PklSerializerOptions {
+            amends: root_name.map(|name| format!("{}.pkl", name)),
+            ..Default::default()
+        };
+        return pkl_value_to_string(value, &options);
+    }
+    if let PklValue::Object(properties) = &mut value
+        && let Some(schema) = schema
+    {
+        properties.insert(0, ("$schema".to_string(), schema));
+    }
+    serialize_config_in_format(&value, format)
+}
+
+/// Settings moon declares with `#[setting(flatten)]`, by struct and field. A file holds their entries beside the
+/// struct's own settings, but the schema doesn't record the flattening and the struct serializes them nested.
+const FLATTENED_SETTINGS: [(&str, &str); 4] = [
+    ("ExtensionConfig", "config"),
+    ("ProjectToolchainConfig", "plugins"),
+    ("ToolchainConfig", "plugins"),
+    ("ToolchainPluginConfig", "config"),
+];
+
+/// Drops struct fields the schema doesn't have or hides, and inlines flattened ones.
+fn prune_to_schema(value: &mut PklValue, schema: &Schema, types: &TypeMap) {
+    match (&schema.ty, value) {
+        (SchemaType::Struct(structure), PklValue::Object(properties)) => {
+            // `schema` is handled by `render_config_file`
+            properties.retain(|(key, _)| {
+                key == "schema" || structure.fields.get(key).is_some_and(|field| !field.hidden)
+            });
+            for (key, value) in properties.iter_mut() {
+                if let Some(field) = structure.fields.get(key) {
+                    prune_to_schema(value, &field.schema, types);
+                }
+            }
+            inline_flattened(schema.name.as_deref(), properties);
+        }
+        (SchemaType::Array(array), PklValue::Listing(items)) => {
+            items.iter_mut().for_each(|item| prune_to_schema(item, &array.items_type, types));
+        }
+        (SchemaType::Object(object), PklValue::Mapping(entries)) => {
+            entries.iter_mut().for_each(|(_, value)| prune_to_schema(value, &object.value_type, types));
+        }
+        (SchemaType::Union(union), value) => {
+            // the first struct that has every key, or the first variant of the same kind
+            let variant = union.variants_types.iter().find(|variant| match (&variant.ty, &*value) {
+                (SchemaType::Struct(structure), PklValue::Object(properties)) => {
+                    properties.iter().all(|(key, _)| structure.fields.contains_key(key))
+                }
+                (SchemaType::Array(_), PklValue::Listing(_)) => true,
+                (SchemaType::Object(_), PklValue::Mapping(_)) => true,
+                (SchemaType::Reference(_), _) => true,
+                _ => false,
+            });
+            if let Some(variant) = variant {
+                prune_to_schema(value, variant, types);
+            }
+        }
+        (SchemaType::Reference(name), value) => {
+            if let Some(schema) = types.get(name) {
+                prune_to_schema(value, schema, types);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Moves the entries of `struct_name`'s flattened setting in among its other settings, so an empty one is left out
+fn inline_flattened(struct_name: Option<&str>, properties: &mut Vec<(String, PklValue)>) {
+    let Some((_, field)) = FLATTENED_SETTINGS.iter().find(|(name, _)| Some(*name) == struct_name) else {
+        return;
+    };
+    let Some(index) = properties.iter().position(|(key, _)| key == field) else {
+        return;
+    };
+    let PklValue::Mapping(entries) = properties.remove(index).1 else {
+        return;
+    };
+    for (key, value) in entries {
+        // a setting of the struct's own wins, as it does when moon loads the file
+        if let PklValue::String(key) = key
+            && !properties.iter().any(|(name, _)| *name == key)
+        {
+            properties.push((key, value));
+        }
+    }
+}
+
+fn strip_nulls(value: &mut PklValue) {
+    match value {
+        PklValue::Object(properties) => {
+            properties.retain(|(_, value)| *value != PklValue::Null);
+            properties.iter_mut().for_each(|(_, value)| strip_nulls(value));
+        }
+        PklValue::Mapping(entries) => {
+            entries.retain(|(_, value)| *value != PklValue::Null);
+            entries.iter_mut().for_each(|(_, value)| strip_nulls(value));
+        }
+        PklValue::Listing(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Convert configuration source from one format to another, validating it as `config_type` on the way
+pub fn convert_config(
+    source_name: &str,
+    content: &str,
+    config_type: MoonConfig,
+    from_format: SchemaFormat,
+    to_format: SchemaFormat,
+) -> Result<String, CliError> {
+    let config = parse_config(source_name, content, config_type, &from_format)?;
+    render_config_with_schematic(&config, to_format)
+}
+
+/// Detect format from file path extension
+pub fn detect_format_from_path(path: &Path) -> Result<SchemaFormat, CliError> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .ok_or_else(|| CliError::UnsupportedFormat {
+            format: "unknown".to_string(),
+            available: vec!["yaml", "yml", "json", "toml", "pkl", "pcf"],
+        })?;
+
+    SchemaFormat::from_str(extension)
+}
+
+/// Enhanced format detection that includes Pkl support
+pub fn detect_format_from_path_enhanced(path: &std::path::Path) -> Result<SchemaFormat, CliError> {
+    detect_format_from_path(path)
+}
+
+/// Check if Pkl CLI is available for Pkl operations
+pub async fn ensure_pkl_available() -> Result<crate::pkl_tooling::PklCli, CliError> {
+    // Try to find existing Pkl installation
+    if let Ok(Some(pkl_cli)) = crate::pkl_tooling::find_pkl_executable().await {
+        return Ok(pkl_cli);
+    }
+
+    // If not found, suggest installation
+    Err(CliError::PklInstallFailed {
+        reason: "Pkl CLI not found".to_string(),
+        help: Some("Install Pkl CLI with: spklr pkl-me pkl".to_string()),
+    })
+}
+
+/// Generate JSON schema for a Moon configuration type using schematic's existing capabilities
+pub fn generate_schema(
+    config_type: MoonConfig,
+    format: &str,
+) -> Result<String, CliError> {
+    use schematic::schema::{SchemaGenerator, JsonSchemaRenderer, TypeScriptRenderer};
+
+    if format == "pkl" {
+        return crate::custom_types::render_moon_schema(config_type, &SchemaFormat::Pkl);
+    }
+
+    let mut generator = SchemaGenerator::default();
+
+    // Add the appropriate config type to the generator using schematic's existing capabilities
+    match config_type {
+        MoonConfig::Project => {
+            generator.add::<moon_config::ProjectConfig>();
+        }
+        MoonConfig::Workspace => {
+            generator.add::<moon_config::WorkspaceConfig>();
+        }
+        MoonConfig::Toolchain => {
+            generator.add::<moon_config::ToolchainConfig>();
+        }
+        MoonConfig::Template => {
+            generator.add::<moon_config::TemplateConfig>();
+        }
+        MoonConfig::Task => {
+            generator.add::<moon_config::TaskConfig>();
+        }
+        MoonConfig::InheritedTasks => {
+            generator.add::<moon_config::InheritedTasksConfig>();
+        }
+        MoonConfig::All => {
+            return Err(CliError::Generic("Cannot generate schema for 'All' - use generate_all_schemas functions".to_string()));
+        }
+    }
+
+    // Generate schema using schematic's existing renderers
+    match format {
+        "json-schema" => {
+            let temp_file = std::env::temp_dir().join("schema.json");
+            generator.generate(&temp_file, JsonSchemaRenderer::default())
+                .map_err(|e| CliError::ValidationError {
+                    source: Box::new(std::io::Error::other(e.to_string()))
+                })?;
+
+            std::fs::read_to_string(&temp_file)
+                .map_err(|e| CliError::IoError {
+                    context: "Reading generated schema".to_string(),
+                    source: e,
+                })
+        }
+        "typescript" => {
+            let temp_file = std::env::temp_dir().join("types.ts");
+            generator.generate(&temp_file, TypeScriptRenderer::default())
+                .map_err(|e| CliError::ValidationError {
+                    source: Box::new(std::io::Error::other(e.to_string()))
+                })?;
+
+            std::fs::read_to_string(&temp_file)
+                .map_err(|e| CliError::IoError {
+                    context: "Reading generated TypeScript types".to_string(),
+                    source: e,
+                })
+        }
+        _ => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["json-schema", "typescript", "pkl"],
+        })
+    }
+}
+
+/// Generate schema for all configuration types and formats
+pub fn generate_all_schemas(format: &str) -> Result<Vec<(String, String)>, CliError> {
+    let mut results = Vec::new();
+
+    for config_type in MoonConfig::all_types() {
+        let schema_content = generate_schema(config_type, format)?;
+        let filename = format!("{}_schema.{}", config_type,
+            match format {
+                "json-schema" => "json",
+                "typescript" => "ts",
+                _ => format,
+            }
+        );
+        results.push((filename, schema_content));
+    }
+
+    Ok(results)
+}
+
+/// Generate schemas for all formats for a specific config type
+pub fn generate_all_formats_schema(config_type: MoonConfig) -> Result<Vec<(String, String)>, CliError> {
+    let formats = vec!["json-schema", "typescript"];
+    let mut results = Vec::new();
+
+    for format in formats {
+        let schema_content = generate_schema(config_type, format)?;
+        let filename = format!("{}_schema.{}", config_type,
+            match format {
+                "json-schema" => "json",
+                "typescript" => "ts",
+                _ => format,
+            }
+        );
+        results.push((filename, schema_content));
+    }
+
+    Ok(results)
+}
+
+/// Generate all schemas for all types and all formats
+pub fn generate_all_schemas_all_formats() -> Result<Vec<(String, String)>, CliError> {
+    let formats = ["json-schema", "typescript"];
+    let mut results = Vec::new();
+
+    for config_type in MoonConfig::all_types() {
+        for format in formats.iter() {
+            let schema_content = generate_schema(config_type, format)?;
+            let filename = format!("{}_schema.{}", config_type,
+                match *format {
+                    "json-schema" => "json",
+                    "typescript" => "ts",
+                    _ => format,
+                }
+            );
+            results.push((filename, schema_content));
+        }
+    }
+
+    Ok(results)
+}
+
+/// Generate schema using schematic's built-in renderers
+pub fn generate_schema_with_schematic(
+    config_type: MoonConfig,
+    format: &str,
+) -> Result<String, CliError> {
+    // For now, delegate to the existing working implementation
+    // This will be enhanced once we have the proper schematic API integration
+    generate_schema(config_type, format)
+}
+
+/// Generate default/template configuration using existing moon_config templates and defaults
+pub fn generate_template(
+    config_type: MoonConfig,
+    format: SchemaFormat,
+) -> Result<String, CliError> {
+    // Use existing moon_config templates when available, or generate defaults using schematic
+    let template_content = match config_type {
+        MoonConfig::Project => {
+            // Generate minimal project config using defaults
+            let config = moon_config::ProjectConfig::default();
+            serialize_config_in_format(&config, &format)?
+        }
+        MoonConfig::Workspace => {
+            // Generate minimal workspace config using defaults
+            // Set some sensible defaults for workspace
+            let config = moon_config::WorkspaceConfig {
+                projects: moon_config::WorkspaceProjects::Globs(vec!["projects/*".to_string()]),
+                ..Default::default()
+            };
+            serialize_config_in_format(&config, &format)?
+        }
+        MoonConfig::Toolchain => {
+            // Generate minimal toolchain config using defaults
+            let config = moon_config::ToolchainConfig::default();
+            serialize_config_in_format(&config, &format)?
+        }
+        MoonConfig::Template => {
+            // Generate minimal template config using defaults
+            let config = moon_config::TemplateConfig::default();
+            serialize_config_in_format(&config, &format)?
+        }
+        MoonConfig::Task => {
+            // Generate minimal task config using defaults
+            let config = moon_config::TaskConfig::default();
+            serialize_config_in_format(&config, &format)?
+        }
+        MoonConfig::InheritedTasks => {
+            // Generate minimal inherited tasks config using defaults
+            let config = moon_config::InheritedTasksConfig::default();
+            serialize_config_in_format(&config, &format)?
+        }
+        MoonConfig::All => {
+            return Err(CliError::Generic("Cannot generate template for 'All' - use generate_all_templates functions".to_string()));
+        }
+    };
+
+    Ok(template_content)
+}
+
+/// Generate template for all configuration types
+pub fn generate_all_templates(format: SchemaFormat) -> Result<Vec<(String, String)>, CliError> {
+    let mut results = Vec::new();
+
+    for config_type in MoonConfig::all_types() {
+        let template_content = generate_template(config_type, format.clone())?;
+        let filename = format!("{}.{}", config_type, format);
+        results.push((filename, template_content));
+    }
+
+    Ok(results)
+}
+
+/// Generate templates for all formats for a specific config type
+pub fn generate_all_formats_template(config_type: MoonConfig) -> Result<Vec<(String, String)>, CliError> {
+    let formats = vec![SchemaFormat::Yaml, SchemaFormat::Json, SchemaFormat::Pkl];
+    let mut results = Vec::new();
+
+    for format in formats {
+        let template_content = generate_template(config_type, format.clone())?;
+        let filename = format!("{}.{}", config_type, format);
+        results.push((filename, template_content));
+    }
+
+    Ok(results)
+}
+
+/// Generate all templates for all types and all formats
+pub fn generate_all_templates_all_formats() -> Result<Vec<(String, String)>, CliError> {
+    let formats = [SchemaFormat::Yaml, SchemaFormat::Json, SchemaFormat::Pkl];
+    let mut results = Vec::new();
+
+    for config_type in MoonConfig::all_types() {
+        for format in formats.iter() {
+            let template_content = generate_template(config_type, format.clone())?;
+            let filename = format!("{}.{}", config_type, format);
+            results.push((filename, template_content));
+        }
+    }
+
+    Ok(results)
+}
+
+/// Generate template configurations using schematic's default mechanisms
+pub fn generate_template_with_schematic(
+    config_type: MoonConfig,
+    format: SchemaFormat,
+) -> Result<String, CliError> {
+    // Create default configuration using schematic's default mechanisms
+    let loaded_config = match config_type {
+        MoonConfig::Project => {
+            let config = ProjectConfig::default();
+            LoadedConfig::Project(config)
+        }
+        MoonConfig::Workspace => {
+            // Set some sensible defaults for workspace
+            let config = WorkspaceConfig {
+                projects: moon_config::WorkspaceProjects::Globs(vec!["projects/*".to_string()]),
+                ..Default::default()
+            };
+            LoadedConfig::Workspace(config)
+        }
+        MoonConfig::Toolchain => {
+            let config = ToolchainConfig::default();
+            LoadedConfig::Toolchain(config)
+        }
+        MoonConfig::Template => {
+            let config = TemplateConfig::default();
+            LoadedConfig::Template(config)
+        }
+        MoonConfig::Task => {
+            let config = TaskConfig::default();
+            LoadedConfig::Task(config)
+        }
+        MoonConfig::InheritedTasks => {
+            let config = InheritedTasksConfig::default();
+            LoadedConfig::InheritedTasks(config)
+        }
+        MoonConfig::All => {
+            return Err(CliError::Generic("Cannot generate template for 'all' - use specific functions".to_string()));
+        }
+    };
+
+    // Use the new schematic-based renderer
+    render_config_with_schematic(&loaded_config, format)
+}
+
+/// Helper to serialize a config struct in the requested format
+fn serialize_config_in_format<T: serde::Serialize>(
+    config: &T,
+    format: &SchemaFormat,
+) -> Result<String, CliError> {
+    match format {
+        SchemaFormat::Yaml => {
+            serde_yaml::to_string(config)
+                .map_err(|e| CliError::ValidationError {
+                    source: Box::new(e)
+                })
+        }
+        SchemaFormat::Json => {
+            serde_json::to_string_pretty(config)
+                .map_err(|e| CliError::ValidationError {
+                    source: Box::new(e)
+                })
+        }
+        SchemaFormat::Toml => {
+            toml::to_string_pretty(config)
+                .map_err(|e| CliError::ValidationError {
+                    source: Box::new(e)
+                })
+        }
+        SchemaFormat::Pkl => crate::pkl_serializer::to_pkl_string(config),
+        SchemaFormat::Typescript => Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["yaml", "json", "toml", "pkl"],
+        }),
+    }
+}
@@ -21,12 +21,19 @@ pub struct Cli {
 pub enum Commands {
     /// Convert Moon configuration files between formats
     Convert(crate::commands::convert::ConvertArgs),
+    /// Generate schemas or templates for any type, not just Moon configs
+    #[command(subcommand)]
+    Custom(crate::commands::custom::CustomCommands),
+    /// Show the config Moon ends up with once extends and inherited tasks are merged in
+    Effective(crate::commands::effective::EffectiveArgs),
     /// Generate schemas or template configurations
     #[command(subcommand)]
     Generate(crate::commands::generate::GenerateCommands),
-    /// Install Pkl CLI tool
+    /// Install Pkl CLI and manage its installed versions
     #[command(subcommand)]
     PklMe(crate::commands::pklme::InstallCommands),
+    /// Validate Moon configuration files against their schemas
+    Validate(crate::commands::validate::ValidateArgs),
 }
 
 /// CLI application with error handling
@@ -44,6 +51,26 @@ pub async fn run() -> Result<()> {
                 }
             }
         }
+        Commands::Custom(commands) => {
+            tracing::info!("Starting custom type generation");
+            match crate::commands::custom::handle_custom(commands).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Generation failed: {}", e);
+                    Err(e)
+                }
+            }
+        }
+        Commands::Effective(args) => {
+            tracing::info!("Starting effective configuration resolution");
+            match crate::commands::effective::handle_effective(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Resolution failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
         Commands::Generate(commands) => {
             tracing::info!("Starting schema/template generation");
             match crate::commands::generate::handle_generate(commands).await {
@@ -64,5 +91,15 @@ pub async fn run() -> Result<()> {
                 }
             }
         }
+        Commands::Validate(args) => {
+            tracing::info!("Starting configuration validation");
+            match crate::commands::validate::handle_validate(args).await {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    tracing::error!("Validation failed: {}", e);
+                    Err(miette::Report::new(e))
+                }
+            }
+        }
     }
 }
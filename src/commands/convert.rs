@@ -1,79 +1,111 @@
 //! Convert command implementation for Space Pklr
 //!
 //! This module handles configuration file conversion between formats
-//!.
 
 use clap::Args;
 use miette::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::types::{CliError, SchemaFormat, MoonConfig};
+use crate::types::{CliError, Confidence, SchemaFormat, MoonConfig, TypeInference};
 
 /// Convert command arguments.
 #[derive(Args)]
 pub struct ConvertArgs {
-    /// Moon configuration type (required for type safety)
-    #[arg(long, help = "Configuration type: project, workspace, template, toolchain, task")]
-    pub config_type: MoonConfig,
+    /// Moon configuration type (optional, inferred from the file's name or settings if not provided)
+    #[arg(
+        long,
+        help = "Configuration type: project, workspace, template, toolchain, task, inherited-tasks (inferred if not specified)"
+    )]
+    pub config_type: Option<MoonConfig>,
 
     /// Path to the input configuration file
-    #[arg(short, long, help = "Input configuration file path")]
-    pub input: PathBuf,
+    #[arg(short, long, required_unless_present = "workspace", help = "Input configuration file path")]
+    pub input: Option<PathBuf>,
 
     /// Path to the output file (optional, defaults to stdout)
-    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    #[arg(short, long, help = "Output file path (defaults to stdout); with --workspace, a directory to mirror the projects into")]
     pub output: Option<PathBuf>,
 
     /// Input format (optional, auto-detected if not provided)
-    #[arg(long, help = "Input format (auto-detected if not specified)")]
+    #[arg(long, help = "Input format: yaml, json, toml, pkl (auto-detected if not specified)")]
     pub from: Option<SchemaFormat>,
 
     /// Output format (intelligent defaults applied)
-    #[arg(long, help = "Output format (defaults to json if input is yaml, otherwise yaml)")]
+    #[arg(long, help = "Output format: yaml, json, toml, pkl (defaults to json if input is yaml, otherwise yaml; pkl with --workspace)")]
     pub to: Option<SchemaFormat>,
 
     /// Overwrite existing output file
     #[arg(short, long, help = "Force overwrite of existing output files")]
     pub force: bool,
+
+    /// Convert every project config in a workspace
+    #[arg(
+        long,
+        value_name = "ROOT",
+        num_args = 0..=1,
+        default_missing_value = ".",
+        conflicts_with_all = ["input", "config_type", "from"],
+        help = "Convert every project listed in ROOT/.moon/workspace.yml (ROOT defaults to the current directory)"
+    )]
+    pub workspace: Option<PathBuf>,
+
+    /// Write converted project configs beside the originals
+    #[arg(
+        long,
+        requires = "workspace",
+        conflicts_with = "output",
+        help = "With --workspace, write each converted config beside its original (moon.pkl beside moon.yml)"
+    )]
+    pub beside: bool,
 }
 
 /// Handle convert command execution
 pub async fn handle_convert(args: ConvertArgs) -> Result<(), CliError> {
-    use crate::_rewrite::{load_config, convert_config, ensure_pkl_available};
+    use crate::_rewrite::{detect_config_type, detect_format_from_path, load_config_with_schematic, render_config_with_schematic};
 
+    if let Some(root) = &args.workspace {
+        return handle_workspace_convert(root, &args).await;
+    }
 
     // Validate arguments
     validate_convert_args(&args)?;
+    let Some(input) = &args.input else {
+        unreachable!("clap requires --input without --workspace");
+    };
+
+    let input_format = match &args.from {
+        Some(format) => format.clone(),
+        None => detect_format_from_path(input)?,
+    };
+    let config_type = match args.config_type {
+        Some(config_type) => config_type,
+        None => {
+            let inference = detect_config_type(input, Some(input_format.clone())).await?;
+            report_inference(&inference);
+            inference.config_type
+        }
+    };
 
-    println!("🔄 Converting {} configuration...", args.config_type);
-    println!("📁 Input: {}", args.input.display());
-
-    // Load the configuration file
-    let (content, detected_input_format) = load_config(&args.input, args.config_type, args.from).await?;
+    println!("🔄 Converting {} configuration...", config_type);
+    println!("📁 Input: {}", input.display());
 
     // Apply format defaults with Pkl preferences
-    let output_format = apply_format_defaults_with_pkl(Some(detected_input_format.clone()), args.to);
+    let output_format = apply_format_defaults_with_pkl(Some(input_format.clone()), args.to.clone());
 
-    println!("🔧 Converting from {} to {}", detected_input_format, output_format);
+    println!("🔧 Converting from {} to {}", input_format, output_format);
 
-    // Check if Pkl CLI is needed and available
-    if detected_input_format == SchemaFormat::Pkl || output_format == SchemaFormat::Pkl {
-        match ensure_pkl_available().await {
-            Ok(_) => {
-                println!("✅ Pkl CLI is available");
+    // Parse and validate the configuration; only Pkl that isn't static needs the Pkl CLI
+    let config = load_config_with_schematic(input, config_type, Some(input_format))
+        .await
+        .inspect_err(|error| {
+            if matches!(error, CliError::PcfParseError { .. }) {
+                println!("⚠️  This Pkl needs evaluating, and the Pkl CLI wasn't found. Install it with:");
+                println!("   spklr pkl-me pkl");
             }
-            Err(_) => {
-                println!("⚠️  Pkl CLI not found. To use Pkl conversions, install it with:");
-                println!("   spklr install pkl");
-
-                // For now, proceed with placeholder conversion
-                println!("🔄 Proceeding with basic conversion (full Pkl support requires Pkl CLI)");
-            }
-        }
-    }
+        })?;
 
     // Convert the configuration
-    let converted_content = convert_config(&content, detected_input_format, output_format.clone())?;
+    let converted_content = render_config_with_schematic(&config, output_format)?;
 
     // Write output
     if let Some(output_path) = &args.output {
@@ -101,9 +133,58 @@ pub async fn handle_convert(args: ConvertArgs) -> Result<(), CliError> {
 
     Ok(())
 }
+
+/// Convert every project config in the workspace at `root`
+async fn handle_workspace_convert(root: &Path, args: &ConvertArgs) -> Result<(), CliError> {
+    use crate::workspace::{BatchAction, BatchOutput, find_project_configs, report_failures, run_batch, summary_table};
+
+    let configs = find_project_configs(root).await?;
+    let to = args.to.clone().unwrap_or(SchemaFormat::Pkl);
+    let output = match (&args.output, args.beside) {
+        (Some(dir), _) => BatchOutput::Directory(dir.clone()),
+        (None, true) => BatchOutput::Beside,
+        (None, false) => BatchOutput::None,
+    };
+
+    println!("🔄 Converting {} project configurations to {}...", configs.len(), to);
+    if output == BatchOutput::None {
+        println!("ℹ️  Nothing will be written; pass --beside or --output DIR to keep the results");
+    }
+
+    let outcomes = run_batch(root, configs, BatchAction::Convert { to, output, force: args.force }).await;
+    let summary = summary_table(&outcomes);
+    let total = outcomes.len();
+    let failed = report_failures(outcomes);
+    println!("{summary}");
+
+    if failed > 0 {
+        return Err(CliError::ConversionFailed { failed, total });
+    }
+    Ok(())
+}
+
+/// Say which config type was inferred, and what else it could be when that's unclear
+pub(crate) fn report_inference(inference: &TypeInference) {
+    println!("🔎 Detected {} configuration ({} confidence)", inference.config_type, inference.confidence);
+    if inference.confidence == Confidence::Low {
+        let candidates: Vec<String> = inference.candidates.iter().map(ToString::to_string).collect();
+        println!("⚠️  It could be any of: {}. Pass --config-type to choose.", candidates.join(", "));
+    }
+}
+
+/// Pick the output format: the one asked for, otherwise JSON for YAML input and YAML for everything else
+fn apply_format_defaults_with_pkl(input: Option<SchemaFormat>, output: Option<SchemaFormat>) -> SchemaFormat {
+    output.unwrap_or(match input {
+        Some(SchemaFormat::Yaml) => SchemaFormat::Json,
+        _ => SchemaFormat::Yaml,
+    })
+}
+
 /// Validate conversion arguments
 fn validate_convert_args(args: &ConvertArgs) -> Result<(), CliError> {
-    crate::types::ensure_file_exists(&args.input)?;
+    if let Some(input) = &args.input {
+        crate::types::ensure_file_exists(input)?;
+    }
 
     if let Some(output) = &args.output {
         crate::types::ensure_output_writable(output, args.force)?;
@@ -0,0 +1,110 @@
+//! Custom command implementation for Space Pklr
+//!
+//! This module handles schema and template generation for types that aren't Moon configs
+
+use clap::{Args, Subcommand};
+use miette::Result;
+use std::path::PathBuf;
+
+use crate::types::{SchemaFormat, TemplateFormat, TypeSource};
+
+/// Custom type commands with subcommands.
+#[derive(Subcommand)]
+pub enum CustomCommands {
+    /// Generate a schema for any type
+    Schema(CustomSchemaArgs),
+    /// Generate a template (default) configuration file for any type
+    Template(CustomTemplateArgs),
+}
+
+/// Common arguments for custom subcommands
+#[derive(Args)]
+pub struct CustomArgs {
+    /// File holding the types
+    #[arg(long, value_name = "FILE", help = "File holding the types: a JSON Schema document or a serialized schematic TypeMap")]
+    pub from: PathBuf,
+
+    /// What kind of file `--from` is (defaults to 'json-schema')
+    #[arg(long, default_value = "json-schema", help = "Source kind: json-schema (default), schematic")]
+    pub source: TypeSource,
+
+    /// Root type to generate for (optional, defaults to the struct no other type references)
+    #[arg(long, value_name = "TYPE", help = "Root type name (defaults to the struct no other type references)")]
+    pub root: Option<String>,
+
+    /// Output file path (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+}
+
+/// Custom schema generation arguments
+#[derive(Args)]
+pub struct CustomSchemaArgs {
+    #[command(flatten)]
+    pub common: CustomArgs,
+
+    /// Schema format (defaults to 'pkl')
+    #[arg(long, default_value = "pkl", help = "Schema format: pkl (default), json-schema, typescript")]
+    pub format: SchemaFormat,
+}
+
+/// Custom template generation arguments
+#[derive(Args)]
+pub struct CustomTemplateArgs {
+    #[command(flatten)]
+    pub common: CustomArgs,
+
+    /// Output configuration format (defaults to 'pkl')
+    #[arg(long, default_value = "pkl", help = "Configuration format: pkl (default), yaml, json, jsonc, toml")]
+    pub format: TemplateFormat,
+}
+
+/// Handle custom command execution
+pub async fn handle_custom(commands: CustomCommands) -> Result<()> {
+    match commands {
+        CustomCommands::Schema(args) => handle_custom_schema(args).await,
+        CustomCommands::Template(args) => handle_custom_template(args).await,
+    }
+}
+
+/// Handle schema generation for an arbitrary type map
+pub async fn handle_custom_schema(args: CustomSchemaArgs) -> Result<()> {
+    use crate::custom_types::{load_type_map, render_schema};
+    use crate::types::ensure_file_exists;
+
+    ensure_file_exists(&args.common.from)?;
+    println!("🔧 Generating {} schema from {}...", args.format, args.common.from.display());
+
+    let types = load_type_map(&args.common.from, args.common.source)?;
+    let content = render_schema(types, args.common.root.as_deref(), &args.format)?;
+    write_output(&args.common, &content, "Schema").await
+}
+
+/// Handle template generation for an arbitrary type map
+pub async fn handle_custom_template(args: CustomTemplateArgs) -> Result<()> {
+    use crate::custom_types::{load_type_map, render_template};
+    use crate::types::ensure_file_exists;
+
+    ensure_file_exists(&args.common.from)?;
+    println!("🔧 Generating {} template from {}...", args.format, args.common.from.display());
+
+    let types = load_type_map(&args.common.from, args.common.source)?;
+    let content = render_template(types, args.common.root.as_deref(), &args.format)?;
+    write_output(&args.common, &content, "Template").await
+}
+
+/// Write generated content to the output file, or stdout without one
+async fn write_output(args: &CustomArgs, content: &str, kind: &str) -> Result<()> {
+    if let Some(output_path) = &args.output {
+        tokio::fs::write(output_path, content)
+            .await
+            .map_err(|e| miette::miette!("Failed to write {} to {}: {}",
+                                       kind.to_lowercase(), output_path.display(), e))?;
+
+        println!("✅ {} generated successfully: {}", kind, output_path.display());
+    } else {
+        println!("{}", content);
+    }
+
+    Ok(())
+}
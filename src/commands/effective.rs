@@ -0,0 +1,118 @@
+//! Effective command implementation for Space Pklr
+//!
+//! This module shows a Moon config with everything it extends and inherits merged in
+
+use clap::Args;
+use miette::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::_rewrite::{detect_config_type, render_config_with_schematic};
+use crate::commands::convert::report_inference;
+use crate::effective::{EffectiveOptions, find_workspace_root, resolve_effective_config};
+use crate::types::{CliError, MoonConfig, SchemaFormat};
+
+/// Effective command arguments.
+#[derive(Args)]
+pub struct EffectiveArgs {
+    /// Path to the configuration file
+    #[arg(short, long, help = "Configuration file path")]
+    pub input: PathBuf,
+
+    /// Moon configuration type (optional, inferred from the file's name or settings if not provided)
+    #[arg(
+        long,
+        help = "Configuration type: project, workspace, template, toolchain, task, inherited-tasks (inferred if not specified)"
+    )]
+    pub config_type: Option<MoonConfig>,
+
+    /// Output format
+    #[arg(long, default_value = "yaml", help = "Output format: yaml, json, toml, pkl")]
+    pub to: SchemaFormat,
+
+    /// Path to the output file (optional, defaults to stdout)
+    #[arg(short, long, help = "Output file path (defaults to stdout)")]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite existing output file
+    #[arg(short, long, help = "Force overwrite of existing output files")]
+    pub force: bool,
+
+    /// Workspace root (optional, found from the input's path if not provided)
+    #[arg(long, help = "Workspace root holding .moon/ (found from the input's path if not specified)")]
+    pub workspace_root: Option<PathBuf>,
+
+    /// Local copies of URLs configs extend
+    #[arg(
+        long = "stand-in",
+        value_name = "URL=PATH",
+        value_parser = parse_stand_in,
+        help = "Read PATH wherever a config extends URL; can be repeated"
+    )]
+    pub stand_ins: Vec<(String, PathBuf)>,
+
+    /// Show where each setting came from instead of the config
+    #[arg(long, help = "Show which file each setting and task came from instead of the merged config")]
+    pub explain: bool,
+}
+
+/// Handle effective command execution
+pub async fn handle_effective(args: EffectiveArgs) -> Result<(), CliError> {
+    crate::types::ensure_file_exists(&args.input)?;
+    if let Some(output) = &args.output {
+        crate::types::ensure_output_writable(output, args.force)?;
+    }
+
+    let config_type = match args.config_type {
+        Some(config_type) => config_type,
+        None => {
+            let inference = detect_config_type(&args.input, None).await?;
+            report_inference(&inference);
+            inference.config_type
+        }
+    };
+
+    let options = EffectiveOptions {
+        workspace_root: args.workspace_root.clone(),
+        stand_ins: args.stand_ins.iter().cloned().collect::<HashMap<_, _>>(),
+    };
+    let effective = resolve_effective_config(&args.input, config_type, &options)?;
+
+    let content = if args.explain {
+        let root = options
+            .workspace_root
+            .clone()
+            .or_else(|| find_workspace_root(&args.input))
+            .and_then(|root| std::fs::canonicalize(root).ok())
+            .unwrap_or_default();
+        effective.explain_relative_to(&root)
+    } else {
+        render_config_with_schematic(&effective.config, args.to.clone())?
+    };
+
+    if let Some(output_path) = &args.output {
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| CliError::IoError {
+                context: format!("Creating output directory: {}", parent.display()),
+                source: e,
+            })?;
+        }
+        tokio::fs::write(output_path, content).await.map_err(|e| CliError::IoError {
+            context: format!("Writing output file: {}", output_path.display()),
+            source: e,
+        })?;
+        println!("✅ Wrote the effective {} configuration to {}", config_type, output_path.display());
+    } else {
+        println!("{content}");
+    }
+
+    Ok(())
+}
+
+/// Parse a `URL=PATH` stand-in; URLs can hold `=` themselves, so the path is after the last one
+fn parse_stand_in(value: &str) -> Result<(String, PathBuf), String> {
+    match value.rsplit_once('=') {
+        Some((url, path)) if !url.is_empty() && !path.is_empty() => Ok((url.to_string(), PathBuf::from(path))),
+        _ => Err(format!("expected URL=PATH, got `{value}`")),
+    }
+}
@@ -6,9 +6,10 @@
 use std::str::FromStr;
 use clap::{Args, Subcommand};
 use miette::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use indexmap::IndexMap;
 
-use crate::types::MoonConfig;
+use crate::types::{ModuleLayout, MoonConfig};
 
 /// Generate command with subcommands.
 #[derive(Subcommand)]
@@ -23,7 +24,7 @@ pub enum GenerateCommands {
 #[derive(Args)]
 pub struct GenerateArgs {
     /// Moon configuration type (defaults to 'all')
-    #[arg(long, default_value = "all", help = "Configuration type: project, workspace, template, toolchain, task, all (default)")]
+    #[arg(long, default_value = "all", help = "Configuration type: project, workspace, template, toolchain, task, inherited-tasks, all (default)")]
     pub config_type: MoonConfig,
 
     /// Output directory for multiple files or file path for single output (optional, defaults to stdout)
@@ -37,8 +38,16 @@ pub struct SchemaArgs {
     #[command(flatten)]
     pub common: GenerateArgs,
 
-    #[arg(long, default_value = "all", help = "Schema format: json-schema, typescript, all (default)")]
+    #[arg(long, default_value = "all", help = "Schema format: json-schema, typescript, pkl, all (default)")]
     pub format: String,
+
+    /// Read the schema from a JSON Schema document instead of a Moon configuration type
+    #[arg(long, value_name = "FILE", help = "Generate from a JSON Schema document (draft-07 or 2020-12) instead of a Moon config type")]
+    pub from_json_schema: Option<PathBuf>,
+
+    /// Lay Pkl schemas out as one module, or split them into a package of modules
+    #[arg(long, default_value = "single", help = "Pkl module layout: single (default) or split, a package of modules sharing one Common.pkl")]
+    pub module_layout: ModuleLayout,
 }
 
 /// Template generation arguments
@@ -62,6 +71,13 @@ pub async fn handle_generate(commands: GenerateCommands) -> Result<()> {
 
 /// Handle schema generation using schematic's existing capabilities
 pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
+    if let Some(source) = &args.from_json_schema {
+        return handle_json_schema_generation(source, &args).await;
+    }
+    if args.module_layout == ModuleLayout::Split {
+        return handle_split_schema_generation(&args).await;
+    }
+
     use crate::_rewrite::{generate_schema, generate_all_schemas, generate_all_formats_schema, generate_all_schemas_all_formats};
     use crate::types::MoonConfig;
 
@@ -156,6 +172,89 @@ pub async fn handle_schema_generation(args: SchemaArgs) -> Result<()> {
     Ok(())
 }
 
+/// Handle Pkl schema generation split into a package of modules. With `all`, every config type lands in the one
+/// package, sharing a single `Common.pkl`.
+async fn handle_split_schema_generation(args: &SchemaArgs) -> Result<()> {
+    use crate::custom_types::render_moon_modules;
+
+    ensure_pkl_format(&args.format)?;
+    println!("🔧 Generating {} Pkl schemas as a package of modules...", args.common.config_type);
+    let modules = render_moon_modules(args.common.config_type)?;
+    write_modules(modules, args.common.output.as_deref()).await
+}
+
+/// Only Pkl schemas come from a JSON Schema document or in a split layout, so the format must be `pkl` (or `all`)
+fn ensure_pkl_format(format: &str) -> Result<()> {
+    use crate::types::CliError;
+
+    if !matches!(format.to_lowercase().as_str(), "pkl" | "all") {
+        return Err(CliError::UnsupportedFormat {
+            format: format.to_string(),
+            available: vec!["pkl"],
+        }.into());
+    }
+    Ok(())
+}
+
+/// Write the modules of a package into the output directory, or print them to stdout
+async fn write_modules(modules: IndexMap<String, String>, output: Option<&Path>) -> Result<()> {
+    let Some(output_dir) = output else {
+        for (filename, content) in modules {
+            println!("\n=== {} ===", filename);
+            println!("{}", content);
+        }
+        return Ok(());
+    };
+
+    tokio::fs::create_dir_all(output_dir).await
+        .map_err(|e| miette::miette!("Failed to create output directory {}: {}", output_dir.display(), e))?;
+    for (filename, content) in modules {
+        let file_path = output_dir.join(&filename);
+        tokio::fs::write(&file_path, &content).await
+            .map_err(|e| miette::miette!("Failed to write schema to {}: {}", file_path.display(), e))?;
+        println!("✅ Generated: {}", file_path.display());
+    }
+    Ok(())
+}
+
+/// Handle schema generation from a JSON Schema document, rendered to Pkl with [`PklSchemaRenderer`](crate::pkl_renderer::PklSchemaRenderer)
+async fn handle_json_schema_generation(source: &Path, args: &SchemaArgs) -> Result<()> {
+    use crate::custom_types::{render_schema, render_schema_modules};
+    use crate::json_schema_importer::import_json_schema_file;
+    use crate::types::{CliError, SchemaFormat, ensure_file_exists};
+
+    ensure_pkl_format(&args.format)?;
+    ensure_file_exists(source)?;
+
+    println!("🔧 Generating Pkl schema from {}...", source.display());
+    let schemas = import_json_schema_file(source)?;
+    if schemas.is_empty() {
+        return Err(CliError::SchemaImportFailed {
+            source_name: source.display().to_string(),
+            reason: "the document doesn't define any types".to_string(),
+        }.into());
+    }
+    if args.module_layout == ModuleLayout::Split {
+        let modules = render_schema_modules(schemas, None)?;
+        return write_modules(modules, args.common.output.as_deref()).await;
+    }
+    let schema_content = render_schema(schemas, None, &SchemaFormat::Pkl)?;
+
+    // Output to file or stdout
+    if let Some(output_path) = &args.common.output {
+        tokio::fs::write(output_path, &schema_content)
+            .await
+            .map_err(|e| miette::miette!("Failed to write schema to {}: {}",
+                                       output_path.display(), e))?;
+
+        println!("✅ Schema generated successfully: {}", output_path.display());
+    } else {
+        println!("{}", schema_content);
+    }
+
+    Ok(())
+}
+
 /// Handle template configuration generation using existing templates and defaults
 pub async fn handle_template_generation(args: TemplateArgs) -> Result<()> {
     use crate::_rewrite::{generate_template, generate_all_templates, generate_all_formats_template, generate_all_templates_all_formats};
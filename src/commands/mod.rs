@@ -3,8 +3,11 @@
 //! This module contains all command implementations as specified in
 
 pub mod convert;
+pub mod custom;
+pub mod effective;
 pub mod generate;
 pub mod pklme;
+pub mod validate;
 
 // Re-export command structures for easier access
 
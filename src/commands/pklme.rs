@@ -1,43 +1,299 @@
 //! Install command implementation for Space Pklr
 //!
-//! This module handles installation of external tools like Pkl CLI
-//!.
+//! This module handles installation of external tools like Pkl CLI, and managing the installed
+//! Pkl CLI versions.
 
 use clap::{Args, Subcommand};
 use miette::Result;
 
+use crate::pkl_versions::{PklVersions, VersionRequest, VersionSource, find_pinned_version, pin_version};
+use crate::types::CliError;
+
 /// Install command with subcommands.
 #[derive(Subcommand)]
 pub enum InstallCommands {
     /// Install Pkl CLI
     Pkl(PklInstallArgs),
+    /// List the installed Pkl CLI versions
+    List,
+    /// Make an installed Pkl CLI version the default, or pin it for this project
+    Use(PklUseArgs),
+    /// Remove an installed Pkl CLI version
+    Uninstall(PklVersionArgs),
+    /// Show which Pkl CLI is used here, and why
+    Which,
+    /// Check that a Pkl CLI works with the Moon schemas
+    Check(PklCheckArgs),
 }
 
 /// Pkl installation arguments
 #[derive(Args)]
 pub struct PklInstallArgs {
-    /// Specific version to install (defaults to recommended version)
+    /// Specific version or range to install (defaults to recommended version)
     #[arg(
         long,
-        help = "Pkl version to install (defaults to tested compatible version)"
+        help = "Pkl version, range like ^0.28, or latest-compatible to install (defaults to tested compatible version)"
     )]
     pub version: Option<String>,
 
     /// Force reinstallation even if already installed
     #[arg(short, long, help = "Force reinstallation")]
     pub force: bool,
+
+    /// Expected SHA-256 of the downloaded archive
+    #[arg(
+        long,
+        value_name = "SHA256",
+        help = "SHA-256 of the release archive, for versions without a known checksum"
+    )]
+    pub checksum: Option<String>,
+
+    /// Base URL to download Pkl release archives from
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Download from this mirror of the Pkl GitHub releases (or set SPKLR_PKL_MIRROR)"
+    )]
+    pub mirror: Option<String>,
+
+    /// Release archive to install instead of downloading one
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with = "mirror",
+        help = "Install from a Pkl release archive already on disk"
+    )]
+    pub from_archive: Option<std::path::PathBuf>,
+}
+
+/// Pkl version arguments
+#[derive(Args)]
+pub struct PklVersionArgs {
+    /// An installed Pkl version, as listed by `spklr pkl-me list`
+    pub version: String,
+}
+
+/// Arguments for choosing the Pkl version to use
+#[derive(Args)]
+pub struct PklUseArgs {
+    /// An installed Pkl version, as listed by `spklr pkl-me list`, or a range like `0.28`; pins may also be
+    /// `latest-compatible`
+    pub version: String,
+
+    /// Pin the version for this project instead of making it the default
+    #[arg(
+        long,
+        help = "Write the version to .pkl-version in the current directory"
+    )]
+    pub pin: bool,
+}
+
+/// Compatibility check arguments
+#[derive(Args)]
+pub struct PklCheckArgs {
+    /// An installed Pkl version to check instead of the one used here
+    #[arg(long, help = "Installed Pkl version to check (defaults to the one used here)")]
+    pub version: Option<String>,
+
+    /// Print the report as JSON
+    #[arg(long, help = "Print the compatibility report as JSON")]
+    pub json: bool,
 }
 
 /// Handle install command execution
 ///
-/// - Dispatch to appropriate tool installation handler
-/// - Currently only supports Pkl CLI installation
+/// - Dispatch to appropriate tool installation or version management handler
 pub async fn handle_install(commands: InstallCommands) -> Result<()> {
     match commands {
         InstallCommands::Pkl(args) => handle_pkl_installation(args).await,
+        InstallCommands::List => handle_list(),
+        InstallCommands::Use(args) => handle_use(args),
+        InstallCommands::Uninstall(args) => handle_uninstall(args),
+        InstallCommands::Which => handle_which().await,
+        InstallCommands::Check(args) => handle_check(args).await,
     }
 }
 
+/// List the spklr-installed versions, marking the default and the one pinned here
+fn handle_list() -> Result<()> {
+    let versions = PklVersions::open()?;
+    let installed = versions.installed()?;
+    if installed.is_empty() {
+        println!(
+            "No Pkl CLI versions installed in {}",
+            versions.tools_dir().display()
+        );
+        println!("   Install one with: spklr pkl-me pkl");
+        return Ok(());
+    }
+
+    let default = versions.default_version()?;
+    let pinned = find_pinned_version(&current_dir()?)?;
+    for pkl in installed {
+        let mut notes = Vec::new();
+        if default.as_deref() == Some(pkl.version.as_str()) {
+            notes.push(VersionSource::Default.to_string());
+        }
+        if let Some(pinned) = pinned
+            .as_ref()
+            .filter(|pinned| pinned.version == pkl.version)
+        {
+            notes.push(pinned.source.to_string());
+        }
+        if notes.is_empty() {
+            println!("  {}", pkl.version);
+        } else {
+            println!("* {} ({})", pkl.version, notes.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Make a version the default, or pin it for the current directory
+fn handle_use(args: PklUseArgs) -> Result<()> {
+    let versions = PklVersions::open()?;
+    let request: VersionRequest = args.version.parse()?;
+    if args.pin {
+        // pinning a version that isn't installed yet is fine; `spklr pkl-me pkl` installs it
+        let file = pin_version(&current_dir()?, &args.version)?;
+        println!("📌 Pinned Pkl CLI {} in {}", args.version, file.display());
+        if versions.find(&request)?.is_none() {
+            println!("   It isn't installed yet; install it with: spklr pkl-me pkl");
+        }
+    } else {
+        // the default is always one installed version, the newest a range accepts
+        let installed = versions
+            .find(&request)?
+            .ok_or(CliError::PklVersionNotInstalled {
+                version: args.version,
+            })?;
+        let pkl = versions.set_default(&installed.version)?;
+        println!(
+            "✅ Pkl CLI {} is now the default ({})",
+            pkl.version,
+            pkl.path.display()
+        );
+        if let Some(pinned) = find_pinned_version(&current_dir()?)?
+            && !pinned.request()?.matches(&pkl.version)
+        {
+            println!(
+                "   Here, Pkl CLI {} is still used ({})",
+                pinned.version, pinned.source
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Remove an installed version
+fn handle_uninstall(args: PklVersionArgs) -> Result<()> {
+    let pkl = PklVersions::open()?.uninstall(&args.version)?;
+    println!(
+        "🗑️  Removed Pkl CLI {} from {}",
+        pkl.version,
+        pkl.dir.display()
+    );
+    Ok(())
+}
+
+/// Show the Pkl CLI the other commands would run here
+async fn handle_which() -> Result<()> {
+    let dir = current_dir()?;
+    let requested = PklVersions::open()?.requested_version(&dir)?;
+    match crate::pkl_tooling::find_pkl_executable_in(&dir).await? {
+        Some(pkl_cli) => {
+            println!("{}", pkl_cli.path.display());
+            if let Some(version) = &pkl_cli.version {
+                println!("   Version: {}", version);
+            }
+            println!("   Source: {:?}", pkl_cli.source);
+            if let Some(requested) = requested {
+                println!("   Chosen because: {} ({})", requested.source, requested.version);
+            }
+            if let Some(warning) = pkl_cli
+                .version
+                .as_deref()
+                .and_then(crate::pkl_tooling::untested_pkl_version_warning)
+            {
+                println!("⚠️  {}", warning);
+            }
+            Ok(())
+        }
+        None => Err(match requested {
+            Some(requested) => {
+                let context = format!("Pkl CLI {} is {}", requested.version, requested.source);
+                miette::Report::new(CliError::PklVersionNotInstalled {
+                    version: requested.version,
+                })
+                .wrap_err(context)
+            }
+            None => miette::Report::new(CliError::PklInstallFailed {
+                reason: "No Pkl CLI found".to_string(),
+                help: Some("Install Pkl CLI with: spklr pkl-me pkl".to_string()),
+            }),
+        }),
+    }
+}
+
+/// Run the compatibility suite and report each feature, failing if any check did
+async fn handle_check(args: PklCheckArgs) -> Result<()> {
+    let pkl_cli = match args.version {
+        Some(version) => {
+            let pkl = PklVersions::open()?
+                .get(&version)
+                .ok_or(CliError::PklVersionNotInstalled { version })?;
+            crate::pkl_tooling::PklCli {
+                path: pkl.path,
+                source: crate::pkl_tooling::PklSource::Manual(pkl.dir),
+                version: Some(pkl.version),
+            }
+        }
+        None => crate::pkl_tooling::find_pkl_executable()
+            .await?
+            .ok_or_else(|| CliError::PklInstallFailed {
+                reason: "No Pkl CLI found".to_string(),
+                help: Some("Install Pkl CLI with: spklr pkl-me pkl".to_string()),
+            })?,
+    };
+
+    let report = crate::pkl_tooling::validate_pkl_compatibility(&pkl_cli).await?;
+    if args.json {
+        println!("{}", report.to_json()?);
+    } else {
+        println!("Pkl CLI {} ({})", report.pkl_version, pkl_cli.path.display());
+        for result in &report.features {
+            if result.passed {
+                println!("  ✅ {}", result.feature);
+            } else {
+                println!("  ❌ {}", result.feature);
+                for line in result.error.as_deref().unwrap_or_default().lines() {
+                    println!("       {}", line);
+                }
+            }
+        }
+    }
+
+    let failed = report.failures().count();
+    if failed > 0 {
+        return Err(miette::Report::new(CliError::Generic(format!(
+            "Pkl CLI {} failed {} of {} compatibility checks",
+            report.pkl_version,
+            failed,
+            report.features.len()
+        ))));
+    }
+    Ok(())
+}
+
+fn current_dir() -> Result<std::path::PathBuf> {
+    std::env::current_dir().map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: "Reading the current directory".to_string(),
+            source: e,
+        })
+    })
+}
+
 /// Handle Pkl CLI installation
 ///
 /// - Use pkl_tooling module for installation logic
@@ -45,9 +301,15 @@ pub async fn handle_install(commands: InstallCommands) -> Result<()> {
 /// - Handle force reinstallation
 /// - Provide progress indicators and clear feedback
 pub async fn handle_pkl_installation(args: PklInstallArgs) -> Result<()> {
-    let version = args
-        .version
-        .unwrap_or_else(|| crate::pkl_tooling::get_recommended_pkl_version().to_string());
+    // an explicit version, else the one pinned here, else the recommended one
+    let version = match args.version {
+        Some(version) => version,
+        None => find_pinned_version(&current_dir()?)?
+            .map(|pinned| pinned.version)
+            .unwrap_or_else(|| crate::pkl_tooling::get_recommended_pkl_version().to_string()),
+    };
+
+    let request: VersionRequest = version.parse()?;
 
     display_installation_progress(&format!(
         "Starting Pkl CLI installation (version: {})",
@@ -58,12 +320,12 @@ pub async fn handle_pkl_installation(args: PklInstallArgs) -> Result<()> {
         println!("🔄 Force flag enabled - will reinstall if already present");
     }
 
-    // Check existing installation if not forcing
-    if !args.force {
+    // Check existing installation if not forcing; a local archive is always installed
+    if !args.force && args.from_archive.is_none() {
         display_installation_progress("Checking for existing Pkl installation...");
         if let Ok(Some(existing_pkl)) = crate::pkl_tooling::find_pkl_executable().await {
             if let Some(existing_version) = &existing_pkl.version {
-                if existing_version == &version {
+                if request.installs(existing_version) {
                     println!(
                         "✅ Pkl CLI version {} already installed at: {}",
                         existing_version,
@@ -88,23 +350,34 @@ pub async fn handle_pkl_installation(args: PklInstallArgs) -> Result<()> {
 
     // Perform installation
     display_installation_progress(&format!("Installing Pkl CLI version {}...", version));
-    let pkl_cli = crate::pkl_tooling::install_pkl(Some(version.clone())).await?;
+    let options = crate::pkl_tooling::PklInstallOptions {
+        checksum: args.checksum,
+        mirror: args.mirror,
+        from_archive: args.from_archive,
+        ..Default::default()
+    };
+    let pkl_cli = crate::pkl_tooling::install_pkl(Some(version), &options).await?;
 
     // Validate installation
     display_installation_progress("Validating installation...");
     let is_valid = crate::pkl_tooling::validate_pkl_installation(&pkl_cli).await?;
 
     if is_valid {
-        display_installation_success("Pkl CLI", &pkl_cli.path, Some(&version));
+        display_installation_success("Pkl CLI", &pkl_cli.path, pkl_cli.version.as_deref());
         println!("   Source: {:?}", pkl_cli.source);
         println!("   You can now use Pkl conversions in the convert command");
+        if let Some(warning) = pkl_cli
+            .version
+            .as_deref()
+            .and_then(crate::pkl_tooling::untested_pkl_version_warning)
+        {
+            println!("⚠️  {}", warning);
+        }
     } else {
-        return Err(miette::Report::new(
-            crate::types::CliError::PklInstallFailed {
-                reason: "Installation validation failed".to_string(),
-                help: Some("Try reinstalling or check installation manually".to_string()),
-            },
-        ));
+        return Err(miette::Report::new(CliError::PklInstallFailed {
+            reason: "Installation validation failed".to_string(),
+            help: Some("Try reinstalling or check installation manually".to_string()),
+        }));
     }
 
     Ok(())
@@ -0,0 +1,111 @@
+//! Validate command implementation for Space Pklr
+//!
+//! This module checks Moon configuration files against their schemas
+
+use clap::Args;
+use miette::Result;
+use std::path::{Path, PathBuf};
+
+use crate::_rewrite::detect_config_type;
+use crate::commands::convert::report_inference;
+use crate::config_validator::validate_config_file_with_evaluator;
+use crate::pkl_evaluator::SharedEvaluator;
+use crate::types::{CliError, MoonConfig, SchemaFormat};
+
+/// Validate command arguments.
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Configuration files to validate
+    #[arg(required_unless_present = "workspace", help = "Configuration files to validate")]
+    pub inputs: Vec<PathBuf>,
+
+    /// Moon configuration type (optional, inferred from each file's name or settings if not provided)
+    #[arg(
+        long,
+        help = "Configuration type: project, workspace, template, toolchain, task, inherited-tasks (inferred if not specified)"
+    )]
+    pub config_type: Option<MoonConfig>,
+
+    /// Input format (optional, auto-detected if not provided)
+    #[arg(long, help = "Input format: yaml, json, toml, pkl (auto-detected if not specified)")]
+    pub from: Option<SchemaFormat>,
+
+    /// Validate every project config in a workspace
+    #[arg(
+        long,
+        value_name = "ROOT",
+        num_args = 0..=1,
+        default_missing_value = ".",
+        conflicts_with_all = ["inputs", "config_type", "from"],
+        help = "Validate every project listed in ROOT/.moon/workspace.yml (ROOT defaults to the current directory)"
+    )]
+    pub workspace: Option<PathBuf>,
+}
+
+/// Handle validate command execution
+///
+/// Every file is checked; each failure is reported as it's found, and the command fails if any did.
+pub async fn handle_validate(args: ValidateArgs) -> Result<(), CliError> {
+    if let Some(root) = &args.workspace {
+        return handle_workspace_validate(root).await;
+    }
+
+    let total = args.inputs.len();
+    let mut failed = 0;
+    let evaluator = SharedEvaluator::new();
+
+    for input in &args.inputs {
+        // a missing file is one more failure, not the end of the batch
+        let validated = async {
+            crate::types::ensure_file_exists(input)?;
+            let config_type = match args.config_type {
+                Some(config_type) => config_type,
+                None => {
+                    let inference = detect_config_type(input, args.from.clone()).await?;
+                    report_inference(&inference);
+                    inference.config_type
+                }
+            };
+            validate_config_file_with_evaluator(input, Some(config_type), args.from.clone(), Some(&evaluator)).await
+        };
+
+        match validated.await {
+            Ok(config) => println!("✅ {} is a valid {} configuration", input.display(), config.config_type_name(None)),
+            Err(error) => {
+                failed += 1;
+                println!("❌ {}", input.display());
+                eprintln!("{:?}", miette::Report::new(error));
+            }
+        }
+    }
+
+    if let Err(error) = evaluator.close().await {
+        tracing::debug!("Closing pkl server failed: {}", error);
+    }
+
+    if failed > 0 {
+        return Err(CliError::ValidationFailed { failed, total });
+    }
+
+    println!("🎉 {} configuration file{} valid", total, if total == 1 { " is" } else { "s are" });
+    Ok(())
+}
+
+/// Validate every project config in the workspace at `root`
+async fn handle_workspace_validate(root: &Path) -> Result<(), CliError> {
+    use crate::workspace::{BatchAction, find_project_configs, report_failures, run_batch, summary_table};
+
+    let configs = find_project_configs(root).await?;
+    println!("🔍 Validating {} project configurations...", configs.len());
+
+    let outcomes = run_batch(root, configs, BatchAction::Validate).await;
+    let summary = summary_table(&outcomes);
+    let total = outcomes.len();
+    let failed = report_failures(outcomes);
+    println!("{summary}");
+
+    if failed > 0 {
+        return Err(CliError::ValidationFailed { failed, total });
+    }
+    Ok(())
+}
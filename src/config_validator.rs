@@ -0,0 +1,145 @@
+//! Config Validator
+//!
+//! Checks Moon configuration files against their `moon_config` types. Loading already validates, but
+//! schematic's errors only name a setting's path; here each problem becomes a [`ConfigProblem`] whose span
+//! covers that setting's key in the file, so miette can show it in context.
+//!
+//! ```rust,ignore
+//! use space_pklr::config_validator::validate_config_file;
+//!
+//! if let Err(error) = validate_config_file(Path::new("moon.yml"), None, None).await {
+//!     eprintln!("{:?}", miette::Report::new(error));
+//! }
+//! ```
+//!
+//! Keys are found by searching the source for each part of the path in turn, which works for YAML, JSON, TOML
+//! and Pkl alike. When a key isn't there (a required setting that's missing, say), the problem points at the
+//! closest parent that is.
+
+use std::path::Path;
+
+use miette::{NamedSource, SourceSpan};
+use regex::Regex;
+use schematic::ConfigError;
+
+use crate::_rewrite::{detect_config_type, evaluate_pkl_to_json, load_config, parse_config};
+use crate::pkl_evaluator::SharedEvaluator;
+use crate::types::{CliError, ConfigProblem, LoadedConfig, MoonConfig, SchemaFormat};
+
+/// Validate config source, returning the loaded config or a [`CliError::InvalidConfig`] listing every problem
+pub fn validate_config(
+    source_name: &str,
+    content: &str,
+    config_type: MoonConfig,
+    format: &SchemaFormat,
+) -> Result<LoadedConfig, CliError> {
+    parse_config(source_name, content, config_type, format).map_err(|error| {
+        let CliError::ValidationError { source } = &error else {
+            return error;
+        };
+        let Some(config_error) = source.downcast_ref::<ConfigError>() else {
+            return error;
+        };
+        CliError::InvalidConfig {
+            source_name: source_name.to_string(),
+            config_type,
+            problems: config_problems(config_error, source_name, content, format),
+        }
+    })
+}
+
+/// Validate a config file. Unless given, the type is inferred from the file's name or settings and the format
+/// from its extension.
+///
+/// Pkl that isn't static is evaluated with the Pkl CLI when one is installed; its problems then point into the
+/// evaluated JSON.
+pub async fn validate_config_file(
+    path: &Path,
+    config_type: Option<MoonConfig>,
+    format: Option<SchemaFormat>,
+) -> Result<LoadedConfig, CliError> {
+    validate_config_file_with_evaluator(path, config_type, format, None).await
+}
+
+/// Like [`validate_config_file`], but Pkl that isn't static is evaluated with `evaluator` when one is given
+pub async fn validate_config_file_with_evaluator(
+    path: &Path,
+    config_type: Option<MoonConfig>,
+    format: Option<SchemaFormat>,
+    evaluator: Option<&SharedEvaluator>,
+) -> Result<LoadedConfig, CliError> {
+    let config_type = match config_type {
+        Some(config_type) => config_type,
+        None => detect_config_type(path, format.clone()).await?.config_type,
+    };
+    let (content, format) = load_config(path, config_type, format).await?;
+    let source_name = path.display().to_string();
+
+    match validate_config(&source_name, &content, config_type, &format) {
+        Err(error @ CliError::PcfParseError { .. }) => {
+            let Some(json) = evaluate_pkl_to_json(path, evaluator).await else {
+                return Err(error);
+            };
+            validate_config(&format!("{source_name} (evaluated)"), &json?, config_type, &SchemaFormat::Json)
+        }
+        result => result,
+    }
+}
+
+/// One problem for each setting schematic complains about
+fn config_problems(error: &ConfigError, source_name: &str, content: &str, format: &SchemaFormat) -> Vec<ConfigProblem> {
+    let problem = |path: String, message: String, span: Option<SourceSpan>| ConfigProblem {
+        span: span.unwrap_or_else(|| locate_setting(content, &path)),
+        src: NamedSource::new(source_name, content.to_string()),
+        path,
+        message,
+    };
+
+    match error {
+        ConfigError::Parser { error, .. } => {
+            // the parser's own span is kept for syntax errors, which have no path. Pkl is loaded from JSON, so
+            // only spans into YAML, JSON and TOML sources fit
+            if error.path.is_empty() || error.path == "." {
+                let span = error.span.filter(|_| *format != SchemaFormat::Pkl);
+                vec![problem(".".to_string(), error.message.clone(), span)]
+            } else {
+                vec![problem(error.path.clone(), error.message.clone(), None)]
+            }
+        }
+        ConfigError::Validator { error, .. } => error
+            .errors
+            .iter()
+            .map(|error| problem(error.path.to_string(), error.message.clone(), None))
+            .collect(),
+        error => vec![problem(".".to_string(), error.to_string(), None)],
+    }
+}
+
+/// Find the key a dotted setting path (`tasks.build.deps[0]`) names in config source. Indexes are skipped, and
+/// the search for each key starts after the one before it.
+pub fn locate_setting(content: &str, path: &str) -> SourceSpan {
+    let keys = path
+        .split('.')
+        .map(|part| part.split('[').next().unwrap_or_default())
+        .filter(|key| !key.is_empty() && *key != "?");
+
+    let mut span = SourceSpan::from(0..0);
+    let mut offset = 0;
+    for key in keys {
+        let key = regex::escape(key);
+        // a key, bare or quoted, as YAML, JSON, TOML (including `[table]` and dotted keys) or Pkl writes it
+        let pattern = format!(
+            r#"(?m)(?:^|[\s{{,\[.;-])\[?\s*("{key}"|'{key}'|`{key}`|{key})\s*\]?\s*[:={{.\]]"#
+        );
+        let Some(found) = Regex::new(&pattern)
+            .ok()
+            .and_then(|regex| regex.captures(&content[offset..]))
+            .and_then(|captures| captures.get(1))
+        else {
+            break;
+        };
+        span = SourceSpan::from(offset + found.start()..offset + found.end());
+        offset += found.end();
+    }
+    span
+}
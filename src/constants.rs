@@ -1,7 +1,7 @@
-const DATA_SIZE_UNITS: [&str; 11] = [
+pub(crate) const DATA_SIZE_UNITS: [&str; 11] = [
     "b", "kb", "kib", "mb", "mib", "gb", "gib", "tb", "tib", "pb", "pib"
 ];
 
-const DURATION_UNITS: [&str; 7] = [
+pub(crate) const DURATION_UNITS: [&str; 7] = [
     "ns", "us", "ms", "s", "m", "h", "d"
 ];
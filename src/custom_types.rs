@@ -0,0 +1,210 @@
+//! Custom Types
+//!
+//! Schema and template generation for types that aren't Moon configs. Everything here works on a plain
+//! [`TypeMap`], which can come from:
+//!
+//! - a JSON Schema document ([`TypeSource::JsonSchema`]),
+//! - a `TypeMap` serialized by schematic, as JSON or YAML ([`TypeSource::Schematic`]), or
+//! - any Rust type that implements [`Schematic`], through [`type_map_of`].
+//!
+//! Unless you name it, the root type is chosen with [`LoadedConfig::attempt_to_resolve_name`] -- the struct
+//! nothing else references. It's then moved to the end of the map, which is where schematic's renderers
+//! look for it.
+//!
+//! ```rust,ignore
+//! use space_pklr::custom_types::{render_schema, type_map_of};
+//! use space_pklr::SchemaFormat;
+//!
+//! let pkl = render_schema(type_map_of::<MyConfig>(), None, &SchemaFormat::Pkl)?;
+//! ```
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use schematic::Schematic;
+use schematic::schema::{
+    JsonSchemaRenderer, JsoncTemplateRenderer, JsonTemplateRenderer, PklTemplateRenderer, RenderResult,
+    SchemaGenerator, SchemaRenderer, TemplateOptions, TomlTemplateRenderer, TypeScriptRenderer,
+    YamlTemplateRenderer,
+};
+use serde_json::Value;
+
+use crate::json_schema_importer::import_json_schema_file;
+use crate::pkl_renderer::{PklSchemaOptions, PklSchemaRenderer};
+use crate::types::moon::UnknownConfig;
+use crate::types::{CliError, LoadedConfig, ModuleLayout, MoonConfig, Result, SchemaFormat, TemplateFormat, TypeMap, TypeSource};
+
+/// Builds the [`TypeMap`] for a Rust type, with the type itself as the root (last) entry.
+pub fn type_map_of<T: Schematic>() -> TypeMap {
+    let mut generator = SchemaGenerator::default();
+    generator.add::<T>();
+    generator.schemas
+}
+
+/// Loads a [`TypeMap`] from a file of the given kind.
+pub fn load_type_map(path: &Path, source: TypeSource) -> Result<TypeMap> {
+    match source {
+        TypeSource::JsonSchema => import_json_schema_file(path),
+        TypeSource::Schematic => {
+            let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+                context: format!("Reading type map: {}", path.display()),
+                source: e,
+            })?;
+            let is_yaml = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext, "yaml" | "yml"));
+            let source_name = path.display().to_string();
+            if is_yaml {
+                serde_yaml::from_str(&content).map_err(|e| CliError::SchemaImportFailed {
+                    source_name,
+                    reason: e.to_string(),
+                })
+            } else {
+                import_schematic_type_map(&source_name, &content)
+            }
+        }
+    }
+}
+
+/// Parses a `TypeMap` that schematic serialized to JSON (with its `schema_serde` feature).
+pub fn import_schematic_type_map(source_name: &str, source: &str) -> Result<TypeMap> {
+    serde_json::from_str(source).map_err(|e| CliError::SchemaImportFailed {
+        source_name: source_name.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Picks the root type -- `root` if given, otherwise whatever [`LoadedConfig::attempt_to_resolve_name`]
+/// settles on -- and moves it to the end of the map. Returns the root's name with the reordered map.
+pub fn resolve_root(mut types: TypeMap, root: Option<&str>) -> Result<(String, TypeMap)> {
+    let name = match root {
+        Some(root) => root.to_string(),
+        None => LoadedConfig::Unknown(UnknownConfig::default()).attempt_to_resolve_name(Some(types.clone())),
+    };
+    let Some(index) = types.get_index_of(&name) else {
+        return Err(CliError::RootTypeNotFound {
+            name,
+            available: types.keys().cloned().collect(),
+        });
+    };
+    let last = types.len() - 1;
+    types.move_index(index, last);
+    Ok((name, types))
+}
+
+/// Renders a schema for the root type (and everything it uses) as Pkl, JSON Schema or TypeScript.
+pub fn render_schema(types: TypeMap, root: Option<&str>, format: &SchemaFormat) -> Result<String> {
+    render_schema_with_defaults(types, root, format, IndexMap::new())
+}
+
+/// Renders the schema of a Moon config type, like [`render_schema`], with the contents of Moon's
+/// `Listing` and `Mapping` defaults (e.g. `vcs.remoteCandidates`) filled in from its default instance.
+pub fn render_moon_schema(config_type: MoonConfig, format: &SchemaFormat) -> Result<String> {
+    let Some((types, root)) = config_type.schema_types() else {
+        return Err(CliError::Generic(
+            "Cannot render a schema for 'All' - specify a specific config type".to_string(),
+        ));
+    };
+    let default_values = config_type.default_value().map(|value| (root.to_string(), value)).into_iter().collect();
+    render_schema_with_defaults(types, Some(root), format, default_values)
+}
+
+/// Renders the root type's Pkl schema split into a package of modules, keyed by file name (see
+/// [`PklSchemaRenderer::render_modules`]).
+pub fn render_schema_modules(types: TypeMap, root: Option<&str>) -> Result<IndexMap<String, String>> {
+    let (root_name, types) = resolve_root(types, root)?;
+    PklSchemaRenderer::new(PklSchemaOptions {
+        config_name: LoadedConfig::Unknown(UnknownConfig::named(&root_name)),
+        module_layout: ModuleLayout::Split,
+        ..Default::default()
+    })
+    .render_modules(types)
+    .map_err(|e| CliError::RenderError {
+        config_type: root_name,
+        format: SchemaFormat::Pkl,
+        source: e.into(),
+    })
+}
+
+/// Renders the Pkl schemas of a Moon config type, or of every one for `All`, as one package keyed by file
+/// name: a module per config type, with the types they share in a single `Common.pkl`.
+pub fn render_moon_modules(config_type: MoonConfig) -> Result<IndexMap<String, String>> {
+    let config_types = match config_type {
+        MoonConfig::All => MoonConfig::all_types(),
+        config_type => vec![config_type],
+    };
+    let mut roots = Vec::new();
+    let mut default_values = IndexMap::new();
+    for config_type in config_types {
+        let Some((types, root)) = config_type.schema_types() else {
+            continue;
+        };
+        if let Some(value) = config_type.default_value() {
+            default_values.insert(root.to_string(), value);
+        }
+        roots.push((LoadedConfig::Unknown(UnknownConfig::named(root)), types));
+    }
+    PklSchemaRenderer::new(PklSchemaOptions {
+        default_values,
+        module_layout: ModuleLayout::Split,
+        ..Default::default()
+    })
+    .render_package(roots)
+    .map_err(|e| CliError::RenderError {
+        config_type: config_type.to_string(),
+        format: SchemaFormat::Pkl,
+        source: e.into(),
+    })
+}
+
+/// Renders a schema, with [`PklSchemaOptions::default_values`] for Pkl.
+fn render_schema_with_defaults(
+    types: TypeMap,
+    root: Option<&str>,
+    format: &SchemaFormat,
+    default_values: IndexMap<String, Value>,
+) -> Result<String> {
+    let (root_name, types) = resolve_root(types, root)?;
+    let rendered = match format {
+        SchemaFormat::Pkl => PklSchemaRenderer::new(PklSchemaOptions {
+            config_name: LoadedConfig::Unknown(UnknownConfig::named(&root_name)),
+            default_values,
+            ..Default::default()
+        })
+        .render(types),
+        SchemaFormat::Json => JsonSchemaRenderer::default().render(types),
+        SchemaFormat::Typescript => TypeScriptRenderer::default().render(types),
+        SchemaFormat::Yaml | SchemaFormat::Toml => {
+            return Err(CliError::UnsupportedFormat {
+                format: format.to_string(),
+                available: vec!["pkl", "json", "typescript"],
+            });
+        }
+    };
+    rendered.map_err(|e| CliError::RenderError {
+        config_type: root_name,
+        format: format.clone(),
+        source: e.into(),
+    })
+}
+
+/// Renders a template (a config filled in with defaults) for the root type, which must be a struct.
+pub fn render_template(types: TypeMap, root: Option<&str>, format: &TemplateFormat) -> Result<String> {
+    let (root_name, types) = resolve_root(types, root)?;
+    let options = TemplateOptions::default();
+    let rendered: RenderResult = match format {
+        TemplateFormat::Pkl => PklTemplateRenderer::new(options).render(types),
+        TemplateFormat::Yaml => YamlTemplateRenderer::new(options).render(types),
+        TemplateFormat::Json => JsonTemplateRenderer::new(options).render(types),
+        TemplateFormat::JsonC => JsoncTemplateRenderer::new(options).render(types),
+        TemplateFormat::Toml => TomlTemplateRenderer::new(options).render(types),
+        TemplateFormat::Typescript => {
+            return Err(CliError::UnsupportedFormat {
+                format: format.to_string(),
+                available: vec!["pkl", "yaml", "json", "jsonc", "toml"],
+            });
+        }
+    };
+    rendered.map_err(|e| CliError::Generic(format!("Failed to render {root_name} template as {format}: {e}")))
+}
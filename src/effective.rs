@@ -0,0 +1,484 @@
+//! Effective Configs
+//!
+//! A Moon config file rarely says everything on its own: workspace, toolchain and tasks configs can `extends` other
+//! files, and projects inherit tasks from `.moon/tasks.yml` and `.moon/tasks/*.yml`. This module resolves all of
+//! that into the config Moon ends up with, and remembers which file each setting and task came from.
+//!
+//! ```rust,ignore
+//! use space_pklr::effective::{EffectiveOptions, resolve_effective_config};
+//!
+//! let effective = resolve_effective_config(Path::new("apps/web/moon.yml"), MoonConfig::Project, &EffectiveOptions::default())?;
+//! println!("{}", effective.explain());
+//! ```
+//!
+//! ## How things merge
+//!
+//! - `extends` is followed depth first, so the files extended come before the one extending them. Local paths are
+//!   relative to the file; URLs aren't fetched, so each needs a local stand-in in [`EffectiveOptions::stand_ins`].
+//! - Later files win. Maps merge key by key and everything else is replaced, as schematic does it.
+//! - Inherited task files are layered in Moon's lookup order -- `tasks.yml`, then the project's stack, type,
+//!   toolchains (from `toolchain.default`, `platform` and `language`) and combinations of those, then `tag-*`.
+//!   Their `implicitDeps` and `implicitInputs` add up rather than replace.
+//! - The project's `workspace.inheritedTasks` then picks and renames the inherited tasks, `taskOptions` fills in
+//!   their options, and the project's own tasks are merged over them with each task's `merge*` strategies.
+//!   Finally the implicit deps and inputs are added to every task.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use moon_common::Id;
+use moon_config::{InheritedTasksManager, ProjectConfig};
+use schematic::Format;
+use serde_json::{Map, Value};
+use walkdir::WalkDir;
+
+use crate::_rewrite::{detect_format_from_path, parse_value};
+use crate::types::{CliError, LoadedConfig, MoonConfig};
+
+/// Settings whose entries are explained one by one
+const EXPLAINED_MAPS: [&str; 2] = ["tasks", "fileGroups"];
+
+/// Where to look for what a config builds on
+#[derive(Debug, Clone, Default)]
+pub struct EffectiveOptions {
+    /// The workspace root, holding `.moon/`. Found from the config's path when not given.
+    pub workspace_root: Option<PathBuf>,
+    /// Local files to read in place of the URLs configs extend
+    pub stand_ins: HashMap<String, PathBuf>,
+}
+
+/// A config with everything it extends and inherits merged in
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub config: LoadedConfig,
+    /// The files that set each top-level setting and each task or file group (`tasks.build`), in the order they
+    /// were merged. Settings no file sets -- defaults -- aren't here.
+    pub sources: IndexMap<String, Vec<PathBuf>>,
+}
+
+impl EffectiveConfig {
+    /// A table of every setting and the files it came from, paths relative to `root` where they can be
+    pub fn explain_relative_to(&self, root: &Path) -> String {
+        let width = self.sources.keys().map(String::len).chain(["Setting".len()]).max().unwrap_or_default();
+        let mut table = format!("{:<width$}  From\n", "Setting");
+        for (setting, files) in &self.sources {
+            let files: Vec<String> = files
+                .iter()
+                .map(|file| file.strip_prefix(root).unwrap_or(file).display().to_string())
+                .collect();
+            table.push_str(&format!("{setting:<width$}  {}\n", files.join(" → ")));
+        }
+        table.push_str("\nAnything not listed is a default.");
+        table
+    }
+
+    /// A table of every setting and the files it came from
+    pub fn explain(&self) -> String {
+        self.explain_relative_to(Path::new(""))
+    }
+}
+
+/// One file's settings, with `extends` taken out
+struct Layer {
+    path: PathBuf,
+    settings: Map<String, Value>,
+}
+
+/// Resolve the config at `path` into the config Moon would use: follow its `extends`, and for projects, merge in
+/// the tasks it inherits. The result is validated and has its defaults filled in.
+pub fn resolve_effective_config(
+    path: &Path,
+    config_type: MoonConfig,
+    options: &EffectiveOptions,
+) -> Result<EffectiveConfig, CliError> {
+    let mut sources = IndexMap::new();
+    let mut merged = Value::Object(Map::new());
+    for layer in load_layers(path, extends_files(config_type), options)? {
+        merge_layer(&mut merged, layer, &mut sources);
+    }
+
+    if config_type == MoonConfig::Project {
+        let root = match &options.workspace_root {
+            Some(root) => Some(root.clone()),
+            None => find_workspace_root(path),
+        };
+        if let Some(root) = root {
+            let LoadedConfig::Project(project) = LoadedConfig::from_code(MoonConfig::Project, merged.to_string(), Format::Json)?
+            else {
+                unreachable!("project configs load as project configs");
+            };
+            inherit_tasks(&root, &project, &mut merged, &mut sources, options)?;
+        }
+    }
+
+    let config = LoadedConfig::from_code(config_type, merged.to_string(), Format::Json)?;
+    Ok(EffectiveConfig { config, sources })
+}
+
+/// Whether `extends` names files for this config type; for tasks and templates it names other tasks and templates
+fn extends_files(config_type: MoonConfig) -> bool {
+    matches!(config_type, MoonConfig::Workspace | MoonConfig::Toolchain | MoonConfig::InheritedTasks)
+}
+
+/// The closest directory at or above `path` holding a `.moon` directory
+pub fn find_workspace_root(path: &Path) -> Option<PathBuf> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".moon").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// The file at `path` and everything it extends, the extended files first
+fn load_layers(path: &Path, follow_extends: bool, options: &EffectiveOptions) -> Result<Vec<Layer>, CliError> {
+    let mut layers = Vec::new();
+    load_layers_into(path, follow_extends, options, &mut Vec::new(), &mut layers)?;
+    Ok(layers)
+}
+
+fn load_layers_into(
+    path: &Path,
+    follow_extends: bool,
+    options: &EffectiveOptions,
+    chain: &mut Vec<PathBuf>,
+    layers: &mut Vec<Layer>,
+) -> Result<(), CliError> {
+    let mut settings = read_settings(path)?;
+    let extends = if follow_extends { settings.remove("extends") } else { None };
+    let targets: Vec<String> = match extends {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(target)) => vec![target],
+        Some(Value::Array(targets)) => targets
+            .into_iter()
+            .filter_map(|target| target.as_str().map(str::to_string))
+            .collect(),
+        Some(other) => {
+            return Err(CliError::UnresolvedExtends {
+                target: other.to_string(),
+                path: path.to_path_buf(),
+                reason: "`extends` must be a file path, a URL, or a list of them".to_string(),
+            });
+        }
+    };
+
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    chain.push(canonical);
+    for target in targets {
+        let extended = resolve_extends(path, &target, options)?;
+        let canonical = std::fs::canonicalize(&extended).unwrap_or_else(|_| extended.clone());
+        if chain.contains(&canonical) {
+            return Err(CliError::UnresolvedExtends {
+                target,
+                path: path.to_path_buf(),
+                reason: format!("{} already extends {}, so this would go round forever", extended.display(), path.display()),
+            });
+        }
+        load_layers_into(&extended, true, options, chain, layers)?;
+    }
+    chain.pop();
+
+    layers.push(Layer {
+        path: path.to_path_buf(),
+        settings,
+    });
+    Ok(())
+}
+
+/// The local file an `extends` in `from` refers to
+fn resolve_extends(from: &Path, target: &str, options: &EffectiveOptions) -> Result<PathBuf, CliError> {
+    let unresolved = |reason: String| CliError::UnresolvedExtends {
+        target: target.to_string(),
+        path: from.to_path_buf(),
+        reason,
+    };
+
+    let path = if target.starts_with("https://") || target.starts_with("http://") {
+        options.stand_ins.get(target).cloned().ok_or_else(|| {
+            unresolved(format!("URLs aren't fetched; pass --stand-in {target}=<file> to read a local copy instead"))
+        })?
+    } else {
+        from.parent().unwrap_or(Path::new("")).join(target)
+    };
+    if !path.is_file() {
+        return Err(unresolved(format!("{} doesn't exist", path.display())));
+    }
+    Ok(path)
+}
+
+/// Read a config file's settings, whatever its format
+fn read_settings(path: &Path) -> Result<Map<String, Value>, CliError> {
+    let content = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+        context: format!("Reading config file: {}", path.display()),
+        source: e,
+    })?;
+    let format = detect_format_from_path(path)?;
+    match parse_value(&path.display().to_string(), &content, &format)? {
+        Value::Object(settings) => Ok(settings),
+        // an empty YAML file is null
+        Value::Null => Ok(Map::new()),
+        _ => Err(CliError::Generic(format!("{} doesn't hold settings", path.display()))),
+    }
+}
+
+/// Merge a layer over what's been merged so far, noting the settings it sets
+fn merge_layer(merged: &mut Value, layer: Layer, sources: &mut IndexMap<String, Vec<PathBuf>>) {
+    for (key, value) in &layer.settings {
+        if key == "$schema" {
+            continue;
+        }
+        note_source(sources, key.clone(), &layer.path);
+        if EXPLAINED_MAPS.contains(&key.as_str())
+            && let Value::Object(entries) = value
+        {
+            for id in entries.keys() {
+                note_source(sources, format!("{key}.{id}"), &layer.path);
+            }
+        }
+    }
+    merge_values(merged, Value::Object(layer.settings));
+}
+
+fn note_source(sources: &mut IndexMap<String, Vec<PathBuf>>, setting: String, path: &Path) {
+    let files = sources.entry(setting).or_default();
+    if files.last().is_none_or(|last| last != path) {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// Merge `layer` over `base`: objects merge key by key and anything else is replaced
+fn merge_values(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Merge the tasks, file groups and implicit deps and inputs a project inherits into its settings
+fn inherit_tasks(
+    root: &Path,
+    project: &ProjectConfig,
+    merged: &mut Value,
+    sources: &mut IndexMap<String, Vec<PathBuf>>,
+    options: &EffectiveOptions,
+) -> Result<(), CliError> {
+    let files = find_inherited_task_files(root);
+    let mut inherited = Map::new();
+    let mut inherited_sources = IndexMap::new();
+    let mut implicit_deps = Vec::new();
+    let mut implicit_inputs = Vec::new();
+
+    for name in lookup_order(project) {
+        let Some(path) = files.get(&name) else {
+            continue;
+        };
+        for mut layer in load_layers(path, true, options)? {
+            // these add up across files rather than replace each other
+            if let Some(Value::Array(deps)) = layer.settings.remove("implicitDeps") {
+                implicit_deps.extend(deps);
+            }
+            if let Some(Value::Array(inputs)) = layer.settings.remove("implicitInputs") {
+                implicit_inputs.extend(inputs);
+            }
+            let mut inherited_value = Value::Object(std::mem::take(&mut inherited));
+            merge_layer(&mut inherited_value, layer, &mut inherited_sources);
+            let Value::Object(map) = inherited_value else {
+                unreachable!("merging objects gives an object");
+            };
+            inherited = map;
+        }
+    }
+    // what follows takes tasks and their options apart, so they have to be the shapes Moon expects
+    LoadedConfig::from_code(MoonConfig::InheritedTasks, Value::Object(inherited.clone()).to_string(), Format::Json)?;
+
+    let Value::Object(settings) = merged else {
+        unreachable!("configs are objects");
+    };
+
+    // pick, rename and fill in the inherited tasks
+    let selection = &project.workspace.inherited_tasks;
+    let task_options = inherited.remove("taskOptions");
+    let mut tasks = Map::new();
+    if let Some(Value::Object(inherited_tasks)) = inherited.remove("tasks") {
+        for (id, mut task) in inherited_tasks {
+            let included = selection
+                .include
+                .as_ref()
+                .is_none_or(|include| include.iter().any(|included| included.as_str() == id));
+            if !included || selection.exclude.iter().any(|excluded| excluded.as_str() == id) {
+                continue;
+            }
+            if let Some(options) = &task_options {
+                let mut filled = options.clone();
+                if let Some(own) = task.get_mut("options").map(Value::take) {
+                    merge_values(&mut filled, own);
+                }
+                task["options"] = filled;
+            }
+            let renamed = selection
+                .rename
+                .iter()
+                .find(|(from, _)| from.as_str() == id)
+                .map(|(_, to)| to.to_string())
+                .unwrap_or_else(|| id.clone());
+            if let Some(files) = inherited_sources.shift_remove(&format!("tasks.{id}")) {
+                prepend_sources(sources, format!("tasks.{renamed}"), files);
+            }
+            tasks.insert(renamed, task);
+        }
+    }
+
+    // the project's own tasks go over them
+    if let Some(Value::Object(own_tasks)) = settings.remove("tasks") {
+        for (id, task) in own_tasks {
+            match tasks.get_mut(&id) {
+                Some(inherited_task) => merge_task(inherited_task, task),
+                None => {
+                    tasks.insert(id, task);
+                }
+            }
+        }
+    }
+    for task in tasks.values_mut() {
+        append_implicit(task, "deps", &implicit_deps);
+        append_implicit(task, "inputs", &implicit_inputs);
+    }
+    if !tasks.is_empty() {
+        settings.insert("tasks".to_string(), Value::Object(tasks));
+    }
+
+    // file groups too
+    if let Some(Value::Object(mut file_groups)) = inherited.remove("fileGroups") {
+        for (id, files) in inherited_sources.iter().filter(|(setting, _)| setting.starts_with("fileGroups.")) {
+            prepend_sources(sources, id.clone(), files.clone());
+        }
+        if let Some(Value::Object(own_groups)) = settings.remove("fileGroups") {
+            file_groups.extend(own_groups);
+        }
+        settings.insert("fileGroups".to_string(), Value::Object(file_groups));
+    }
+    for setting in ["tasks", "fileGroups"] {
+        if let Some(files) = inherited_sources.get(setting) {
+            prepend_sources(sources, setting.to_string(), files.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Inherited files come before the project's own
+fn prepend_sources(sources: &mut IndexMap<String, Vec<PathBuf>>, setting: String, mut files: Vec<PathBuf>) {
+    let own = sources.get(&setting).cloned().unwrap_or_default();
+    for file in own {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    sources.insert(setting, files);
+}
+
+/// The inherited task files under `root/.moon`, by the name Moon looks them up with: `*` for `tasks.yml` and
+/// the file stem for those in `tasks/`
+fn find_inherited_task_files(root: &Path) -> HashMap<String, PathBuf> {
+    let moon_dir = root.join(".moon");
+    let mut files: HashMap<String, PathBuf> = WalkDir::new(moon_dir.join("tasks"))
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && detect_format_from_path(entry.path()).is_ok())
+        .filter_map(|entry| {
+            let name = entry.path().file_stem()?.to_str()?.to_string();
+            Some((name, entry.into_path()))
+        })
+        .collect();
+    if let Some(tasks) = crate::workspace::find_config_file(&moon_dir, "tasks") {
+        files.insert("*".to_string(), tasks);
+    }
+    files
+}
+
+/// The names of the inherited task files that apply to a project, least specific first
+fn lookup_order(project: &ProjectConfig) -> Vec<String> {
+    let mut toolchains: Vec<String> = Vec::new();
+    if let Some(default) = &project.toolchain.default {
+        toolchains.extend(default.to_list().iter().map(ToString::to_string));
+    }
+    #[allow(deprecated)]
+    if let Some(platform) = &project.platform {
+        toolchains.push(platform.to_string());
+    }
+    let language = project.language.to_string();
+    if matches!(language.as_str(), "javascript" | "typescript") {
+        toolchains.push("node".to_string());
+    }
+    toolchains.push(language);
+    toolchains.dedup();
+
+    let toolchains: Vec<Id> = toolchains.iter().map(Id::raw).collect();
+    InheritedTasksManager::default().get_lookup_order(&toolchains, &project.stack, &project.type_of, &project.tags)
+}
+
+/// Merge a project's task over the inherited one of the same ID, following the task's `merge*` strategies
+fn merge_task(inherited: &mut Value, own: Value) {
+    let strategy = |setting: &str| {
+        [own.pointer(&format!("/options/{setting}")), own.pointer("/options/merge"), inherited.pointer(&format!("/options/{setting}")), inherited.pointer("/options/merge")]
+            .into_iter()
+            .flatten()
+            .find_map(Value::as_str)
+            .unwrap_or("append")
+            .to_string()
+    };
+    let strategies: Vec<(&str, String)> = [("args", "mergeArgs"), ("deps", "mergeDeps"), ("env", "mergeEnv"), ("inputs", "mergeInputs"), ("outputs", "mergeOutputs")]
+        .into_iter()
+        .map(|(setting, option)| (setting, strategy(option)))
+        .collect();
+
+    let Value::Object(own) = own else {
+        *inherited = own;
+        return;
+    };
+    for (key, value) in own {
+        let Some(existing) = inherited.get_mut(&key) else {
+            inherited[&key] = value;
+            continue;
+        };
+        let Some((_, strategy)) = strategies.iter().find(|(setting, _)| *setting == key) else {
+            merge_values(existing, value);
+            continue;
+        };
+        match (strategy.as_str(), existing, value) {
+            ("preserve", ..) => {}
+            ("append", Value::Array(existing), Value::Array(value)) => existing.extend(value),
+            ("prepend", Value::Array(existing), Value::Array(mut value)) => {
+                value.append(existing);
+                *existing = value;
+            }
+            ("append", existing @ Value::Object(_), value @ Value::Object(_)) => merge_values(existing, value),
+            ("prepend", Value::Object(existing), Value::Object(mut value)) => {
+                for (key, setting) in std::mem::take(existing) {
+                    value.entry(key).or_insert(setting);
+                }
+                *existing = value;
+            }
+            (_, existing, value) => *existing = value,
+        }
+    }
+}
+
+/// Add implicit deps or inputs to a task
+fn append_implicit(task: &mut Value, setting: &str, implicit: &[Value]) {
+    if implicit.is_empty() {
+        return;
+    }
+    match task.get_mut(setting) {
+        Some(Value::Array(existing)) => existing.extend(implicit.iter().filter(|value| !existing.contains(value)).cloned().collect::<Vec<_>>()),
+        _ => task[setting] = Value::Array(implicit.to_vec()),
+    }
+}
@@ -0,0 +1,529 @@
+//! JSON Schema Importer
+//!
+//! Maps JSON Schema documents (draft-07 and 2020-12) into a schematic [`TypeMap`], so any third-party
+//! config schema can go through the same renderers as Moon's -- most usefully, out as idiomatic Pkl.
+//!
+//! ## How things map
+//!
+//! - `$defs` (2020-12) and `definitions` (draft-07) become named types, and `$ref`s to them become references.
+//! - The document itself becomes the root type, named after its `title` (or whatever name you pass in).
+//! - Objects with `properties` become structs. Inline ones are hoisted into named types (their `title`, or
+//!   the parent's name plus the property name), because that's what a `TypeMap` expects and what makes
+//!   good Pkl classes. `required` decides which fields are optional.
+//! - Objects with only `additionalProperties`/`patternProperties` become maps.
+//! - `items` becomes an array; `prefixItems` (or draft-07's array-form `items`) becomes a tuple.
+//! - `enum` becomes an enum, `const` a literal, `oneOf`/`anyOf` unions, and `allOf` merges its object members.
+//! - `type: [..., "null"]` and `null` in an `enum` make the type nullable.
+//! - `pattern`, `format`, `minLength`/`maxLength`, `minimum`/`maximum` (and their exclusive forms),
+//!   `multipleOf`, `minItems`/`maxItems`, `uniqueItems`, `default`, `description`, `deprecated`,
+//!   `readOnly` and `writeOnly` carry over.
+
+use std::path::Path;
+
+use schematic::schema::{
+    ArrayType, BooleanType, EnumType, FloatKind, FloatType, IntegerKind, IntegerType, LiteralType,
+    LiteralValue, ObjectType, Schema, SchemaField, SchemaType, StringType, StructType, TupleType,
+    UnionType,
+};
+use serde_json::{Map, Value};
+
+use crate::pkl_importer::apply_default;
+use crate::types::{CliError, Result, TypeMap};
+
+/// Imports a JSON Schema document from source text. The root type is named after the document's
+/// `title`, or `Root` if it has none.
+pub fn import_json_schema(source: &str) -> Result<TypeMap> {
+    let document = parse_document("<input>", source)?;
+    let root_name = title_of(&document).unwrap_or_else(|| "Root".to_string());
+    import_json_schema_value(&document, &root_name)
+}
+
+/// Imports a JSON Schema file. The root type is named after the document's `title`, or after the
+/// file name (`moon.schema.json` -> `Moon`).
+pub fn import_json_schema_file(path: &Path) -> Result<TypeMap> {
+    let source = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+        context: format!("Reading JSON Schema: {}", path.display()),
+        source: e,
+    })?;
+    let document = parse_document(&path.display().to_string(), &source)?;
+    let root_name = title_of(&document).unwrap_or_else(|| {
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("Root");
+        pascal_case(file_name.split('.').next().unwrap_or(file_name))
+    });
+    import_json_schema_value(&document, &root_name)
+}
+
+/// Imports an already-parsed JSON Schema document, naming the root type `root_name`.
+///
+/// The root type comes last in the map, the way schematic's generator orders it. A document that only
+/// holds `$defs`/`definitions` (no root type of its own) gives you just the definitions.
+pub fn import_json_schema_value(document: &Value, root_name: &str) -> Result<TypeMap> {
+    let mut importer = JsonSchemaImporter {
+        document,
+        root_name: root_name.to_string(),
+        types: TypeMap::new(),
+    };
+
+    for (name, definition) in importer.definitions() {
+        let schema = importer.convert_named(&name, definition)?;
+        importer.types.insert(name, schema);
+    }
+
+    if has_root_type(document) {
+        let schema = importer.convert_named(root_name, document)?;
+        importer.types.insert(root_name.to_string(), schema);
+    }
+    Ok(importer.types)
+}
+
+fn parse_document(source_name: &str, source: &str) -> Result<Value> {
+    serde_json::from_str(source).map_err(|e| CliError::SchemaImportFailed {
+        source_name: source_name.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+fn title_of(document: &Value) -> Option<String> {
+    document
+        .get("title")
+        .and_then(Value::as_str)
+        .map(pascal_case)
+        .filter(|title| !title.is_empty())
+}
+
+/// Whether the document describes a type, rather than being a bag of definitions.
+fn has_root_type(document: &Value) -> bool {
+    const TYPE_KEYWORDS: [&str; 10] = [
+        "type", "properties", "items", "prefixItems", "enum", "const", "oneOf", "anyOf", "allOf", "additionalProperties",
+    ];
+    document
+        .as_object()
+        .is_some_and(|map| TYPE_KEYWORDS.iter().any(|keyword| map.contains_key(*keyword)))
+}
+
+/// The definition `#/$defs/a~1b` or `#/definitions/a~1b` points at, with JSON pointer escapes undone: `a/b`
+fn definition_name(reference: &str) -> Option<String> {
+    let name = reference
+        .strip_prefix("#/$defs/")
+        .or_else(|| reference.strip_prefix("#/definitions/"))?;
+    Some(name.replace("~1", "/").replace("~0", "~"))
+}
+
+/// `moon-project config` -> `MoonProjectConfig`
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+struct JsonSchemaImporter<'a> {
+    document: &'a Value,
+    root_name: String,
+    types: TypeMap,
+}
+
+impl<'a> JsonSchemaImporter<'a> {
+    fn definitions(&self) -> Vec<(String, &'a Value)> {
+        ["$defs", "definitions"]
+            .iter()
+            .filter_map(|key| self.document.get(*key).and_then(Value::as_object))
+            .flat_map(|defs| defs.iter().map(|(name, definition)| (name.clone(), definition)))
+            .collect()
+    }
+
+    fn error(&self, reason: impl Into<String>) -> CliError {
+        CliError::SchemaImportFailed {
+            source_name: self.root_name.clone(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Converts a definition or the root: structs are returned as-is rather than hoisted.
+    fn convert_named(&mut self, name: &str, definition: &Value) -> Result<Schema> {
+        let mut schema = self.convert(definition, name, true)?;
+        schema.name = Some(name.to_string());
+        Ok(schema)
+    }
+
+    /// Converts a schema. `context` names the type we're inside of, for naming hoisted structs.
+    fn convert(&mut self, definition: &Value, context: &str, named: bool) -> Result<Schema> {
+        let map = match definition {
+            Value::Object(map) => map,
+            // `true` accepts anything; `false` accepts nothing, which schematic can't say either
+            _ => return Ok(Schema::unknown()),
+        };
+
+        let mut schema = if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+            self.convert_reference(reference)?
+        } else if let Some(members) = map.get("allOf").and_then(Value::as_array) {
+            self.convert_all_of(map, members, context, named)?
+        } else if let Some(value) = map.get("const") {
+            literal_value(value)
+                .map(|value| Schema::literal(LiteralType::new(value)))
+                .unwrap_or_else(|| if value.is_null() { Schema::null() } else { Schema::unknown() })
+        } else if let Some(values) = map.get("enum").and_then(Value::as_array) {
+            convert_enum(values)
+        } else if let Some(variants) = map.get("oneOf").and_then(Value::as_array) {
+            let variants = self.convert_variants(variants, context)?;
+            union_schema(UnionType::new_one(variants))
+        } else if let Some(variants) = map.get("anyOf").and_then(Value::as_array) {
+            let variants = self.convert_variants(variants, context)?;
+            union_schema(UnionType::new_any(variants))
+        } else {
+            match map.get("type") {
+                Some(Value::String(ty)) => self.convert_typed(ty, map, context, named)?,
+                Some(Value::Array(types)) => {
+                    let mut variants = Vec::new();
+                    let mut nullable = false;
+                    for ty in types.iter().filter_map(Value::as_str) {
+                        if ty == "null" {
+                            nullable = true;
+                        } else {
+                            variants.push(self.convert_typed(ty, map, context, named)?);
+                        }
+                    }
+                    let mut schema = match variants.len() {
+                        0 => Schema::null(),
+                        1 => variants.remove(0),
+                        _ => union_schema(UnionType::new_any(variants)),
+                    };
+                    if nullable && !schema.ty.is_null() {
+                        schema.nullify();
+                    }
+                    schema
+                }
+                _ => {
+                    let inferred = if map.contains_key("properties") || map.contains_key("additionalProperties") {
+                        "object"
+                    } else if map.contains_key("items") || map.contains_key("prefixItems") {
+                        "array"
+                    } else {
+                        ""
+                    };
+                    self.convert_typed(inferred, map, context, named)?
+                }
+            }
+        };
+
+        if let Some(description) = map
+            .get("description")
+            .or_else(|| map.get("title"))
+            .and_then(Value::as_str)
+        {
+            schema.description = Some(description.to_string());
+        }
+        if map.get("deprecated").and_then(Value::as_bool) == Some(true) {
+            schema.deprecated = Some(String::new());
+        }
+        if let Some(default) = map.get("default").and_then(literal_value) {
+            apply_default(&mut schema, default);
+        }
+        Ok(schema)
+    }
+
+    fn convert_reference(&self, reference: &str) -> Result<Schema> {
+        let name = if reference == "#" {
+            self.root_name.clone()
+        } else if let Some(name) = definition_name(reference) {
+            if self.definitions().iter().all(|(defined, _)| *defined != name) {
+                return Err(self.error(format!("`$ref` points at a missing definition: {}", reference)));
+            }
+            name
+        } else {
+            // external documents aren't followed
+            return Ok(Schema::unknown());
+        };
+        Ok(Schema {
+            name: Some(name.clone()),
+            ty: SchemaType::Reference(name),
+            ..Default::default()
+        })
+    }
+
+    fn convert_variants(&mut self, variants: &[Value], context: &str) -> Result<Vec<Schema>> {
+        variants
+            .iter()
+            .map(|variant| self.convert(variant, context, false))
+            .collect()
+    }
+
+    /// `allOf` is usually "this object, plus those fields", so object members (inline or referenced)
+    /// are merged into one struct. Anything else keeps the first member.
+    fn convert_all_of(&mut self, map: &Map<String, Value>, members: &[Value], context: &str, named: bool) -> Result<Schema> {
+        let mut merged = Map::new();
+        let mut properties = Map::new();
+        let mut required: Vec<Value> = Vec::new();
+        let mut objects = 0;
+
+        let own = Value::Object(map.iter().filter(|(key, _)| *key != "allOf").map(|(k, v)| (k.clone(), v.clone())).collect());
+        for member in members.iter().chain(std::iter::once(&own)) {
+            let resolved = match member.get("$ref").and_then(Value::as_str) {
+                Some(reference) => self.resolve_local(reference).unwrap_or(member),
+                None => member,
+            };
+            if let Some(member_properties) = resolved.get("properties").and_then(Value::as_object) {
+                objects += 1;
+                properties.extend(member_properties.clone());
+                if let Some(member_required) = resolved.get("required").and_then(Value::as_array) {
+                    required.extend(member_required.iter().cloned());
+                }
+            }
+        }
+
+        if objects == 0 {
+            return match members.first() {
+                Some(first) => self.convert(first, context, named),
+                None => Ok(Schema::unknown()),
+            };
+        }
+        merged.insert("type".to_string(), Value::String("object".to_string()));
+        merged.insert("properties".to_string(), Value::Object(properties));
+        merged.insert("required".to_string(), Value::Array(required));
+        self.convert_typed("object", &merged, context, named)
+    }
+
+    fn resolve_local(&self, reference: &str) -> Option<&'a Value> {
+        if reference == "#" {
+            return Some(self.document);
+        }
+        let name = definition_name(reference)?;
+        self.definitions()
+            .into_iter()
+            .find(|(defined, _)| *defined == name)
+            .map(|(_, definition)| definition)
+    }
+
+    fn convert_typed(&mut self, ty: &str, map: &Map<String, Value>, context: &str, named: bool) -> Result<Schema> {
+        let schema = match ty {
+            "string" => Schema::string(StringType {
+                format: get_str(map, "format"),
+                pattern: get_str(map, "pattern"),
+                min_length: get_usize(map, "minLength"),
+                max_length: get_usize(map, "maxLength"),
+                ..Default::default()
+            }),
+            "integer" => {
+                let mut integer = IntegerType::new_kind(IntegerKind::I64);
+                integer.format = get_str(map, "format");
+                let bounds = NumberBounds::from(map);
+                integer.min = bounds.min.map(|value| value as isize);
+                integer.max = bounds.max.map(|value| value as isize);
+                integer.min_exclusive = bounds.min_exclusive.map(|value| value as isize);
+                integer.max_exclusive = bounds.max_exclusive.map(|value| value as isize);
+                integer.multiple_of = bounds.multiple_of.map(|value| value as isize);
+                Schema::integer(integer)
+            }
+            "number" => {
+                let mut float = FloatType::new_kind(FloatKind::F64);
+                float.format = get_str(map, "format");
+                let bounds = NumberBounds::from(map);
+                float.min = bounds.min;
+                float.max = bounds.max;
+                float.min_exclusive = bounds.min_exclusive;
+                float.max_exclusive = bounds.max_exclusive;
+                float.multiple_of = bounds.multiple_of;
+                Schema::float(float)
+            }
+            "boolean" => Schema::boolean(BooleanType::default()),
+            "null" => Schema::null(),
+            "array" => self.convert_array(map, context)?,
+            "object" => self.convert_object(map, context, named)?,
+            _ => Schema::unknown(),
+        };
+        Ok(schema)
+    }
+
+    fn convert_array(&mut self, map: &Map<String, Value>, context: &str) -> Result<Schema> {
+        let item_context = format!("{}Item", context);
+        // 2020-12 `prefixItems`, or draft-07 array-form `items`
+        let positional = map
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .or_else(|| map.get("items").and_then(Value::as_array));
+        if let Some(items) = positional {
+            let items = self.convert_variants(items, &item_context)?;
+            return Ok(Schema::tuple(TupleType::new(items)));
+        }
+
+        let items = match map.get("items") {
+            Some(items) => self.convert(items, &item_context, false)?,
+            None => Schema::unknown(),
+        };
+        let mut array = ArrayType::new(items);
+        array.min_length = get_usize(map, "minItems");
+        array.max_length = get_usize(map, "maxItems");
+        array.unique = map.get("uniqueItems").and_then(Value::as_bool).filter(|unique| *unique);
+        if map.contains_key("contains") {
+            array.contains = Some(true);
+            array.min_contains = get_usize(map, "minContains");
+            array.max_contains = get_usize(map, "maxContains");
+        }
+        Ok(Schema::array(array))
+    }
+
+    fn convert_object(&mut self, map: &Map<String, Value>, context: &str, named: bool) -> Result<Schema> {
+        let Some(properties) = map.get("properties").and_then(Value::as_object) else {
+            // a map: `additionalProperties` or `patternProperties` describe the values
+            let value_context = format!("{}Value", context);
+            let values = map
+                .get("additionalProperties")
+                .filter(|value| value.is_object())
+                .or_else(|| {
+                    map.get("patternProperties")
+                        .and_then(Value::as_object)
+                        .and_then(|patterns| patterns.values().next())
+                });
+            let value_type = match values {
+                Some(values) => self.convert(values, &value_context, false)?,
+                None => Schema::unknown(),
+            };
+            let key_type = Schema::string(StringType {
+                pattern: map
+                    .get("propertyNames")
+                    .and_then(|names| names.get("pattern"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                ..Default::default()
+            });
+            let mut object = ObjectType::new(key_type, value_type);
+            object.min_length = get_usize(map, "minProperties");
+            object.max_length = get_usize(map, "maxProperties");
+            return Ok(Schema::object(object));
+        };
+
+        let required: Vec<String> = map
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let mut fields = Vec::new();
+        for (name, property) in properties {
+            let property_context = format!("{}{}", context, pascal_case(name));
+            let schema = self.convert(property, &property_context, false)?;
+            let nullable = schema.nullable || matches!(&schema.ty, SchemaType::Union(union) if union.has_null());
+            let field = SchemaField {
+                comment: schema.description.clone(),
+                deprecated: schema.deprecated.clone(),
+                read_only: property.get("readOnly").and_then(Value::as_bool).unwrap_or_default(),
+                write_only: property.get("writeOnly").and_then(Value::as_bool).unwrap_or_default(),
+                optional: !required.contains(name),
+                nullable,
+                schema,
+                ..Default::default()
+            };
+            fields.push((name.clone(), field));
+        }
+
+        let mut structure = StructType::new(fields);
+        if !required.is_empty() {
+            structure.required = Some(required);
+        }
+        let schema = Schema::structure(structure);
+        if named {
+            return Ok(schema);
+        }
+        Ok(self.hoist(map, context, schema))
+    }
+
+    /// Moves an inline struct into the `TypeMap` and returns a reference to it.
+    fn hoist(&mut self, map: &Map<String, Value>, context: &str, mut schema: Schema) -> Schema {
+        let base = map
+            .get("title")
+            .and_then(Value::as_str)
+            .map(pascal_case)
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| context.to_string());
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.types.contains_key(&name)
+            || self.definitions().iter().any(|(defined, _)| *defined == name)
+            || name == self.root_name
+        {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        schema.name = Some(name.clone());
+        if let Some(description) = map.get("description").and_then(Value::as_str) {
+            schema.description = Some(description.to_string());
+        }
+        self.types.insert(name.clone(), schema);
+        Schema {
+            name: Some(name.clone()),
+            ty: SchemaType::Reference(name),
+            ..Default::default()
+        }
+    }
+}
+
+/// `minimum`/`maximum` and friends, including draft-04's boolean `exclusiveMinimum`/`exclusiveMaximum`.
+struct NumberBounds {
+    min: Option<f64>,
+    max: Option<f64>,
+    min_exclusive: Option<f64>,
+    max_exclusive: Option<f64>,
+    multiple_of: Option<f64>,
+}
+
+impl From<&Map<String, Value>> for NumberBounds {
+    fn from(map: &Map<String, Value>) -> Self {
+        let mut bounds = NumberBounds {
+            min: map.get("minimum").and_then(Value::as_f64),
+            max: map.get("maximum").and_then(Value::as_f64),
+            min_exclusive: map.get("exclusiveMinimum").and_then(Value::as_f64),
+            max_exclusive: map.get("exclusiveMaximum").and_then(Value::as_f64),
+            multiple_of: map.get("multipleOf").and_then(Value::as_f64),
+        };
+        if map.get("exclusiveMinimum").and_then(Value::as_bool) == Some(true) {
+            bounds.min_exclusive = bounds.min.take();
+        }
+        if map.get("exclusiveMaximum").and_then(Value::as_bool) == Some(true) {
+            bounds.max_exclusive = bounds.max.take();
+        }
+        bounds
+    }
+}
+
+fn get_str(map: &Map<String, Value>, key: &str) -> Option<String> {
+    map.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn get_usize(map: &Map<String, Value>, key: &str) -> Option<usize> {
+    map.get(key).and_then(Value::as_u64).map(|value| value as usize)
+}
+
+fn literal_value(value: &Value) -> Option<LiteralValue> {
+    match value {
+        Value::Bool(value) => Some(LiteralValue::Bool(*value)),
+        Value::String(value) => Some(LiteralValue::String(value.clone())),
+        Value::Number(number) => number
+            .as_i64()
+            .map(|value| LiteralValue::Int(value as isize))
+            .or_else(|| number.as_f64().map(LiteralValue::F64)),
+        _ => None,
+    }
+}
+
+/// `enum` values become an enum; a `null` among them makes it nullable.
+fn convert_enum(values: &[Value]) -> Schema {
+    let literals: Vec<LiteralValue> = values.iter().filter_map(literal_value).collect();
+    let mut schema = Schema::enumerable(EnumType::new(literals));
+    if values.iter().any(Value::is_null) {
+        schema.nullify();
+    }
+    schema
+}
+
+fn union_schema(union: UnionType) -> Schema {
+    let nullable = union.has_null();
+    let mut schema = Schema::union(union);
+    schema.nullable = nullable;
+    schema
+}
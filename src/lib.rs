@@ -3,11 +3,35 @@
 //! This library provides the core functionality for the Space Pklr tool,
 //! including configuration conversion, schema generation, and Pkl tooling integration.
 
+pub mod _rewrite;
 pub mod cli_app;
 pub mod commands;
+pub mod config_validator;
+mod constants;
+pub mod custom_types;
+pub mod effective;
+pub mod json_schema_importer;
+pub mod pkl_deserializer;
+pub mod pkl_errors;
+pub mod pkl_evaluator;
+pub mod pkl_importer;
+pub mod pkl_renderer;
+pub mod pkl_serializer;
 pub mod pkl_tooling;
+pub mod pkl_versions;
 pub mod types;
+pub mod workspace;
 
 // Re-export commonly used types
-pub use types::{CliError, InternalError, Result, SchemaFormat, LoadedConfig, MoonConfig, TypeMap, EnumTranslation, OpenStructs, ConfigTranslation, OptionalFormat, PropertyDefault, ensure_file_exists, ensure_output_writable, pkl_execution_error};
-pub use pkl_tooling::{CompatibilityReport, PklCli, PklSource};
+pub use types::{CliError, Confidence, ConfigProblem, InternalError, Result, SchemaFormat, TemplateFormat, TypeInference, TypeSource, LoadedConfig, MoonConfig, UnknownConfig, TypeMap, EnumTranslation, OpenStructs, ConfigTranslation, OptionalFormat, PropertyDefault, TupleTranslation, ModuleLayout, ensure_file_exists, ensure_output_writable, pkl_execution_error};
+pub use config_validator::{validate_config, validate_config_file};
+pub use effective::{EffectiveConfig, EffectiveOptions, resolve_effective_config};
+pub use custom_types::{load_type_map, render_moon_modules, render_moon_schema, render_schema, render_schema_modules, render_template, resolve_root, type_map_of};
+pub use json_schema_importer::{import_json_schema, import_json_schema_file, import_json_schema_value};
+pub use pkl_deserializer::{PklDocument, from_pkl_str, from_pkl_value, load_pkl_config, load_pkl_config_file, parse_pcf};
+pub use pkl_evaluator::{EvaluatorOptions, InMemoryModules, ModuleReader, PklEvaluator, SharedEvaluator};
+pub use pkl_importer::{import_pkl_file, import_pkl_schema};
+pub use pkl_renderer::{PklSchemaOptions, PklSchemaRenderer, RenderType};
+pub use pkl_serializer::{PklSerializerOptions, PklValue, pkl_value_to_string, to_pkl_string, to_pkl_string_with_options, to_pkl_value};
+pub use pkl_tooling::{CompatibilityReport, FeatureResult, PklCli, PklInstallOptions, PklSource};
+pub use pkl_versions::{InstalledPkl, PklVersions, RequestedVersion, VersionRequest, VersionSource};
@@ -2,10 +2,7 @@
 //!
 //! This is the main entry point for the Space Pklr tool.
 
-mod cli_app;
-mod pkl_tooling;
-mod types;
-mod commands;
+use space_pklr::{cli_app, pkl_tooling};
 
 use miette::Result;
 
@@ -51,8 +48,8 @@ fn init_tracing() -> Result<()> {
 /// Run CLI with comprehensive error handling and logging
 async fn run_cli() -> Result<()> {
     tracing::info!("Starting Space Pklr");
-    tracing::debug!("Recommended Pkl version: {}", crate::pkl_tooling::get_recommended_pkl_version());
-    tracing::debug!("Compatible Pkl versions: {:?}", crate::pkl_tooling::get_compatible_pkl_versions());
+    tracing::debug!("Recommended Pkl version: {}", pkl_tooling::get_recommended_pkl_version());
+    tracing::debug!("Compatible Pkl versions: {:?}", pkl_tooling::get_compatible_pkl_versions());
 
     let result = cli_app::run().await;
 
@@ -0,0 +1,702 @@
+//! Pkl Deserializer
+//!
+//! A parser for static Pkl (PCF) and a [`serde::Deserializer`] over what it reads, so `moon.pcf` and
+//! friends can be loaded without a Pkl installation. It's the counterpart of the
+//! [serializer](crate::pkl_serializer): anything that writes can be read back.
+//!
+//! ## What's supported
+//!
+//! - An optional `amends "..."` header, which is kept on the [`PklDocument`] but not evaluated.
+//! - Properties (`name = value`, `` `name` = value ``), entries (`["key"] = value`) and elements, separated by
+//!   new lines or `;`.
+//! - Amending in place (`name { ... }`, `["key"] { ... }`), which merges with anything set earlier.
+//! - `new { ... }`, `new Listing { ... }`, `new Mapping { ... }` and `new SomeClass { ... }`.
+//! - `null`, booleans, integers (decimal, `0x`, `0b`, `0o`, with `_` separators), floats, `NaN` and
+//!   `Infinity`, and strings -- single-line, multi-line and `#"..."#` -- with `\n`, `\t`, `\r`, `\"`, `\\` and
+//!   `\u{...}` escapes.
+//!
+//! Anything that needs evaluation -- imports, expressions, interpolation, `local` or typed properties -- is a
+//! [`CliError::PcfParseError`] pointing at where it starts.
+//!
+//! ## How things map
+//!
+//! A body holding only elements is a listing, one holding entries is a mapping (its properties become string
+//! keys), and anything else is an object. Empty bodies deserialize as empty sequences and maps alike.
+//!
+//! ```rust,ignore
+//! use space_pklr::pkl_deserializer::load_pkl_config_file;
+//! use space_pklr::MoonConfig;
+//!
+//! let config = load_pkl_config_file(Path::new("moon.pcf"), MoonConfig::Project)?;
+//! ```
+
+use std::path::Path;
+
+use schematic::Format;
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::pkl_importer::trim_multiline;
+use crate::pkl_serializer::{PklValue, describe};
+use crate::types::{CliError, LoadedConfig, MoonConfig, Result};
+
+/// A parsed PCF module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PklDocument {
+    /// The module named by the `amends` header, if there is one
+    pub amends: Option<String>,
+    /// The module's properties, as a [`PklValue::Object`]
+    pub value: PklValue,
+}
+
+/// Parses static Pkl source. `source_name` labels errors.
+pub fn parse_pcf(source_name: &str, source: &str) -> Result<PklDocument> {
+    Parser {
+        source_name,
+        source,
+        pos: 0,
+    }
+    .parse_module()
+}
+
+/// Parses static Pkl source and deserializes it into `T`.
+pub fn from_pkl_str<T: DeserializeOwned>(source: &str) -> Result<T> {
+    from_pkl_value(parse_pcf("<input>", source)?.value)
+}
+
+/// Deserializes a [`PklValue`] tree into `T`.
+pub fn from_pkl_value<T: DeserializeOwned>(value: PklValue) -> Result<T> {
+    T::deserialize(value)
+}
+
+/// Loads a Moon config from static Pkl source, filling in defaults and validating it with schematic.
+pub fn load_pkl_config(source_name: &str, source: &str, config: MoonConfig) -> Result<LoadedConfig> {
+    let value: serde_json::Value = from_pkl_value(parse_pcf(source_name, source)?.value)?;
+    LoadedConfig::from_code(config, value.to_string(), Format::Json)
+}
+
+/// Loads a Moon config from a static Pkl file, like `moon.pcf`.
+pub fn load_pkl_config_file(path: &Path, config: MoonConfig) -> Result<LoadedConfig> {
+    let source = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+        context: format!("Reading Pkl config: {}", path.display()),
+        source: e,
+    })?;
+    load_pkl_config(&path.display().to_string(), &source, config)
+}
+
+// ================================ Parser ================================
+
+/// Identifiers that start a value rather than a member.
+const VALUE_KEYWORDS: [&str; 6] = ["null", "true", "false", "new", "NaN", "Infinity"];
+
+struct Parser<'a> {
+    source_name: &'a str,
+    source: &'a str,
+    pos: usize,
+}
+
+/// What a key in a body refers to.
+enum Member {
+    Property(String),
+    Entry(PklValue),
+}
+
+/// The members of an object body, collected before we know which kind of value it is.
+struct Body {
+    properties: Vec<(String, PklValue)>,
+    entries: Vec<(PklValue, PklValue)>,
+    elements: Vec<PklValue>,
+    /// What the body is when nothing is in it
+    empty: PklValue,
+}
+
+impl Body {
+    fn new(empty: PklValue) -> Self {
+        Self {
+            properties: Vec::new(),
+            entries: Vec::new(),
+            elements: Vec::new(),
+            empty,
+        }
+    }
+
+    /// Starts from an existing value, for amending. Returns the value back if it isn't a container.
+    fn amending(value: PklValue) -> std::result::Result<Self, PklValue> {
+        Ok(match value {
+            PklValue::Object(properties) => Self {
+                properties,
+                ..Self::new(PklValue::Object(Vec::new()))
+            },
+            PklValue::Mapping(entries) => Self {
+                entries,
+                ..Self::new(PklValue::Mapping(Vec::new()))
+            },
+            PklValue::Listing(elements) => Self {
+                elements,
+                ..Self::new(PklValue::Listing(Vec::new()))
+            },
+            other => return Err(other),
+        })
+    }
+
+    fn get(&mut self, member: &Member) -> Option<&mut PklValue> {
+        match member {
+            Member::Property(name) => self.properties.iter_mut().find(|(n, _)| n == name).map(|(_, v)| v),
+            Member::Entry(key) => self.entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+        }
+    }
+
+    fn set(&mut self, member: Member, value: PklValue) {
+        if let Some(existing) = self.get(&member) {
+            *existing = value;
+            return;
+        }
+        match member {
+            Member::Property(name) => self.properties.push((name, value)),
+            Member::Entry(key) => self.entries.push((key, value)),
+        }
+    }
+
+    fn into_value(self) -> std::result::Result<PklValue, &'static str> {
+        if !self.elements.is_empty() {
+            if !self.properties.is_empty() || !self.entries.is_empty() {
+                return Err("a body can't mix elements with properties or entries");
+            }
+            return Ok(PklValue::Listing(self.elements));
+        }
+        if !self.entries.is_empty() {
+            let mut entries: Vec<_> = self
+                .properties
+                .into_iter()
+                .map(|(name, value)| (PklValue::String(name), value))
+                .collect();
+            entries.extend(self.entries);
+            return Ok(PklValue::Mapping(entries));
+        }
+        if !self.properties.is_empty() {
+            return Ok(PklValue::Object(self.properties));
+        }
+        Ok(self.empty)
+    }
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>, offset: usize, len: usize) -> CliError {
+        let offset = offset.min(self.source.len());
+        CliError::PcfParseError {
+            message: message.into(),
+            src: miette::NamedSource::new(self.source_name, self.source.to_string()),
+            span: (offset, len.min(self.source.len() - offset)).into(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.rest().starts_with(text) {
+            self.pos += text.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) -> Result<()> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if self.rest().starts_with("//") {
+                let len = self.rest().find('\n').unwrap_or(self.rest().len());
+                self.pos += len;
+            } else if self.rest().starts_with("/*") {
+                match self.rest().find("*/") {
+                    Some(end) => self.pos += end + 2,
+                    None => return Err(self.error("unterminated block comment", self.pos, 2)),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads an identifier if one starts here. Backticked names are never keywords.
+    fn identifier(&mut self) -> Result<Option<(String, bool)>> {
+        let start = self.pos;
+        if self.eat("`") {
+            let Some(len) = self.rest().find('`') else {
+                return Err(self.error("unterminated backticked identifier", start, 1));
+            };
+            let name = self.rest()[..len].to_string();
+            self.pos += len + 1;
+            return Ok(Some((name, true)));
+        }
+        if !self.peek().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$') {
+            return Ok(None);
+        }
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(self.rest().len());
+        let name = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(Some((name, false)))
+    }
+
+    fn parse_module(&mut self) -> Result<PklDocument> {
+        self.skip_trivia()?;
+        let mut amends = None;
+        let start = self.pos;
+        if let Some((keyword, false)) = self.identifier()? {
+            if keyword == "amends" {
+                self.skip_trivia()?;
+                if !matches!(self.peek(), Some('"' | '#')) {
+                    return Err(self.error("expected the amended module's URI as a string", self.pos, 1));
+                }
+                amends = Some(self.string()?);
+            } else {
+                self.pos = start;
+            }
+        }
+
+        let mut body = Body::new(PklValue::Object(Vec::new()));
+        self.members(&mut body, None)?;
+        let value = body.into_value().map_err(|message| self.error(message, 0, 0))?;
+        Ok(PklDocument { amends, value })
+    }
+
+    /// Reads members into `body` until the closing brace (or the end of a module, when `open` is `None`).
+    fn members(&mut self, body: &mut Body, open: Option<usize>) -> Result<()> {
+        loop {
+            self.skip_trivia()?;
+            if self.eat(";") {
+                continue;
+            }
+            match (self.peek(), open) {
+                (Some('}'), Some(_)) => {
+                    self.bump();
+                    return Ok(());
+                }
+                (None, Some(open)) => return Err(self.error("unclosed `{`", open, 1)),
+                (None, None) => return Ok(()),
+                _ => self.member(body, open.is_none())?,
+            }
+        }
+    }
+
+    fn member(&mut self, body: &mut Body, in_module: bool) -> Result<()> {
+        let start = self.pos;
+        let member = if self.eat("[") {
+            let key = self.value()?;
+            self.skip_trivia()?;
+            if !self.eat("]") {
+                return Err(self.error("expected `]` after the entry's key", self.pos, 1));
+            }
+            Member::Entry(key)
+        } else {
+            match self.identifier()? {
+                Some((name, quoted)) if quoted || !VALUE_KEYWORDS.contains(&name.as_str()) => Member::Property(name),
+                _ => {
+                    // not a key, so it's an element
+                    self.pos = start;
+                    if in_module {
+                        return Err(self.error("expected a property; a module only holds properties", start, 1));
+                    }
+                    let element = self.value()?;
+                    body.elements.push(element);
+                    return Ok(());
+                }
+            }
+        };
+        if in_module && matches!(member, Member::Entry(_)) {
+            return Err(self.error("a module only holds properties", start, self.pos - start));
+        }
+
+        let key_end = self.pos;
+        self.skip_trivia()?;
+        if self.eat("=") {
+            let value = self.value()?;
+            body.set(member, value);
+        } else if self.peek() == Some('{') {
+            let base = body
+                .get(&member)
+                .map(|value| std::mem::replace(value, PklValue::Null))
+                .unwrap_or(PklValue::Object(Vec::new()));
+            let value = self.amend(base, start)?;
+            body.set(member, value);
+        } else {
+            let found = &self.source[start..key_end];
+            return Err(self.error(
+                format!("expected `=` or `{{` after `{found}`; only literal values are supported"),
+                start,
+                key_end - start,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads `{ ... }` on top of `base`.
+    fn amend(&mut self, base: PklValue, start: usize) -> Result<PklValue> {
+        let open = self.pos;
+        let mut body = Body::amending(base).map_err(|value| {
+            self.error(format!("can't amend {}", describe(&value)), start, open - start)
+        })?;
+        self.bump();
+        self.members(&mut body, Some(open))?;
+        body.into_value().map_err(|message| self.error(message, open, self.pos - open))
+    }
+
+    fn value(&mut self) -> Result<PklValue> {
+        self.skip_trivia()?;
+        let start = self.pos;
+        match self.peek() {
+            Some('"' | '#') => return self.string().map(PklValue::String),
+            Some('-') => {
+                self.bump();
+                return match self.value()? {
+                    PklValue::Int(int) => Ok(PklValue::Int(-int)),
+                    PklValue::Float(float) => Ok(PklValue::Float(-float)),
+                    _ => Err(self.error("`-` only applies to numbers", start, self.pos - start)),
+                };
+            }
+            Some(c) if c.is_ascii_digit() => return self.number(),
+            _ => {}
+        }
+        let Some((word, false)) = self.identifier()? else {
+            return Err(self.error("expected a value", start, 1));
+        };
+        match word.as_str() {
+            "null" => Ok(PklValue::Null),
+            "true" => Ok(PklValue::Boolean(true)),
+            "false" => Ok(PklValue::Boolean(false)),
+            "NaN" => Ok(PklValue::Float(f64::NAN)),
+            "Infinity" => Ok(PklValue::Float(f64::INFINITY)),
+            "new" => self.new_object(start),
+            _ => Err(self.error(
+                format!("`{word}` isn't a literal value; static Pkl can't be evaluated"),
+                start,
+                self.pos - start,
+            )),
+        }
+    }
+
+    /// `new { ... }`, with an optional type name that decides what an empty body is.
+    fn new_object(&mut self, start: usize) -> Result<PklValue> {
+        self.skip_trivia()?;
+        let mut type_name = None;
+        while let Some((name, _)) = self.identifier()? {
+            type_name = Some(name);
+            if !self.eat(".") {
+                break;
+            }
+        }
+        self.skip_trivia()?;
+        if self.peek() == Some('<') {
+            let mut depth = 0;
+            while let Some(c) = self.bump() {
+                match c {
+                    '<' => depth += 1,
+                    '>' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    break;
+                }
+            }
+            self.skip_trivia()?;
+        }
+        if self.peek() != Some('{') {
+            return Err(self.error("expected `{` after `new`", start, self.pos - start));
+        }
+        let empty = match type_name.as_deref() {
+            Some("Listing" | "List") => PklValue::Listing(Vec::new()),
+            Some("Mapping" | "Map") => PklValue::Mapping(Vec::new()),
+            _ => PklValue::Object(Vec::new()),
+        };
+        let open = self.pos;
+        self.bump();
+        let mut body = Body::new(empty);
+        self.members(&mut body, Some(open))?;
+        body.into_value().map_err(|message| self.error(message, open, self.pos - open))
+    }
+
+    fn number(&mut self) -> Result<PklValue> {
+        let start = self.pos;
+        let radix = [("0x", 16), ("0b", 2), ("0o", 8)]
+            .into_iter()
+            .find(|(prefix, _)| self.rest().starts_with(prefix));
+        if let Some((prefix, radix)) = radix {
+            self.pos += prefix.len();
+            let len = self
+                .rest()
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(self.rest().len());
+            let digits = self.rest()[..len].replace('_', "");
+            self.pos += len;
+            return i128::from_str_radix(&digits, radix)
+                .map(PklValue::Int)
+                .map_err(|_| self.error("invalid integer", start, self.pos - start));
+        }
+
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+            let fraction = c == '.' && self.rest()[1..].starts_with(|next: char| next.is_ascii_digit());
+            let exponent = c == 'e' || c == 'E';
+            let exponent_sign = (c == '-' || c == '+') && self.source[..self.pos].ends_with(['e', 'E']);
+            if c.is_ascii_digit() || c == '_' || fraction || exponent || exponent_sign {
+                is_float |= fraction || exponent;
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        let digits = self.source[start..self.pos].replace('_', "");
+        let value = if is_float {
+            digits.parse().map(PklValue::Float).ok()
+        } else {
+            digits.parse().map(PklValue::Int).ok()
+        };
+        value.ok_or_else(|| self.error("invalid number", start, self.pos - start))
+    }
+
+    /// Reads a string literal: `"..."`, `"""..."""`, or their `#"..."#` raw forms.
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.pos += hashes;
+        let multiline = self.rest().starts_with("\"\"\"");
+        let quote = if multiline { "\"\"\"" } else { "\"" };
+        if !self.eat(quote) {
+            return Err(self.error("expected a string after `#`", start, hashes));
+        }
+        let closing = format!("{}{}", quote, "#".repeat(hashes));
+        let escape = format!("\\{}", "#".repeat(hashes));
+
+        let mut raw = String::new();
+        loop {
+            if self.eat(&closing) {
+                break;
+            }
+            if self.eat(&escape) {
+                raw.push_str(&escape);
+                if let Some(c) = self.bump() {
+                    raw.push(c);
+                }
+                continue;
+            }
+            match self.bump() {
+                Some('\n') if !multiline => return Err(self.error("unterminated string", start, self.pos - start)),
+                Some(c) => raw.push(c),
+                None => return Err(self.error("unterminated string", start, self.pos - start)),
+            }
+        }
+        let raw = if multiline { trim_multiline(&raw) } else { raw };
+        self.unescape(&raw, &escape, start)
+    }
+
+    fn unescape(&self, raw: &str, escape: &str, start: usize) -> Result<String> {
+        let len = self.pos - start;
+        let mut value = String::new();
+        let mut rest = raw;
+        while let Some(index) = rest.find(escape) {
+            value.push_str(&rest[..index]);
+            rest = &rest[index + escape.len()..];
+            let mut chars = rest.chars();
+            let escaped = match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some(c @ ('"' | '\\')) => c,
+                Some('u') => {
+                    let code = chars
+                        .as_str()
+                        .strip_prefix('{')
+                        .and_then(|code| code.split_once('}'))
+                        .and_then(|(hex, after)| {
+                            let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)?;
+                            Some((c, after))
+                        });
+                    let Some((c, after)) = code else {
+                        return Err(self.error("invalid unicode escape", start, len));
+                    };
+                    value.push(c);
+                    rest = after;
+                    continue;
+                }
+                Some('(') => return Err(self.error("string interpolation can't be evaluated in static Pkl", start, len)),
+                Some(c) => return Err(self.error(format!("invalid escape `{escape}{c}`"), start, len)),
+                None => return Err(self.error("unterminated string", start, len)),
+            };
+            value.push(escaped);
+            rest = chars.as_str();
+        }
+        value.push_str(rest);
+        Ok(value)
+    }
+}
+
+// ============================== Deserializer ==============================
+
+impl<'de> IntoDeserializer<'de, CliError> for PklValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for PklValue {
+    type Error = CliError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            PklValue::Null => visitor.visit_unit(),
+            PklValue::Boolean(boolean) => visitor.visit_bool(boolean),
+            PklValue::Int(int) => {
+                if let Ok(int) = i64::try_from(int) {
+                    visitor.visit_i64(int)
+                } else if let Ok(int) = u64::try_from(int) {
+                    visitor.visit_u64(int)
+                } else {
+                    visitor.visit_i128(int)
+                }
+            }
+            PklValue::Float(float) => visitor.visit_f64(float),
+            PklValue::String(string) => visitor.visit_string(string),
+            PklValue::Listing(elements) => visit_seq(elements, visitor),
+            PklValue::Mapping(entries) => visit_map(MapDeserializer::new(entries.into_iter()), visitor),
+            PklValue::Object(properties) => visit_map(MapDeserializer::new(properties.into_iter()), visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            PklValue::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            // `name {}` doesn't say what it is, so an empty object will do for an empty listing
+            PklValue::Object(properties) if properties.is_empty() => visit_seq(Vec::new(), visitor),
+            PklValue::Mapping(entries) if entries.is_empty() => visit_seq(Vec::new(), visitor),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let tagged = match self {
+            PklValue::String(variant) => return visitor.visit_enum(StringDeserializer::<CliError>::new(variant)),
+            PklValue::Object(mut properties) if properties.len() == 1 => properties.pop(),
+            PklValue::Mapping(mut entries) if entries.len() == 1 => match entries.pop() {
+                Some((PklValue::String(variant), value)) => Some((variant, value)),
+                _ => None,
+            },
+            ref other => {
+                return Err(de::Error::custom(format!(
+                    "expected an enum variant as a string or a single property, found {}",
+                    describe(other)
+                )));
+            }
+        };
+        match tagged {
+            Some((variant, value)) => visitor.visit_enum(EnumDeserializer { variant, value }),
+            None => Err(de::Error::custom("expected an enum variant with a string name")),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
+
+fn visit_seq<'de, V: Visitor<'de>>(elements: Vec<PklValue>, visitor: V) -> Result<V::Value> {
+    let mut seq = SeqDeserializer::new(elements.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, V, I, K>(mut map: MapDeserializer<'de, I, CliError>, visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+    I: Iterator<Item = (K, PklValue)>,
+    K: IntoDeserializer<'de, CliError>,
+{
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+/// An externally tagged variant: `Variant { ... }` or `Variant = value`.
+struct EnumDeserializer {
+    variant: String,
+    value: PklValue,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = CliError;
+    type Variant = PklValue;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, PklValue)> {
+        let variant = seed.deserialize(StringDeserializer::<CliError>::new(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for PklValue {
+    type Error = CliError;
+
+    fn unit_variant(self) -> Result<()> {
+        match self {
+            PklValue::Null => Ok(()),
+            PklValue::Object(properties) if properties.is_empty() => Ok(()),
+            other => Err(de::Error::custom(format!("expected a unit variant, found {}", describe(&other)))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
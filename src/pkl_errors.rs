@@ -0,0 +1,256 @@
+//! Pkl Error Parsing for Space Pklr
+//!
+//! Reads the errors Pkl prints when evaluation fails, so they can be reported as diagnostics pointing at
+//! the offending line rather than as raw stderr:
+//!
+//! ```text
+//! –– Pkl Error ––
+//! Expected value of type `Int`, but got type `String`.
+//!
+//! 3 | port: Int = "8080"
+//!                 ^^^^^^
+//! at moon#port (file:///repo/moon.pkl, line 3)
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+
+use miette::Diagnostic;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::types::CliError;
+
+/// One `at ...` line of a Pkl error's stack trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PklStackFrame {
+    /// What was being evaluated, like `moon#port`
+    pub member: String,
+    /// The module's URI or path, like `file:///repo/moon.pkl`
+    pub location: String,
+    /// 1-based line, from the frame or its source excerpt
+    pub line: Option<usize>,
+    /// 1-based column, from the excerpt's carets or the frame
+    pub column: Option<usize>,
+    /// Characters the excerpt's carets underline
+    pub length: Option<usize>,
+    /// The excerpt's source line
+    pub source_line: Option<String>,
+}
+
+impl fmt::Display for PklStackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {} ({}", self.member, self.location)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+/// A Pkl error, as read from its output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PklError {
+    pub message: String,
+    /// Innermost first
+    pub frames: Vec<PklStackFrame>,
+    /// Anything Pkl printed after the stack trace, such as suggestions
+    pub hints: Vec<String>,
+}
+
+impl PklError {
+    /// The error as a diagnostic with the first frame's source, when it can be read, and the whole trace
+    pub fn into_cli_error(self, command: impl Into<String>, help: Option<String>) -> CliError {
+        let (src, span) = self.frames.iter().find_map(frame_source).unzip();
+
+        let mut help_lines: Vec<String> = self.hints;
+        if !self.frames.is_empty() {
+            help_lines.push(format!(
+                "Pkl stack:\n{}",
+                self.frames.iter().map(|frame| format!("  {}", frame)).collect::<Vec<_>>().join("\n")
+            ));
+        }
+        help_lines.extend(help);
+
+        CliError::PklEvaluationFailed(Box::new(PklEvaluationError {
+            command: command.into(),
+            message: self.message,
+            frames: self.frames,
+            src,
+            span,
+            help: (!help_lines.is_empty()).then(|| help_lines.join("\n")),
+        }))
+    }
+}
+
+/// Pkl's error evaluating a module, pointing at the line it blames
+#[derive(Error, Diagnostic, Debug)]
+#[error("Pkl evaluation failed: {message}")]
+#[diagnostic(code(cli::pkl_evaluation_failed))]
+pub struct PklEvaluationError {
+    pub command: String,
+    pub message: String,
+    /// Pkl's stack trace, innermost first
+    pub frames: Vec<PklStackFrame>,
+    #[source_code]
+    pub src: Option<miette::NamedSource<String>>,
+    #[label("here")]
+    pub span: Option<miette::SourceSpan>,
+    #[help]
+    pub help: Option<String>,
+}
+
+/// Parse Pkl's error output, or `None` if `stderr` isn't a Pkl error
+pub fn parse_pkl_error(stderr: &str) -> Option<PklError> {
+    let ansi = Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").ok()?;
+    let header = Regex::new(r"^[–—-]+\s*Pkl Error\s*[–—-]+$").ok()?;
+    let excerpt_line = Regex::new(r"^\s*(\d+) \| (.*)$").ok()?;
+    let carets = Regex::new(r"^(\s*)(\^+)\s*$").ok()?;
+    let frame_line = Regex::new(r"^at (.+?) \((.+)\)$").ok()?;
+
+    let stderr = ansi.replace_all(stderr, "");
+    let mut lines = stderr.lines().map(str::trim_end).skip_while(|line| !header.is_match(line.trim()));
+    lines.next()?;
+
+    let mut message: Vec<&str> = Vec::new();
+    let mut frames = Vec::new();
+    let mut hints = Vec::new();
+    let mut excerpt: Option<Excerpt> = None;
+    // where the source starts on an excerpt line, to line the carets up against
+    let mut excerpt_indent = 0;
+
+    for line in lines {
+        if let Some(captures) = excerpt_line.captures(line) {
+            let number = captures[1].parse().unwrap_or_default();
+            excerpt_indent = captures.get(2).map_or(0, |source| source.start());
+            excerpt = Some(Excerpt {
+                line: number,
+                source: captures[2].to_string(),
+                underline: None,
+            });
+        } else if let Some(captures) = carets.captures(line)
+            && let Some(excerpt) = excerpt.as_mut()
+        {
+            let column = captures[1].len().saturating_sub(excerpt_indent) + 1;
+            excerpt.underline = Some((column, captures[2].len()));
+        } else if let Some(captures) = frame_line.captures(line) {
+            let (location, line, column) = split_location(&captures[2]);
+            let mut frame = PklStackFrame {
+                member: captures[1].to_string(),
+                location,
+                line,
+                column,
+                length: None,
+                source_line: None,
+            };
+            if let Some(excerpt) = excerpt.take() {
+                frame.line = frame.line.or(Some(excerpt.line));
+                frame.source_line = Some(excerpt.source);
+                if let Some((column, length)) = excerpt.underline {
+                    frame.column = Some(column);
+                    frame.length = Some(length);
+                }
+            }
+            frames.push(frame);
+        } else if frames.is_empty() && excerpt.is_none() {
+            message.push(line);
+        } else if !line.trim().is_empty() {
+            hints.push(line.trim().to_string());
+        }
+    }
+
+    let message = message.join("\n").trim().to_string();
+    if message.is_empty() {
+        return None;
+    }
+    Some(PklError {
+        message,
+        frames,
+        hints,
+    })
+}
+
+/// The source excerpt Pkl prints before a frame
+struct Excerpt {
+    line: usize,
+    source: String,
+    /// Where the carets under the source start, and how many there are
+    underline: Option<(usize, usize)>,
+}
+
+/// Split a frame's location into the module and position: `uri, line 3`, `path:3:7` and `path:3` are all read
+fn split_location(location: &str) -> (String, Option<usize>, Option<usize>) {
+    let location = location.trim();
+    if let Some((module, line)) = location.rsplit_once(", line ")
+        && let Ok(line) = line.trim().parse()
+    {
+        return (module.to_string(), Some(line), None);
+    }
+
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next().and_then(|part| part.parse::<usize>().ok());
+    let second = parts.next();
+    match (last, second.and_then(|part| part.parse::<usize>().ok()), parts.next()) {
+        (Some(column), Some(line), Some(module)) => (module.to_string(), Some(line), Some(column)),
+        (Some(line), _, _) => {
+            let module = location.rsplit_once(':').map_or(location, |(module, _)| module);
+            (module.to_string(), Some(line), None)
+        }
+        _ => (location.to_string(), None, None),
+    }
+}
+
+/// The source a frame points into and the span there: the module file if it can be read, and otherwise
+/// just the excerpt Pkl printed
+fn frame_source(frame: &PklStackFrame) -> Option<(miette::NamedSource<String>, miette::SourceSpan)> {
+    let line = frame.line?;
+    let path = module_path(&frame.location);
+
+    if let Some(path) = path
+        && let Ok(content) = std::fs::read_to_string(&path)
+        && let Some(span) = line_span(&content, line, frame.column, frame.length)
+    {
+        return Some((miette::NamedSource::new(path.display().to_string(), content), span));
+    }
+
+    let source_line = frame.source_line.clone()?;
+    let span = line_span(&source_line, 1, frame.column, frame.length)?;
+    Some((miette::NamedSource::new(format!("{}:{}", frame.location, line), source_line), span))
+}
+
+/// The file a module location refers to, if it's a file
+fn module_path(location: &str) -> Option<PathBuf> {
+    if location.starts_with("file:") {
+        // `file:///tmp/a%20b.pkl`, percent-encoded
+        return url::Url::parse(location).ok()?.to_file_path().ok();
+    }
+    (!location.contains("://") && !location.starts_with("repl:")).then(|| PathBuf::from(location))
+}
+
+/// The span of `length` characters from `column` on 1-based `line`, or the line's text without its indent
+fn line_span(content: &str, line: usize, column: Option<usize>, length: Option<usize>) -> Option<miette::SourceSpan> {
+    let mut offset = 0;
+    for (index, text) in content.split_inclusive('\n').enumerate() {
+        if index + 1 == line {
+            let text = text.trim_end_matches(['\r', '\n']);
+            let indent = text.len() - text.trim_start().len();
+            let (start, len) = match column {
+                Some(column) => {
+                    let start = text.char_indices().nth(column.saturating_sub(1)).map_or(text.len(), |(at, _)| at);
+                    let end = text[start..]
+                        .char_indices()
+                        .nth(length.unwrap_or(1))
+                        .map_or(text.len(), |(at, _)| start + at);
+                    (start, end - start)
+                }
+                None => (indent, text.len() - indent),
+            };
+            return Some((offset + start, len).into());
+        }
+        offset += text.len();
+    }
+    None
+}
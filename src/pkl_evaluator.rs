@@ -0,0 +1,604 @@
+//! Pkl Evaluator
+//!
+//! A client for Pkl's [message passing API](https://pkl-lang.org/main/current/bindings-specification/message-passing-api.html):
+//! one `pkl server` process, started once, evaluates as many modules as it's asked to. Each `pkl eval` pays for
+//! a JVM or native-image startup, which adds up quickly when a whole workspace is converted.
+//!
+//! ```rust,ignore
+//! use space_pklr::pkl_evaluator::{EvaluatorOptions, PklEvaluator};
+//!
+//! let mut evaluator = PklEvaluator::start(&pkl_cli, EvaluatorOptions::default()).await?;
+//! let json = evaluator.evaluate_file(Path::new("moon.pkl")).await?;
+//! evaluator.close().await?;
+//! ```
+//!
+//! ## Module readers
+//!
+//! The server asks the client for modules whose URI scheme a [`ModuleReader`] was registered for, as
+//! evaluation imports or amends them. [`InMemoryModules`] serves modules held in memory, so generated Pkl can be
+//! evaluated without writing it out first.
+//!
+//! ## Sharing
+//!
+//! Messages are MessagePack arrays of a type code and a map of fields, and a [`PklEvaluator`] sends one
+//! request at a time. Tasks share one through a [`SharedEvaluator`], which takes turns and only starts the
+//! server once something needs evaluating.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+
+use rmpv::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::Child;
+use tokio::sync::Mutex;
+
+use crate::pkl_tooling::{DEFAULT_PROCESS_TIMEOUT, PklCli, configured_timeout, pkl_command};
+use crate::types::{CliError, pkl_execution_error};
+
+const CREATE_EVALUATOR_REQUEST: u8 = 0x20;
+const CREATE_EVALUATOR_RESPONSE: u8 = 0x21;
+const CLOSE_EVALUATOR: u8 = 0x22;
+const EVALUATE_REQUEST: u8 = 0x23;
+const EVALUATE_RESPONSE: u8 = 0x24;
+const LOG_MESSAGE: u8 = 0x25;
+const READ_RESOURCE_REQUEST: u8 = 0x26;
+const READ_RESOURCE_RESPONSE: u8 = 0x27;
+const READ_MODULE_REQUEST: u8 = 0x28;
+const READ_MODULE_RESPONSE: u8 = 0x29;
+const LIST_RESOURCES_REQUEST: u8 = 0x2a;
+const LIST_RESOURCES_RESPONSE: u8 = 0x2b;
+const LIST_MODULES_REQUEST: u8 = 0x2c;
+const LIST_MODULES_RESPONSE: u8 = 0x2d;
+
+/// The module URI schemes `pkl eval` allows; readers' schemes are added to these
+const ALLOWED_MODULES: [&str; 8] = ["pkl:", "repl:", "file:", "http:", "https:", "modulepath:", "package:", "projectpackage:"];
+
+/// The resource URI schemes `pkl eval` allows
+const ALLOWED_RESOURCES: [&str; 8] = ["env:", "prop:", "file:", "http:", "https:", "modulepath:", "package:", "projectpackage:"];
+
+/// How the server evaluates modules
+#[derive(Clone)]
+pub struct EvaluatorOptions {
+    /// The format evaluated modules are rendered in: `json`, `yaml`, `pcf`, `plist`, `properties`, `textproto`
+    /// or `xml`
+    pub output_format: String,
+    /// Environment variables modules can `read("env:...")`
+    pub env: BTreeMap<String, String>,
+    /// Directories and archives `modulepath:` URIs are resolved against
+    pub module_paths: Vec<PathBuf>,
+    /// When given, files outside this directory can't be read
+    pub root_dir: Option<PathBuf>,
+    /// Longest one evaluation may take
+    pub timeout_seconds: Option<u64>,
+    /// Readers for module URI schemes the server doesn't know
+    pub module_readers: Vec<Arc<dyn ModuleReader>>,
+}
+
+impl Default for EvaluatorOptions {
+    fn default() -> Self {
+        Self {
+            output_format: "json".to_string(),
+            env: BTreeMap::new(),
+            module_paths: Vec::new(),
+            root_dir: None,
+            timeout_seconds: None,
+            module_readers: Vec::new(),
+        }
+    }
+}
+
+/// Reads modules for a URI scheme on the server's behalf
+pub trait ModuleReader: Send + Sync {
+    /// The scheme read, without the `:`
+    fn scheme(&self) -> &str;
+
+    /// Whether URIs have paths, so relative imports resolve against them
+    fn has_hierarchical_uris(&self) -> bool {
+        true
+    }
+
+    /// Whether `import*` can glob these URIs; [`ModuleReader::list`] has to list them if so
+    fn is_globbable(&self) -> bool {
+        false
+    }
+
+    /// Whether modules are read from this machine; only local modules can import `file:` ones
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    /// The source of the module at `uri`
+    fn read(&self, uri: &str) -> Result<String, String>;
+
+    /// The modules and directories directly under `uri`
+    fn list(&self, _uri: &str) -> Result<Vec<PathElement>, String> {
+        Ok(Vec::new())
+    }
+}
+
+/// A module or directory a [`ModuleReader`] lists
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PathElement {
+    pub name: String,
+    pub is_directory: bool,
+}
+
+/// Modules held in memory, read as `<scheme>:/<path>`
+#[derive(Debug, Clone)]
+pub struct InMemoryModules {
+    scheme: String,
+    modules: HashMap<String, String>,
+}
+
+impl InMemoryModules {
+    pub fn new(scheme: impl Into<String>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Add the module at `path`, like `moon.pkl` or `lib/base.pkl`
+    pub fn with_module(mut self, path: impl Into<String>, source: impl Into<String>) -> Self {
+        self.insert(path, source);
+        self
+    }
+
+    /// Add or replace the module at `path`
+    pub fn insert(&mut self, path: impl Into<String>, source: impl Into<String>) {
+        let path = path.into();
+        self.modules.insert(path.trim_start_matches('/').to_string(), source.into());
+    }
+
+    /// The URI the module at `path` is read from
+    pub fn uri(&self, path: &str) -> String {
+        format!("{}:/{}", self.scheme, path.trim_start_matches('/'))
+    }
+
+    /// The path `uri` names, if it's one of ours
+    fn path_of<'a>(&self, uri: &'a str) -> Option<&'a str> {
+        let path = uri.strip_prefix(self.scheme.as_str())?.strip_prefix(':')?;
+        Some(path.trim_start_matches('/'))
+    }
+}
+
+impl ModuleReader for InMemoryModules {
+    fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    fn is_globbable(&self) -> bool {
+        true
+    }
+
+    fn read(&self, uri: &str) -> Result<String, String> {
+        self.path_of(uri)
+            .and_then(|path| self.modules.get(path))
+            .cloned()
+            .ok_or_else(|| format!("No module at {uri}"))
+    }
+
+    fn list(&self, uri: &str) -> Result<Vec<PathElement>, String> {
+        let dir = self.path_of(uri).ok_or_else(|| format!("Cannot list {uri}"))?.trim_end_matches('/');
+        let mut elements: Vec<PathElement> = self
+            .modules
+            .keys()
+            .filter_map(|path| match dir {
+                "" => Some(path.as_str()),
+                dir => path.strip_prefix(dir)?.strip_prefix('/'),
+            })
+            .map(|rest| match rest.split_once('/') {
+                Some((name, _)) => PathElement {
+                    name: name.to_string(),
+                    is_directory: true,
+                },
+                None => PathElement {
+                    name: rest.to_string(),
+                    is_directory: false,
+                },
+            })
+            .collect();
+        elements.sort();
+        elements.dedup();
+        Ok(elements)
+    }
+}
+
+/// A message's fields
+type Fields = Vec<(Value, Value)>;
+
+/// An evaluator in a long-lived `pkl server`
+pub struct PklEvaluator {
+    /// The server process, when we started it
+    child: Option<Child>,
+    reader: Box<dyn AsyncRead + Send + Unpin>,
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+    /// Bytes read but not yet decoded
+    buffer: Vec<u8>,
+    evaluator_id: i64,
+    next_request_id: i64,
+    module_readers: Vec<Arc<dyn ModuleReader>>,
+}
+
+impl PklEvaluator {
+    /// Start `pkl server` with the Pkl CLI `pkl_cli` finds, and create an evaluator in it
+    pub async fn start(pkl_cli: &PklCli, options: EvaluatorOptions) -> Result<Self, CliError> {
+        let mut command = tokio::process::Command::from(pkl_command(pkl_cli, &["server".to_string()]));
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+        let mut child = command.spawn().map_err(|e| {
+            pkl_execution_error(
+                format!("{:?}", command.as_std()),
+                e.to_string(),
+                Some("Check that Pkl CLI is properly installed and accessible".to_string()),
+            )
+        })?;
+
+        let (Some(stdout), Some(stdin)) = (child.stdout.take(), child.stdin.take()) else {
+            unreachable!("the server's stdio is piped");
+        };
+        let mut evaluator = Self::connect(stdout, stdin, options).await?;
+        evaluator.child = Some(child);
+        Ok(evaluator)
+    }
+
+    /// Create an evaluator in a server that's read from `reader` and written to with `writer`
+    pub async fn connect(
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        options: EvaluatorOptions,
+    ) -> Result<Self, CliError> {
+        let mut evaluator = Self {
+            child: None,
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            buffer: Vec::new(),
+            evaluator_id: 0,
+            next_request_id: 1,
+            module_readers: options.module_readers.clone(),
+        };
+
+        let request_id = evaluator.request_id();
+        let allowed_modules = ALLOWED_MODULES
+            .iter()
+            .map(|scheme| scheme.to_string())
+            .chain(options.module_readers.iter().map(|reader| format!("{}:", reader.scheme())));
+        let mut fields = vec![
+            field("requestId", request_id),
+            field("allowedModules", strings(allowed_modules)),
+            field("allowedResources", strings(ALLOWED_RESOURCES.iter().map(|scheme| scheme.to_string()))),
+            field("outputFormat", options.output_format.as_str()),
+        ];
+        if !options.module_readers.is_empty() {
+            let readers = options
+                .module_readers
+                .iter()
+                .map(|reader| {
+                    Value::Map(vec![
+                        field("scheme", reader.scheme()),
+                        field("hasHierarchicalUris", reader.has_hierarchical_uris()),
+                        field("isGlobbable", reader.is_globbable()),
+                        field("isLocal", reader.is_local()),
+                    ])
+                })
+                .collect();
+            fields.push(field("clientModuleReaders", Value::Array(readers)));
+        }
+        if !options.env.is_empty() {
+            let env = options.env.iter().map(|(name, value)| field(name, value.as_str())).collect();
+            fields.push(field("env", Value::Map(env)));
+        }
+        if !options.module_paths.is_empty() {
+            let paths = options.module_paths.iter().map(|path| path.display().to_string());
+            fields.push(field("modulePaths", strings(paths)));
+        }
+        if let Some(root_dir) = &options.root_dir {
+            fields.push(field("rootDir", root_dir.display().to_string()));
+        }
+        if let Some(timeout) = options.timeout_seconds {
+            fields.push(field("timeoutSeconds", timeout));
+        }
+
+        evaluator.send(CREATE_EVALUATOR_REQUEST, fields).await?;
+        let response = evaluator.receive_response(CREATE_EVALUATOR_RESPONSE, request_id).await?;
+        if let Some(error) = get(&response, "error").and_then(Value::as_str) {
+            return Err(server_error(format!("Couldn't create an evaluator: {error}")));
+        }
+        evaluator.evaluator_id = get(&response, "evaluatorId")
+            .and_then(Value::as_i64)
+            .ok_or_else(|| server_error("The server created an evaluator without an ID"))?;
+        Ok(evaluator)
+    }
+
+    /// Evaluate the Pkl file at `path`, returning its output
+    pub async fn evaluate_file(&mut self, path: &Path) -> Result<String, CliError> {
+        let path = std::fs::canonicalize(path).map_err(|e| CliError::IoError {
+            context: format!("Resolving Pkl module: {}", path.display()),
+            source: e,
+        })?;
+        let uri = url::Url::from_file_path(&path)
+            .map_err(|()| CliError::Generic(format!("Pkl module path has no file: URI: {}", path.display())))?;
+        self.evaluate_module(uri.as_str()).await
+    }
+
+    /// Evaluate the module at `uri`, which can be one a [`ModuleReader`] reads, returning its output
+    pub async fn evaluate_module(&mut self, uri: &str) -> Result<String, CliError> {
+        self.evaluate_output(uri, None).await
+    }
+
+    /// Evaluate Pkl source, returning its output
+    pub async fn evaluate_text(&mut self, source: &str) -> Result<String, CliError> {
+        self.evaluate_output("repl:text", Some(source)).await
+    }
+
+    async fn evaluate_output(&mut self, uri: &str, source: Option<&str>) -> Result<String, CliError> {
+        match self.evaluate(uri, source, Some("output.text")).await? {
+            Value::String(text) => text
+                .into_str()
+                .ok_or_else(|| server_error(format!("The output of {uri} isn't UTF-8"))),
+            other => Err(server_error(format!("Expected the output of {uri} to be text, got {other}"))),
+        }
+    }
+
+    /// Evaluate `expr` (the whole module without one) in the module at `uri`, or in `source` when given. The
+    /// result is in Pkl's binary encoding.
+    pub async fn evaluate(&mut self, uri: &str, source: Option<&str>, expr: Option<&str>) -> Result<Value, CliError> {
+        let request_id = self.request_id();
+        let mut fields = vec![
+            field("requestId", request_id),
+            field("evaluatorId", self.evaluator_id),
+            field("moduleUri", uri),
+        ];
+        if let Some(source) = source {
+            fields.push(field("moduleText", source));
+        }
+        if let Some(expr) = expr {
+            fields.push(field("expr", expr));
+        }
+        self.send(EVALUATE_REQUEST, fields).await?;
+
+        let response = self.receive_response(EVALUATE_RESPONSE, request_id).await?;
+        if let Some(error) = get(&response, "error").and_then(Value::as_str) {
+            return Err(pkl_execution_error(
+                format!("pkl server: evaluate {uri}"),
+                error,
+                Some("Check Pkl syntax and file paths".to_string()),
+            ));
+        }
+        let Some(Value::Binary(result)) = get(&response, "result") else {
+            return Err(server_error(format!("The server evaluated {uri} without a result")));
+        };
+        rmpv::decode::read_value(&mut Cursor::new(result))
+            .map_err(|e| server_error(format!("Couldn't decode the result of {uri}: {e}")))
+    }
+
+    /// Close the evaluator and stop the server, if we started it
+    pub async fn close(mut self) -> Result<(), CliError> {
+        let fields = vec![field("evaluatorId", self.evaluator_id)];
+        self.send(CLOSE_EVALUATOR, fields).await?;
+        let _ = self.writer.shutdown().await;
+        if let Some(mut child) = self.child.take() {
+            child.kill().await.map_err(|e| CliError::IoError {
+                context: "Stopping pkl server".to_string(),
+                source: e,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn request_id(&mut self) -> i64 {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+        id
+    }
+
+    async fn send(&mut self, code: u8, fields: Fields) -> Result<(), CliError> {
+        let message = Value::Array(vec![Value::from(code), Value::Map(fields)]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &message)
+            .map_err(|e| server_error(format!("Couldn't encode a message: {e}")))?;
+
+        let io_error = |e| CliError::IoError {
+            context: "Writing to pkl server".to_string(),
+            source: e,
+        };
+        self.writer.write_all(&bytes).await.map_err(io_error)?;
+        self.writer.flush().await.map_err(io_error)
+    }
+
+    async fn receive(&mut self) -> Result<(u8, Fields), CliError> {
+        use rmpv::decode::Error;
+
+        loop {
+            if !self.buffer.is_empty() {
+                let mut cursor = Cursor::new(self.buffer.as_slice());
+                match rmpv::decode::read_value(&mut cursor) {
+                    Ok(message) => {
+                        let used = cursor.position() as usize;
+                        self.buffer.drain(..used);
+                        return parse_message(message);
+                    }
+                    // only part of the message has arrived
+                    Err(Error::InvalidMarkerRead(e) | Error::InvalidDataRead(e))
+                        if e.kind() == std::io::ErrorKind::UnexpectedEof => {}
+                    Err(e) => return Err(server_error(format!("Couldn't decode a message: {e}"))),
+                }
+            }
+
+            let mut chunk = [0; 8192];
+            let read = self.reader.read(&mut chunk).await.map_err(|e| CliError::IoError {
+                context: "Reading from pkl server".to_string(),
+                source: e,
+            })?;
+            if read == 0 {
+                return Err(server_error("The server stopped before answering"));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    /// Wait for the `code` response to request `request_id`, answering the server's own requests meanwhile
+    async fn receive_response(&mut self, code: u8, request_id: i64) -> Result<Fields, CliError> {
+        loop {
+            let (received, fields) = self.receive().await?;
+            if received == code && get(&fields, "requestId").and_then(Value::as_i64) == Some(request_id) {
+                return Ok(fields);
+            }
+            self.answer(received, &fields).await?;
+        }
+    }
+
+    /// Answer a request from the server, or log what it says
+    async fn answer(&mut self, code: u8, fields: &Fields) -> Result<(), CliError> {
+        let request_id = get(fields, "requestId").cloned().unwrap_or(Value::Nil);
+        let uri = get(fields, "uri").and_then(Value::as_str).unwrap_or_default();
+        let reader = self
+            .module_readers
+            .iter()
+            .find(|reader| uri.split(':').next() == Some(reader.scheme()))
+            .cloned();
+        let mut response = vec![field("requestId", request_id), field("evaluatorId", self.evaluator_id)];
+
+        let response_code = match code {
+            LOG_MESSAGE => {
+                let message = get(fields, "message").and_then(Value::as_str).unwrap_or_default();
+                let frame = get(fields, "frameUri").and_then(Value::as_str).unwrap_or_default();
+                match get(fields, "level").and_then(Value::as_i64) {
+                    Some(0) => tracing::trace!("pkl: {message} ({frame})"),
+                    _ => tracing::warn!("pkl: {message} ({frame})"),
+                }
+                return Ok(());
+            }
+            READ_MODULE_REQUEST => {
+                match reader.ok_or_else(|| format!("No reader for {uri}")).and_then(|reader| reader.read(uri)) {
+                    Ok(contents) => response.push(field("contents", contents)),
+                    Err(error) => response.push(field("error", error)),
+                }
+                READ_MODULE_RESPONSE
+            }
+            LIST_MODULES_REQUEST => {
+                match reader.ok_or_else(|| format!("No reader for {uri}")).and_then(|reader| reader.list(uri)) {
+                    Ok(elements) => {
+                        let elements = elements
+                            .into_iter()
+                            .map(|element| {
+                                Value::Map(vec![
+                                    field("name", element.name),
+                                    field("isDirectory", element.is_directory),
+                                ])
+                            })
+                            .collect();
+                        response.push(field("pathElements", Value::Array(elements)));
+                    }
+                    Err(error) => response.push(field("error", error)),
+                }
+                LIST_MODULES_RESPONSE
+            }
+            READ_RESOURCE_REQUEST | LIST_RESOURCES_REQUEST => {
+                response.push(field("error", format!("No resource reader for {uri}")));
+                if code == READ_RESOURCE_REQUEST {
+                    READ_RESOURCE_RESPONSE
+                } else {
+                    LIST_RESOURCES_RESPONSE
+                }
+            }
+            code => {
+                tracing::debug!("Ignoring pkl server message {code:#x}");
+                return Ok(());
+            }
+        };
+        self.send(response_code, response).await
+    }
+}
+
+/// A [`PklEvaluator`] tasks can share. The server is started with the installed Pkl CLI the first time something
+/// needs evaluating, and evaluates one module at a time, the way `pkl eval` would: modules can read the process
+/// environment, and each evaluation gets the same timeout as other Pkl commands.
+#[derive(Clone, Default)]
+pub struct SharedEvaluator {
+    evaluator: Arc<Mutex<Option<PklEvaluator>>>,
+}
+
+impl SharedEvaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Share an evaluator that's already running
+    pub fn with_evaluator(evaluator: PklEvaluator) -> Self {
+        Self {
+            evaluator: Arc::new(Mutex::new(Some(evaluator))),
+        }
+    }
+
+    /// Evaluate the Pkl file at `path` to JSON. Without a Pkl CLI this is a [`CliError::PklInstallFailed`].
+    pub async fn evaluate_to_json(&self, path: &Path) -> Result<String, CliError> {
+        let mut evaluator = self.evaluator.lock().await;
+        if evaluator.is_none() {
+            let pkl_cli = crate::_rewrite::ensure_pkl_available().await?;
+            *evaluator = Some(PklEvaluator::start(&pkl_cli, eval_options()).await?);
+        }
+        let Some(evaluator) = evaluator.as_mut() else {
+            unreachable!("the evaluator was just started");
+        };
+        evaluator.evaluate_file(path).await
+    }
+
+    /// Close the evaluator, if it was started
+    pub async fn close(&self) -> Result<(), CliError> {
+        match self.evaluator.lock().await.take() {
+            Some(evaluator) => evaluator.close().await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Options matching a `pkl eval` run: the process environment, and the timeout Pkl commands get
+fn eval_options() -> EvaluatorOptions {
+    let env = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect();
+    let timeout = configured_timeout(DEFAULT_PROCESS_TIMEOUT);
+    EvaluatorOptions {
+        env,
+        timeout_seconds: Some(timeout.as_secs_f64().ceil() as u64),
+        ..Default::default()
+    }
+}
+
+fn field(name: &str, value: impl Into<Value>) -> (Value, Value) {
+    (Value::from(name), value.into())
+}
+
+fn strings(values: impl Iterator<Item = String>) -> Value {
+    Value::Array(values.map(Value::from).collect())
+}
+
+fn get<'a>(fields: &'a Fields, name: &str) -> Option<&'a Value> {
+    fields.iter().find(|(key, _)| key.as_str() == Some(name)).map(|(_, value)| value)
+}
+
+/// Split a message into its type code and fields
+fn parse_message(message: Value) -> Result<(u8, Fields), CliError> {
+    let Value::Array(mut parts) = message else {
+        return Err(server_error(format!("Expected a message, got {message}")));
+    };
+    match (parts.pop(), parts.pop()) {
+        (Some(Value::Map(fields)), Some(code)) if parts.is_empty() => code
+            .as_u64()
+            .and_then(|code| u8::try_from(code).ok())
+            .map(|code| (code, fields))
+            .ok_or_else(|| server_error(format!("Unknown message type {code}"))),
+        _ => Err(server_error("Expected a message type and fields")),
+    }
+}
+
+fn server_error(reason: impl Into<String>) -> CliError {
+    pkl_execution_error("pkl server", reason, Some("Check that the Pkl CLI is 0.25 or later".to_string()))
+}
@@ -0,0 +1,1182 @@
+//! Pkl Schema Importer
+//!
+//! Reads Pkl schema modules -- the ones spklr generates, or hand-edited ones like `.schema/Project.pkl` --
+//! back into a schematic [`TypeMap`]. Once a schema is a `TypeMap` you can diff it, re-render it, or hand it
+//! to schematic's JSON Schema and TypeScript renderers, all without the Pkl CLI.
+//!
+//! ## What gets read
+//!
+//! - `class` definitions become structs. `extends` is followed when the parent is in the same module.
+//! - `typealias` definitions become named types. Unions made only of string literals become enums.
+//! - Module-level properties become a struct named after the module.
+//! - `Listing`/`List`/`Set`, `Mapping`/`Map`, `Pair`, nullable `?` types and `*` union defaults.
+//! - Common constraints: `isBetween`, `length.isBetween`, comparisons against `this`,
+//!   `matches(Regex(...))`, `!isEmpty`/`!isBlank`, `isPositive` and `isDistinct`.
+//! - Doc comments (`///`), `@Deprecated`, and `hidden`/`fixed`/`const` modifiers.
+//!
+//! ## What gets skipped
+//!
+//! Functions, `amends`/`extends`/`import` clauses (imported types are kept as references), object
+//! amendments, and any default that isn't a plain literal. A property with a non-literal default is
+//! still marked optional.
+
+use std::path::Path;
+
+use miette::{NamedSource, SourceSpan};
+use schematic::schema::{
+    ArrayType, BooleanType, EnumType, FloatKind, FloatType, IntegerKind, IntegerType, LiteralType,
+    LiteralValue, ObjectType, Schema, SchemaField, SchemaType, StringType, StructType, TupleType,
+    UnionType,
+};
+
+use crate::types::{CliError, Result, TypeMap};
+
+/// Imports a Pkl schema module from source text.
+///
+/// Module-level properties are collected into a struct named after the `module` clause, or `Module`
+/// if the source doesn't have one.
+pub fn import_pkl_schema(source: &str) -> Result<TypeMap> {
+    import_pkl_source("<input>", "Module", source)
+}
+
+/// Imports a Pkl schema module from a file.
+///
+/// Module-level properties are collected into a struct named after the `module` clause, or after the
+/// file stem if the module doesn't declare one.
+pub fn import_pkl_file(path: &Path) -> Result<TypeMap> {
+    let source = std::fs::read_to_string(path).map_err(|e| CliError::IoError {
+        context: format!("Reading Pkl schema: {}", path.display()),
+        source: e,
+    })?;
+    let fallback = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Module");
+    import_pkl_source(&path.display().to_string(), fallback, &source)
+}
+
+fn import_pkl_source(source_name: &str, fallback_module: &str, source: &str) -> Result<TypeMap> {
+    let tokens = Lexer::new(source)
+        .tokenize()
+        .map_err(|(message, offset)| parse_error(source_name, source, message, offset, 1))?;
+    let mut parser = Parser {
+        source_name,
+        source,
+        tokens,
+        pos: 0,
+    };
+    parser.parse_module(fallback_module)
+}
+
+fn parse_error(source_name: &str, source: &str, message: String, offset: usize, len: usize) -> CliError {
+    CliError::PklParseError {
+        message,
+        src: NamedSource::new(source_name, source.to_string()),
+        span: SourceSpan::from((offset.min(source.len()), len)),
+    }
+}
+
+// ================================ Lexer ================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    /// A backticked identifier -- never a keyword
+    QuotedIdent(String),
+    Str(String),
+    Num(String),
+    Doc(String),
+    Punct(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    start: usize,
+    end: usize,
+    line: usize,
+    end_line: usize,
+}
+
+const MULTI_CHAR_PUNCT: [&str; 12] = ["->", "==", "!=", ">=", "<=", "&&", "||", "??", "?.", "!!", "|>", "..."];
+const SINGLE_CHAR_PUNCT: [&str; 22] = [
+    "{", "}", "(", ")", "[", "]", "<", ">", "|", "?", "=", ",", ".", ":", "*", "!", "+", "-", "/", "%", ";", "@",
+];
+
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, pos: 0, line: 1 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn tokenize(mut self) -> std::result::Result<Vec<Token>, (String, usize)> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+            let start = self.pos;
+            let line = self.line;
+            let tok = if self.rest().starts_with("///") && !self.rest().starts_with("////") {
+                let text = self.take_line();
+                let text = text.trim_start_matches("///");
+                Tok::Doc(text.strip_prefix(' ').unwrap_or(text).trim_end().to_string())
+            } else if self.rest().starts_with("//") {
+                self.take_line();
+                continue;
+            } else if self.rest().starts_with("/*") {
+                match self.rest().find("*/") {
+                    Some(end) => {
+                        for _ in self.source[self.pos..self.pos + end + 2].chars() {
+                            self.bump();
+                        }
+                    }
+                    None => return Err(("unterminated block comment".to_string(), start)),
+                }
+                continue;
+            } else if c == '"' || (c == '#' && matches!(self.peek_nth(1), Some('#' | '"'))) {
+                Tok::Str(self.take_string()?)
+            } else if c == '`' {
+                self.bump();
+                let mut name = String::new();
+                loop {
+                    match self.bump() {
+                        Some('`') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(("unterminated backticked identifier".to_string(), start)),
+                    }
+                }
+                Tok::QuotedIdent(name)
+            } else if c.is_ascii_digit() {
+                Tok::Num(self.take_number())
+            } else if c.is_alphabetic() || c == '_' || c == '$' {
+                let mut name = String::new();
+                while let Some(c) = self.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '$' {
+                        name.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Tok::Ident(name)
+            } else if let Some(punct) = MULTI_CHAR_PUNCT
+                .iter()
+                .chain(SINGLE_CHAR_PUNCT.iter())
+                .copied()
+                .find(|punct| self.rest().starts_with(punct))
+            {
+                self.pos += punct.len();
+                Tok::Punct(punct)
+            } else {
+                return Err((format!("unexpected character `{}`", c), start));
+            };
+            tokens.push(Token {
+                tok,
+                start,
+                end: self.pos,
+                line,
+                end_line: self.line,
+            });
+        }
+        Ok(tokens)
+    }
+
+    fn take_line(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find('\n').unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn take_number(&mut self) -> String {
+        let mut number = String::new();
+        if self.rest().starts_with("0x") || self.rest().starts_with("0b") || self.rest().starts_with("0o") {
+            number.push_str(&self.rest()[..2]);
+            self.pos += 2;
+            while let Some(c) = self.peek().filter(|c| c.is_ascii_hexdigit() || *c == '_') {
+                number.push(c);
+                self.bump();
+            }
+            return number;
+        }
+        while let Some(c) = self.peek() {
+            let exponent_sign = (c == '-' || c == '+') && number.ends_with(['e', 'E']);
+            let fraction = c == '.' && self.peek_nth(1).is_some_and(|next| next.is_ascii_digit());
+            if c.is_ascii_digit() || c == '_' || c == 'e' || c == 'E' || exponent_sign || fraction {
+                number.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        number
+    }
+
+    /// Reads a string literal: `"..."`, `"""..."""`, or their `#"..."#` raw forms.
+    fn take_string(&mut self) -> std::result::Result<String, (String, usize)> {
+        let start = self.pos;
+        let mut hashes = 0;
+        while self.peek() == Some('#') {
+            hashes += 1;
+            self.bump();
+        }
+        let multiline = self.rest().starts_with("\"\"\"");
+        let quote = if multiline { "\"\"\"" } else { "\"" };
+        if !self.rest().starts_with('"') {
+            return Err(("expected a string after `#`".to_string(), start));
+        }
+        self.pos += quote.len();
+        let closing = format!("{}{}", quote, "#".repeat(hashes));
+        let escape = format!("\\{}", "#".repeat(hashes));
+
+        let mut value = String::new();
+        loop {
+            if self.rest().starts_with(closing.as_str()) {
+                self.pos += closing.len();
+                break;
+            }
+            if self.rest().starts_with(escape.as_str()) {
+                self.pos += escape.len();
+                match self.bump() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    // keep interpolations and unicode escapes as written
+                    Some(c @ ('(' | 'u')) => {
+                        value.push_str(&escape);
+                        value.push(c);
+                    }
+                    Some(c) => value.push(c),
+                    None => return Err(("unterminated string".to_string(), start)),
+                }
+                continue;
+            }
+            match self.bump() {
+                Some('\n') if !multiline => return Err(("unterminated string".to_string(), start)),
+                Some(c) => value.push(c),
+                None => return Err(("unterminated string".to_string(), start)),
+            }
+        }
+        if multiline {
+            value = trim_multiline(&value);
+        }
+        Ok(value)
+    }
+}
+
+/// Multi-line strings drop their first and last line breaks and the closing delimiter's indentation.
+pub(crate) fn trim_multiline(value: &str) -> String {
+    let value = value.strip_prefix('\n').unwrap_or(value);
+    let (body, indent) = match value.rfind('\n') {
+        Some(index) if value[index + 1..].trim().is_empty() => (&value[..index], &value[index + 1..]),
+        _ => (value, ""),
+    };
+    body.lines()
+        .map(|line| line.strip_prefix(indent).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ================================ Parser ================================
+
+const MODIFIERS: [&str; 7] = ["abstract", "open", "external", "local", "hidden", "fixed", "const"];
+
+/// Operators that carry an expression onto the next line.
+const CONTINUATIONS: [&str; 18] = [
+    ".", "?.", "|", "||", "&&", "+", "-", "*", "/", "%", "==", "!=", "<", ">", "<=", ">=", "??", "|>",
+];
+
+struct Parser<'a> {
+    source_name: &'a str,
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+/// What a declaration's doc comments, annotations and modifiers told us.
+#[derive(Default)]
+struct Preamble {
+    doc: Option<String>,
+    deprecated: Option<String>,
+    modifiers: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn parse_module(&mut self, fallback_module: &str) -> Result<TypeMap> {
+        let mut types = TypeMap::new();
+        let mut parents: Vec<(String, String)> = Vec::new();
+        let mut module_name: Option<String> = None;
+        let mut module_doc: Option<String> = None;
+        let mut module_fields: Vec<(String, SchemaField)> = Vec::new();
+
+        while self.peek().is_some() {
+            let preamble = self.parse_preamble()?;
+            if self.peek().is_none() {
+                break;
+            }
+            if self.eat_keyword("module") {
+                module_name = self.parse_qualified_name()?.rsplit('.').next().map(str::to_string);
+                module_doc = preamble.doc;
+            } else if self.eat_keyword("amends") || self.eat_keyword("extends") {
+                self.expect_string()?;
+            } else if self.eat_keyword("import") {
+                self.eat_punct("*");
+                self.expect_string()?;
+                if self.eat_keyword("as") {
+                    self.expect_ident()?;
+                }
+            } else if self.is_keyword("typealias") {
+                let (name, schema) = self.parse_typealias(preamble)?;
+                types.insert(name, schema);
+            } else if self.is_keyword("class") {
+                let (name, schema, parent) = self.parse_class(preamble)?;
+                if let Some(parent) = parent {
+                    parents.push((name.clone(), parent));
+                }
+                types.insert(name, schema);
+            } else if let Some(member) = self.parse_member(preamble)? {
+                module_fields.push(member);
+            }
+        }
+
+        resolve_inheritance(&mut types, &parents);
+
+        if !module_fields.is_empty() {
+            let name = module_name.unwrap_or_else(|| fallback_module.to_string());
+            let mut schema = Schema::structure(StructType::new(module_fields));
+            schema.name = Some(name.clone());
+            schema.description = module_doc;
+            types.insert(name, schema);
+        }
+        Ok(types)
+    }
+
+    // ---------------------------- declarations ----------------------------
+
+    /// Collects doc comments, annotations and modifiers ahead of a declaration.
+    fn parse_preamble(&mut self) -> Result<Preamble> {
+        let mut preamble = Preamble::default();
+        let mut doc_lines: Vec<String> = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Tok::Doc(line)) => {
+                    doc_lines.push(line.clone());
+                    self.pos += 1;
+                }
+                Some(Tok::Punct("@")) => {
+                    self.pos += 1;
+                    let annotation = self.parse_qualified_name()?;
+                    let message = if self.is_punct("{") {
+                        self.annotation_message()?
+                    } else {
+                        None
+                    };
+                    if annotation.rsplit('.').next() == Some("Deprecated") {
+                        preamble.deprecated = Some(message.unwrap_or_default());
+                    }
+                }
+                // `hidden: Boolean` is a property, not a modifier
+                Some(Tok::Ident(word))
+                    if MODIFIERS.contains(&word.as_str())
+                        && !matches!(self.tokens.get(self.pos + 1).map(|token| &token.tok), Some(Tok::Punct(":" | "=" | "{"))) =>
+                {
+                    preamble.modifiers.push(word.clone());
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        let doc = doc_lines.join("\n");
+        if !doc.trim().is_empty() {
+            preamble.doc = Some(doc.trim().to_string());
+        }
+        Ok(preamble)
+    }
+
+    /// Reads `{ message = "..." }` from an annotation body, skipping anything else.
+    fn annotation_message(&mut self) -> Result<Option<String>> {
+        let start = self.pos;
+        self.skip_balanced()?;
+        let body = &self.tokens[start..self.pos];
+        Ok(body.windows(3).find_map(|window| match (&window[0].tok, &window[1].tok, &window[2].tok) {
+            (Tok::Ident(key), Tok::Punct("="), Tok::Str(message)) if key == "message" => Some(message.clone()),
+            _ => None,
+        }))
+    }
+
+    fn parse_typealias(&mut self, preamble: Preamble) -> Result<(String, Schema)> {
+        self.expect_keyword("typealias")?;
+        let name = self.expect_ident()?;
+        self.skip_type_parameters()?;
+        self.expect_punct("=")?;
+        let mut schema = self.parse_type()?;
+        schema.name = Some(name.clone());
+        schema.description = preamble.doc;
+        schema.deprecated = preamble.deprecated;
+        Ok((name, schema))
+    }
+
+    fn parse_class(&mut self, preamble: Preamble) -> Result<(String, Schema, Option<String>)> {
+        let class_token = self.expect_keyword("class")?;
+        let name = self.expect_ident()?;
+        self.skip_type_parameters()?;
+        let parent = if self.eat_keyword("extends") {
+            let parent = self.parse_qualified_name()?;
+            self.skip_type_arguments()?;
+            parent.rsplit('.').next().map(str::to_string)
+        } else {
+            None
+        };
+
+        let mut fields = Vec::new();
+        if self.eat_punct("{") {
+            loop {
+                let member_preamble = self.parse_preamble()?;
+                if self.eat_punct("}") {
+                    break;
+                }
+                if self.peek().is_none() {
+                    return Err(self.error_at_token(format!("class `{}` is missing its closing `}}`", name), &class_token));
+                }
+                if let Some(member) = self.parse_member(member_preamble)? {
+                    fields.push(member);
+                }
+            }
+        }
+
+        let mut schema = Schema::structure(StructType::new(fields));
+        schema.name = Some(name.clone());
+        schema.description = preamble.doc;
+        schema.deprecated = preamble.deprecated;
+        Ok((name, schema, parent))
+    }
+
+    /// Parses a property declaration. Functions and untyped overrides (`foo = ...`, `foo { ... }`)
+    /// are skipped and return `None`.
+    fn parse_member(&mut self, preamble: Preamble) -> Result<Option<(String, SchemaField)>> {
+        if self.eat_keyword("function") {
+            self.skip_function()?;
+            return Ok(None);
+        }
+        let name = self.expect_ident()?;
+
+        if self.eat_punct("=") {
+            self.skip_expression();
+            return Ok(None);
+        }
+        if self.is_punct("{") {
+            self.skip_balanced()?;
+            return Ok(None);
+        }
+        self.expect_punct(":")?;
+
+        let mut schema = self.parse_type()?;
+        let mut has_default = false;
+        if self.eat_punct("=") {
+            has_default = true;
+            if let Some(value) = self.parse_default() {
+                apply_default(&mut schema, value);
+            }
+        }
+
+        let nullable = schema.nullable || matches!(&schema.ty, SchemaType::Union(union) if union.has_null());
+        let field = SchemaField {
+            comment: preamble.doc,
+            deprecated: preamble.deprecated,
+            hidden: preamble.modifiers.iter().any(|modifier| modifier == "hidden"),
+            read_only: preamble.modifiers.iter().any(|modifier| modifier == "fixed" || modifier == "const"),
+            nullable,
+            optional: nullable || has_default,
+            schema,
+            ..Default::default()
+        };
+        Ok(Some((name, field)))
+    }
+
+    // ---------------------------- types ----------------------------
+
+    /// `type := '*'? postfix ('|' '*'? postfix)*`
+    fn parse_type(&mut self) -> Result<Schema> {
+        let mut variants = Vec::new();
+        let mut default_index = None;
+        loop {
+            if self.eat_punct("*") && default_index.is_none() {
+                default_index = Some(variants.len());
+            }
+            variants.push(self.parse_postfix_type()?);
+            if !self.eat_punct("|") {
+                break;
+            }
+        }
+        if variants.len() == 1 {
+            return Ok(variants.remove(0));
+        }
+
+        let literals: Vec<LiteralValue> = variants
+            .iter()
+            .filter_map(|variant| match &variant.ty {
+                SchemaType::Literal(literal) => Some(literal.value.clone()),
+                _ => None,
+            })
+            .collect();
+        if literals.len() == variants.len() {
+            let mut enum_type = EnumType::new(literals);
+            enum_type.default_index = default_index;
+            return Ok(Schema::enumerable(enum_type));
+        }
+
+        let mut union = UnionType::new_any(variants);
+        union.default_index = default_index;
+        let nullable = union.has_null();
+        let mut schema = Schema::union(union);
+        schema.nullable = nullable;
+        Ok(schema)
+    }
+
+    /// `postfix := primary ('?' | '(' constraints ')')*`
+    fn parse_postfix_type(&mut self) -> Result<Schema> {
+        let mut schema = self.parse_primary_type()?;
+        loop {
+            if self.eat_punct("?") {
+                schema.nullify();
+            } else if self.is_punct("(") && self.is_adjacent() {
+                self.parse_constraints(&mut schema)?;
+            } else {
+                return Ok(schema);
+            }
+        }
+    }
+
+    fn parse_primary_type(&mut self) -> Result<Schema> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Err(self.error_at_end("expected a type"));
+        };
+        match token.tok {
+            Tok::Str(value) => {
+                self.pos += 1;
+                Ok(Schema::literal(LiteralType::new(LiteralValue::String(value))))
+            }
+            Tok::Punct("(") => {
+                self.pos += 1;
+                let mut inner = Vec::new();
+                if !self.is_punct(")") {
+                    loop {
+                        inner.push(self.parse_type()?);
+                        if !self.eat_punct(",") {
+                            break;
+                        }
+                    }
+                }
+                self.expect_punct(")")?;
+                // function types have no schematic equivalent
+                if self.eat_punct("->") {
+                    self.parse_type()?;
+                    return Ok(Schema::unknown());
+                }
+                if inner.len() == 1 {
+                    Ok(inner.remove(0))
+                } else {
+                    Ok(Schema::unknown())
+                }
+            }
+            Tok::Ident(_) | Tok::QuotedIdent(_) => {
+                let name = self.parse_qualified_name()?;
+                let mut arguments = Vec::new();
+                if self.eat_punct("<") {
+                    loop {
+                        arguments.push(self.parse_type()?);
+                        if !self.eat_punct(",") {
+                            break;
+                        }
+                    }
+                    self.expect_punct(">")?;
+                }
+                Ok(builtin_type(&name, arguments))
+            }
+            _ => Err(self.error_at_token("expected a type", &token)),
+        }
+    }
+
+    /// Reads `(constraint, constraint && constraint)` and applies the ones that map onto schematic fields.
+    fn parse_constraints(&mut self, schema: &mut Schema) -> Result<()> {
+        let open = self.expect_punct("(")?;
+        let mut depth = 0usize;
+        let mut current: Vec<Tok> = Vec::new();
+        loop {
+            let Some(token) = self.tokens.get(self.pos).cloned() else {
+                return Err(self.error_at_token("constraint list is missing its closing `)`", &open));
+            };
+            self.pos += 1;
+            match token.tok {
+                Tok::Punct(")") if depth == 0 => {
+                    apply_constraint(schema, &current);
+                    return Ok(());
+                }
+                Tok::Punct("," | "&&") if depth == 0 => {
+                    apply_constraint(schema, &current);
+                    current.clear();
+                }
+                Tok::Punct(open @ ("(" | "[" | "{")) => {
+                    depth += 1;
+                    current.push(Tok::Punct(open));
+                }
+                Tok::Punct(close @ (")" | "]" | "}")) => {
+                    depth = depth.saturating_sub(1);
+                    current.push(Tok::Punct(close));
+                }
+                tok => current.push(tok),
+            }
+        }
+    }
+
+    // ---------------------------- defaults and skipping ----------------------------
+
+    /// Consumes a default value expression, returning it if it's a single literal.
+    fn parse_default(&mut self) -> Option<LiteralValue> {
+        let start = self.pos;
+        let literal = match (self.peek(), self.tokens.get(start + 1).map(|token| &token.tok)) {
+            (Some(Tok::Str(value)), _) => Some((LiteralValue::String(value.clone()), 1)),
+            (Some(Tok::Num(number)), _) => parse_number(number, false).map(|value| (value, 1)),
+            (Some(Tok::Punct("-")), Some(Tok::Num(number))) => parse_number(number, true).map(|value| (value, 2)),
+            (Some(Tok::Ident(word)), _) if word == "true" || word == "false" => {
+                Some((LiteralValue::Bool(word == "true"), 1))
+            }
+            _ => None,
+        };
+        self.skip_expression();
+        // `"a" + "b"` isn't a literal
+        literal
+            .filter(|(_, len)| self.pos - start == *len)
+            .map(|(value, _)| value)
+    }
+
+    /// Skips an expression. An expression ends at a closing bracket it didn't open, a doc comment, or a
+    /// new line that doesn't continue it.
+    fn skip_expression(&mut self) {
+        let mut depth = 0usize;
+        let mut previous: Option<Token> = None;
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            if let Some(previous) = previous.as_ref().filter(|_| depth == 0) {
+                let closes = matches!(token.tok, Tok::Punct(")" | "]" | "}" | ";"));
+                let new_line = token.line > previous.end_line
+                    && !continues_expression(&previous.tok, true)
+                    && !continues_expression(&token.tok, false);
+                if closes || new_line || matches!(token.tok, Tok::Doc(_)) {
+                    return;
+                }
+            }
+            match token.tok {
+                Tok::Punct("(" | "[" | "{") => depth += 1,
+                Tok::Punct(")" | "]" | "}") => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.pos += 1;
+            previous = Some(token);
+        }
+    }
+
+    /// Skips a bracketed group, starting at its opening bracket.
+    fn skip_balanced(&mut self) -> Result<()> {
+        let Some(open) = self.tokens.get(self.pos).cloned() else {
+            return Err(self.error_at_end("expected a bracket"));
+        };
+        let mut depth = 0usize;
+        while let Some(token) = self.tokens.get(self.pos) {
+            match token.tok {
+                Tok::Punct("(" | "[" | "{") => depth += 1,
+                Tok::Punct(")" | "]" | "}") => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.pos += 1;
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+        Err(self.error_at_token("missing closing bracket", &open))
+    }
+
+    fn skip_function(&mut self) -> Result<()> {
+        self.expect_ident()?;
+        self.skip_type_parameters()?;
+        if self.is_punct("(") {
+            self.skip_balanced()?;
+        }
+        if self.eat_punct(":") {
+            self.parse_type()?;
+        }
+        if self.eat_punct("=") {
+            self.skip_expression();
+        }
+        Ok(())
+    }
+
+    /// Skips `<T, U>` after a class, typealias or function name.
+    fn skip_type_parameters(&mut self) -> Result<()> {
+        if self.eat_punct("<") {
+            while !self.eat_punct(">") {
+                if self.peek().is_none() {
+                    return Err(self.error_at_end("type parameter list is missing its closing `>`"));
+                }
+                self.pos += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips `<Type, Type>` after a parent class.
+    fn skip_type_arguments(&mut self) -> Result<()> {
+        if self.eat_punct("<") {
+            loop {
+                self.parse_type()?;
+                if !self.eat_punct(",") {
+                    break;
+                }
+            }
+            self.expect_punct(">")?;
+        }
+        Ok(())
+    }
+
+    // ---------------------------- token helpers ----------------------------
+
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|token| &token.tok)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Punct(p)) if *p == punct)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, punct: &'static str) -> Result<Token> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(token) if token.tok == Tok::Punct(punct) => {
+                self.pos += 1;
+                Ok(token)
+            }
+            Some(token) => Err(self.error_at_token(format!("expected `{}`", punct), &token)),
+            None => Err(self.error_at_end(format!("expected `{}`", punct))),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Ident(word)) if word == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<Token> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(token) if matches!(&token.tok, Tok::Ident(word) if word == keyword) => {
+                self.pos += 1;
+                Ok(token)
+            }
+            Some(token) => Err(self.error_at_token(format!("expected `{}`", keyword), &token)),
+            None => Err(self.error_at_end(format!("expected `{}`", keyword))),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token { tok: Tok::Ident(name) | Tok::QuotedIdent(name), .. }) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            Some(token) => Err(self.error_at_token("expected a name", &token)),
+            None => Err(self.error_at_end("expected a name")),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token { tok: Tok::Str(value), .. }) => {
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(token) => Err(self.error_at_token("expected a string", &token)),
+            None => Err(self.error_at_end("expected a string")),
+        }
+    }
+
+    /// `name ('.' name)*`
+    fn parse_qualified_name(&mut self) -> Result<String> {
+        let mut name = self.expect_ident()?;
+        while self.is_punct(".")
+            && matches!(self.tokens.get(self.pos + 1).map(|token| &token.tok), Some(Tok::Ident(_) | Tok::QuotedIdent(_)))
+        {
+            self.pos += 1;
+            name.push('.');
+            name.push_str(&self.expect_ident()?);
+        }
+        Ok(name)
+    }
+
+    /// Whether the current token directly follows the previous one (`String(...)` vs. `String (...)`
+    /// on the next line).
+    fn is_adjacent(&self) -> bool {
+        match (self.pos.checked_sub(1).and_then(|i| self.tokens.get(i)), self.tokens.get(self.pos)) {
+            (Some(previous), Some(current)) => previous.end == current.start,
+            _ => false,
+        }
+    }
+
+    fn error_at_token(&self, message: impl Into<String>, token: &Token) -> CliError {
+        parse_error(self.source_name, self.source, message.into(), token.start, token.end - token.start)
+    }
+
+    fn error_at_end(&self, message: impl Into<String>) -> CliError {
+        parse_error(self.source_name, self.source, message.into(), self.source.len(), 0)
+    }
+}
+
+fn continues_expression(tok: &Tok, trailing: bool) -> bool {
+    match tok {
+        Tok::Punct(punct) => CONTINUATIONS.contains(punct) || (trailing && matches!(*punct, "=" | "(" | "[" | "{" | "->" | "!")),
+        Tok::Ident(word) => {
+            matches!(word.as_str(), "is" | "as")
+                || (trailing && matches!(word.as_str(), "new" | "let" | "if" | "else" | "throw" | "trace" | "import" | "read"))
+        }
+        _ => false,
+    }
+}
+
+// ================================ Schema building ================================
+
+/// Maps a Pkl type name onto schematic's types. Anything we don't know is a reference to a class
+/// or typealias -- from this module or an imported one.
+fn builtin_type(name: &str, mut arguments: Vec<Schema>) -> Schema {
+    let name = name.strip_prefix("base.").unwrap_or(name);
+    let mut argument = |index: usize| {
+        arguments
+            .get_mut(index)
+            .map(std::mem::take)
+            .unwrap_or_else(Schema::unknown)
+    };
+    let bounded = |kind: IntegerKind, min: isize, max: isize| {
+        let mut integer = IntegerType::new_kind(kind);
+        integer.min = Some(min);
+        integer.max = Some(max);
+        Schema::integer(integer)
+    };
+
+    match name {
+        "String" => Schema::string(StringType::default()),
+        "Char" => Schema::string(StringType {
+            min_length: Some(1),
+            max_length: Some(1),
+            ..Default::default()
+        }),
+        "Duration" | "DataSize" | "Regex" => Schema::string(StringType {
+            format: Some(match name {
+                "Duration" => "duration",
+                "DataSize" => "data-size",
+                _ => "regex",
+            }
+            .to_string()),
+            ..Default::default()
+        }),
+        "Boolean" => Schema::boolean(BooleanType::default()),
+        "Int" => Schema::integer(IntegerType::new_kind(IntegerKind::I64)),
+        "Int8" => bounded(IntegerKind::I8, i8::MIN as isize, i8::MAX as isize),
+        "Int16" => bounded(IntegerKind::I16, i16::MIN as isize, i16::MAX as isize),
+        "Int32" => bounded(IntegerKind::I32, i32::MIN as isize, i32::MAX as isize),
+        "UInt8" => bounded(IntegerKind::U8, 0, u8::MAX as isize),
+        "UInt16" => bounded(IntegerKind::U16, 0, u16::MAX as isize),
+        "UInt32" => bounded(IntegerKind::U32, 0, u32::MAX as isize),
+        "UInt" => {
+            let mut integer = IntegerType::new_kind(IntegerKind::U64);
+            integer.min = Some(0);
+            Schema::integer(integer)
+        }
+        "Float" | "Number" => Schema::float(FloatType::new_kind(FloatKind::F64)),
+        "Null" => Schema::null(),
+        "Any" | "Dynamic" | "Object" | "Typed" | "unknown" | "nothing" => Schema::unknown(),
+        "Listing" | "List" | "Collection" => Schema::array(ArrayType::new(argument(0))),
+        "Set" => {
+            let mut array = ArrayType::new(argument(0));
+            array.unique = Some(true);
+            Schema::array(array)
+        }
+        "Mapping" | "Map" => {
+            let key = argument(0);
+            Schema::object(ObjectType::new(key, argument(1)))
+        }
+        "Pair" => {
+            let first = argument(0);
+            Schema::tuple(TupleType::new([first, argument(1)]))
+        }
+        _ => {
+            let short_name = name.rsplit('.').next().unwrap_or(name).to_string();
+            Schema {
+                name: Some(short_name.clone()),
+                ty: SchemaType::Reference(short_name),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+fn parse_number(number: &str, negative: bool) -> Option<LiteralValue> {
+    let cleaned = number.replace('_', "");
+    let sign = if negative { -1 } else { 1 };
+    let radix = match cleaned.get(..2) {
+        Some("0x") => Some(16),
+        Some("0b") => Some(2),
+        Some("0o") => Some(8),
+        _ => None,
+    };
+    if let Some(radix) = radix {
+        return isize::from_str_radix(&cleaned[2..], radix)
+            .ok()
+            .map(|value| LiteralValue::Int(sign * value));
+    }
+    if let Ok(value) = cleaned.parse::<isize>() {
+        return Some(LiteralValue::Int(sign * value));
+    }
+    cleaned
+        .parse::<f64>()
+        .ok()
+        .map(|value| LiteralValue::F64(sign as f64 * value))
+}
+
+/// Sets a literal default on a schema. Nullable types carry it on their non-null variant, and
+/// string enums record which variant it is.
+pub(crate) fn apply_default(schema: &mut Schema, value: LiteralValue) {
+    match &mut schema.ty {
+        SchemaType::Enum(enum_type) => {
+            enum_type.default_index = enum_type.values.iter().position(|known| *known == value);
+        }
+        SchemaType::Union(union) => {
+            if let Some(index) = union
+                .variants_types
+                .iter()
+                .position(|variant| accepts_literal(variant, &value))
+            {
+                union.default_index = Some(index);
+                apply_default(&mut union.variants_types[index], value);
+            }
+        }
+        SchemaType::Float(float) => {
+            float.default = Some(match value {
+                LiteralValue::Int(int) => LiteralValue::F64(int as f64),
+                other => other,
+            });
+        }
+        ty => ty.set_default(value),
+    }
+}
+
+fn accepts_literal(schema: &Schema, value: &LiteralValue) -> bool {
+    match (&schema.ty, value) {
+        (SchemaType::String(_), LiteralValue::String(_)) | (SchemaType::Boolean(_), LiteralValue::Bool(_)) => true,
+        (SchemaType::Integer(_), LiteralValue::Int(_) | LiteralValue::UInt(_)) => true,
+        (SchemaType::Float(_), LiteralValue::Int(_) | LiteralValue::F32(_) | LiteralValue::F64(_)) => true,
+        (SchemaType::Literal(literal), value) => literal.value == *value,
+        (SchemaType::Enum(enum_type), value) => enum_type.values.contains(value),
+        _ => false,
+    }
+}
+
+enum Bound {
+    Min,
+    Max,
+    MinExclusive,
+    MaxExclusive,
+}
+
+/// Applies one constraint (already split on `,` and `&&`) to a schema. Unknown constraints are ignored.
+fn apply_constraint(schema: &mut Schema, constraint: &[Tok]) {
+    let constraint = match constraint {
+        [Tok::Ident(this), Tok::Punct("."), rest @ ..] if this == "this" => rest,
+        [Tok::Ident(this), rest @ ..] if this == "this" => rest,
+        _ => constraint,
+    };
+
+    match constraint {
+        [Tok::Ident(method), arguments @ ..] if method == "isBetween" => {
+            if let [min, max] = numbers_in(arguments)[..] {
+                set_bound(schema, Bound::Min, min);
+                set_bound(schema, Bound::Max, max);
+            }
+        }
+        [Tok::Ident(length), Tok::Punct("."), Tok::Ident(method), arguments @ ..]
+            if length == "length" && method == "isBetween" =>
+        {
+            if let [min, max] = numbers_in(arguments)[..] {
+                set_length(schema, Some(min as usize), Some(max as usize));
+            }
+        }
+        [Tok::Ident(length), Tok::Punct(op), value @ ..] if length == "length" => {
+            if let [value] = numbers_in(value)[..] {
+                let value = value as usize;
+                match *op {
+                    ">=" => set_length(schema, Some(value), None),
+                    ">" => set_length(schema, Some(value + 1), None),
+                    "<=" => set_length(schema, None, Some(value)),
+                    "<" => set_length(schema, None, Some(value.saturating_sub(1))),
+                    "==" => set_length(schema, Some(value), Some(value)),
+                    _ => {}
+                }
+            }
+        }
+        [Tok::Punct(op), value @ ..] if matches!(*op, ">=" | ">" | "<=" | "<") => {
+            if let [value] = numbers_in(value)[..] {
+                let bound = match *op {
+                    ">=" => Bound::Min,
+                    ">" => Bound::MinExclusive,
+                    "<=" => Bound::Max,
+                    _ => Bound::MaxExclusive,
+                };
+                set_bound(schema, bound, value);
+            }
+        }
+        [
+            Tok::Ident(method),
+            Tok::Punct("("),
+            Tok::Ident(regex),
+            Tok::Punct("("),
+            Tok::Str(pattern),
+            Tok::Punct(")"),
+            Tok::Punct(")"),
+        ] if method == "matches" && regex == "Regex" => {
+            if let SchemaType::String(string) = &mut schema.ty {
+                string.pattern = Some(pattern.clone());
+            }
+        }
+        [Tok::Punct("!"), Tok::Ident(method)] if method == "isEmpty" || method == "isBlank" => {
+            set_length(schema, Some(1), None);
+        }
+        [Tok::Ident(method)] if method == "isPositive" => set_bound(schema, Bound::Min, 0.0),
+        [Tok::Ident(method)] if method == "isDistinct" => {
+            if let SchemaType::Array(array) = &mut schema.ty {
+                array.unique = Some(true);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the numbers in a constraint's arguments, e.g. `(-1, 10)` -> `[-1.0, 10.0]`.
+fn numbers_in(tokens: &[Tok]) -> Vec<f64> {
+    let mut numbers = Vec::new();
+    let mut negative = false;
+    for tok in tokens {
+        match tok {
+            Tok::Punct("-") => negative = true,
+            Tok::Num(number) => {
+                match parse_number(number, negative) {
+                    Some(LiteralValue::Int(value)) => numbers.push(value as f64),
+                    Some(LiteralValue::F64(value)) => numbers.push(value),
+                    _ => {}
+                }
+                negative = false;
+            }
+            _ => negative = false,
+        }
+    }
+    numbers
+}
+
+fn set_bound(schema: &mut Schema, bound: Bound, value: f64) {
+    match &mut schema.ty {
+        SchemaType::Integer(integer) => {
+            let value = Some(value as isize);
+            match bound {
+                Bound::Min => integer.min = value,
+                Bound::Max => integer.max = value,
+                Bound::MinExclusive => integer.min_exclusive = value,
+                Bound::MaxExclusive => integer.max_exclusive = value,
+            }
+        }
+        SchemaType::Float(float) => {
+            let value = Some(value);
+            match bound {
+                Bound::Min => float.min = value,
+                Bound::Max => float.max = value,
+                Bound::MinExclusive => float.min_exclusive = value,
+                Bound::MaxExclusive => float.max_exclusive = value,
+            }
+        }
+        _ => {}
+    }
+}
+
+fn set_length(schema: &mut Schema, min: Option<usize>, max: Option<usize>) {
+    let (min_length, max_length) = match &mut schema.ty {
+        SchemaType::String(string) => (&mut string.min_length, &mut string.max_length),
+        SchemaType::Array(array) => (&mut array.min_length, &mut array.max_length),
+        SchemaType::Object(object) => (&mut object.min_length, &mut object.max_length),
+        _ => return,
+    };
+    if min.is_some() {
+        *min_length = min;
+    }
+    if max.is_some() {
+        *max_length = max;
+    }
+}
+
+/// Copies inherited fields into classes that extend another class in the same module.
+/// Fields the subclass declares itself win.
+fn resolve_inheritance(types: &mut TypeMap, parents: &[(String, String)]) {
+    for (child, _) in parents {
+        let mut inherited = Vec::new();
+        let mut current = child.clone();
+        // the chain can't be longer than the number of classes, which also guards against cycles
+        for _ in 0..parents.len() {
+            let Some((_, parent)) = parents.iter().find(|(name, _)| *name == current) else {
+                break;
+            };
+            if let Some(SchemaType::Struct(parent_struct)) = types.get(parent).map(|schema| &schema.ty) {
+                inherited.extend(parent_struct.fields.clone());
+            }
+            current = parent.clone();
+        }
+        if let Some(SchemaType::Struct(child_struct)) = types.get_mut(child).map(|schema| &mut schema.ty) {
+            for (name, field) in inherited {
+                child_struct.fields.entry(name).or_insert(field);
+            }
+        }
+    }
+}
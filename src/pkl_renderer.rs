@@ -1,4 +1,4 @@
-/**========================================================================
+/*=========================================================================
  * *                              About
  *
  *   (c) 2025 Stash AI Inc. (aka Knitli)
@@ -35,14 +35,14 @@
 //!   - Complete implementation of schematic's available type constraints. Pkl's type system allows arbitrary constrained types. This is a valid type in Pkl:
 //!     ```pkl
 //!
-//!    /// self-validating email type -- valid pkl
-//!    typealias Email = String(
-//!       matches(
-//!         Regex(
-//!           #"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\\.[a-zA-Z]{2,}$"#
+//!     /// self-validating email type -- valid pkl
+//!     typealias Email = String(
+//!        matches(
+//!          Regex(
+//!            #"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\\.[a-zA-Z]{2,}$"#
+//!          )
 //!         )
 //!        )
-//!       )
 //!
 //!     // and so is:
 //!
@@ -78,12 +78,12 @@
 //!   - Renders the top-level `Config` struct as a module by default, but can be switched to a class. This allows you to directly use the generated module as a type using `amends`.
 //!   - Customizable options for module/class naming, indentation, and more.
 
-/**========================================================================
+/*=========================================================================
  **                       ## A Crash Course in schematic
  **========================================================================
  **       (You can skip this if you're not going to work on the Renderer)
  *========================================================================**/
-//
+//!
 //! I'm going to explain this simply because the type structure was hard to understand.
 //! This is my `schematic 101`. The [docs](https://moonrepo.github.io/schematic/) are good, they just didn't click for me.
 //!
@@ -157,14 +157,18 @@
 //! - **`Reference`**: The `String` is the name; look up that named type in the `TypeMap`.
 //!
 
-use std::collections::HashSet;
-use indexmap::IndexMap;
-use schematic::format::Format;
-use schematic::schema::{RenderResult, SchemaRenderer, RenderError};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use indexmap::{IndexMap, IndexSet};
+use regex::Regex;
+use serde_json::Value;
+use schematic::schema::{RenderResult, SchemaRenderer};
 use schematic_types::*;
 
 use crate::constants::{DATA_SIZE_UNITS, DURATION_UNITS};
-use crate::types::{TypeMap, EnumTranslation, OpenStructs, ConfigTranslation, OptionalFormat, PropertyDefault, LoadedConfig};
+use crate::types::moon::UnknownConfig;
+use crate::types::{CliError, TypeMap, EnumTranslation, OpenStructs, ConfigTranslation, OptionalFormat, PropertyDefault, TupleTranslation, LoadedConfig, ModuleLayout};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RenderType {
@@ -174,16 +178,18 @@ pub enum RenderType {
 }
 
 impl std::str::FromStr for RenderType {
-  fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-    match s.to_lowercase().as_str() {
-      "template" | "tmpl" | "t" => Ok(RenderType::Template),
-      "schema" | "sch" | "s" => Ok(RenderType::Schema),
-      _ => Err(RenderError::UnsupportedFormat {
-        format: s.to_string(),
-        available: vec!["template", "schema"],
-      }),
-    }
-  }
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "template" | "tmpl" | "t" => Ok(RenderType::Template),
+            "schema" | "sch" | "s" => Ok(RenderType::Schema),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["template", "schema"],
+            }),
+        }
+    }
 }
 
 /// Renders idiomatic Pkl schema definitions with type annotations and constraints.
@@ -193,14 +199,30 @@ pub struct PklSchemaRenderer {
     depth: usize,
     /// Track typealiases to avoid duplicates
     typealiases: IndexMap<String, String>,
+    /// Doc comments and annotations for named typealiases
+    typealias_docs: IndexMap<String, String>,
     /// Track `Reference`s to prevent the universe from imploding
     references: HashSet<String>,
+    /// Classes generated for inline tuples, by name, holding their rendered properties
+    tuple_classes: IndexMap<String, String>,
+    /// The type whose properties are being rendered, to look up its [`PklSchemaOptions::default_values`]
+    current_type: Option<String>,
+    /// [`PklSchemaOptions::default_values`], plus the defaults of the structs nested in them
+    default_values: IndexMap<String, Value>,
+    /// The module each type lands in when splitting; empty for a single module
+    module_assignments: IndexMap<String, String>,
+    /// The module being rendered when splitting, to qualify references to its siblings
+    current_module: Option<String>,
+    /// The roots of a package and the modules they become, by type name; empty outside [`PklSchemaRenderer::render_package`]
+    package_roots: IndexMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct PklSchemaOptions {
     /// The name of the config to use for the root schema, LoadedConfig (moon config type or one you give); no default
     ///
+    /// For anything that isn't a moon config, pass a [`LoadedConfig::Unknown`]. With a `name`, that type is the root;
+    /// without one, we find the root with [`LoadedConfig::attempt_to_resolve_name`].
     pub config_name: LoadedConfig,
     /// Include documentation comments from schema descriptions
     pub include_docs: bool,
@@ -213,13 +235,13 @@ pub struct PklSchemaOptions {
     pub render_type: RenderType,
 
     /// Disable references and render all types inline recursively.
+    ///
+    /// Structs are still referenced by class name, since Pkl has no anonymous classes.
     pub disable_references: bool,
 
     /// Indentation string (default: 2 spaces)
     pub indent: String,
 
-    pub 
-
     /// Include default values in the schema
     pub include_defaults: bool,
 
@@ -230,10 +252,10 @@ pub struct PklSchemaOptions {
     pub comment_out_optional: bool,
 
     /// A list of properties to exclude from created schema
-    pub exclude_properties: Vec<&str>,
+    pub exclude_properties: Vec<String>,
 
     /// A list of imports to add to the generated module. These must be valid `pkl` import paths
-    pub added_imports: Vec<&str>,
+    pub added_imports: Vec<String>,
 
     /// How to translate enum types (typealias/literal_union; default: typealias)
     pub enum_translation: EnumTranslation,
@@ -252,15 +274,37 @@ pub struct PklSchemaOptions {
 
     /// Whether to default to requiring properties or marking them optional when the schema lacks information on optionality.
     pub property_default: PropertyDefault,
+
+    /// How to translate tuples with more than two items (listing/class; default: listing)
+    pub tuple_translation: TupleTranslation,
+
+    /// Default instances of types, by type name, as serialized values (e.g. `serde_json::to_value(ProjectConfig::default())`).
+    ///
+    /// Schematic only records defaults for scalars, so the contents of `Listing` and `Mapping` defaults come from here.
+    /// Structs nested in a default instance get their defaults from it, unless they have their own entry.
+    pub default_values: IndexMap<String, Value>,
+
+    /// Render everything into one module, or split types into a package of modules (single/split; default: single).
+    ///
+    /// Use [`PklSchemaRenderer::render_modules`] to get every module when splitting.
+    pub module_layout: ModuleLayout,
+
+    /// Explicit module assignments when splitting, keyed by module name (e.g. `"Tasks"`) with the names of the types that belong to it.
+    ///
+    /// Unassigned types go to the one module that uses them, or to the [common module](`PklSchemaOptions::common_module`) if several do.
+    pub module_groups: IndexMap<String, Vec<String>>,
+
+    /// Name of the module that holds types shared by more than one module (default: `Common`)
+    pub common_module: String,
 }
 
 impl Default for PklSchemaOptions {
     fn default() -> Self {
         Self {
-          config_name: LoadedConfig::default(),
+          config_name: LoadedConfig::Unknown(UnknownConfig::default()),
           include_docs: true,
           include_constraints: true,
-          render_type: RenderType,
+          render_type: RenderType::Schema,
           disable_references: false,
           indent: "  ".to_string(),
           include_defaults: true,
@@ -268,16 +312,37 @@ impl Default for PklSchemaOptions {
           comment_out_optional: false,
           exclude_properties: Vec::new(),
           added_imports: Vec::new(),
-          enum_translation: EnumTranslation::TypeAlias,
+          enum_translation: EnumTranslation::Typealias,
           open_structs: OpenStructs::Open,
           open_module: OpenStructs::Open,
           config_translation: ConfigTranslation::Module,
           optional_format: OptionalFormat::Optional,
-          property_default: PropertyDefault::RequireProperties,
+          property_default: PropertyDefault::Required,
+          tuple_translation: TupleTranslation::Listing,
+          default_values: IndexMap::new(),
+          module_layout: ModuleLayout::Single,
+          module_groups: IndexMap::new(),
+          common_module: "Common".to_string(),
         }
     }
 }
 
+impl PklSchemaOptions {
+    /// Adds `T::default()` as the default instance of `type_name` (see [`PklSchemaOptions::default_values`]).
+    pub fn with_default_values<T: Default + serde::Serialize>(mut self, type_name: &str) -> Self {
+        if let Ok(value) = serde_json::to_value(T::default()) {
+            self.default_values.insert(type_name.to_string(), value);
+        }
+        self
+    }
+}
+
+impl Default for PklSchemaRenderer {
+    fn default() -> Self {
+        Self::new(PklSchemaOptions::default())
+    }
+}
+
 impl PklSchemaRenderer {
     pub fn new(options: PklSchemaOptions) -> Self {
         Self {
@@ -285,14 +350,17 @@ impl PklSchemaRenderer {
             options,
             depth: 0,
             typealiases: IndexMap::default(),
+            typealias_docs: IndexMap::default(),
             references: HashSet::new(),
+            tuple_classes: IndexMap::default(),
+            current_type: None,
+            default_values: IndexMap::default(),
+            module_assignments: IndexMap::default(),
+            current_module: None,
+            package_roots: IndexMap::default(),
         }
     }
 
-    pub fn default() -> Self {
-        Self::new(PklSchemaOptions::default())
-    }
-
     fn indent(&self) -> String {
         self.options.indent.repeat(self.depth)
     }
@@ -307,7 +375,7 @@ impl PklSchemaRenderer {
         let mut capitalize_next = true;
 
         for ch in name.chars() {
-            if ch == '_' || ch == '-' {
+            if ch == '_' || ch == '-' || ch == ' ' || ch == '.' {
                 capitalize_next = true;
             } else if capitalize_next {
                 result.push(ch.to_uppercase().next().unwrap_or(ch));
@@ -320,71 +388,71 @@ impl PklSchemaRenderer {
         result
     }
 
-    /// Check if a name is a Pkl keyword and needs escaping
-    fn is_pkl_keyword(&self, name: &str) -> bool {
-        matches!(
-            name,
-            "abstract"
-                | "amends"
-                | "as"
-                | "case"
-                | "class"
-                | "const"
-                | "default"
-                | "delete"
-                | "else"
-                | "extends"
-                | "external"
-                | "false"
-                | "fixed"
-                | "for"
-                | "function"
-                | "hidden"
-                | "if"
-                | "import"
-                | "import*"
-                | "in"
-                | "is"
-                | "let"
-                | "local"
-                | "module"
-                | "new"
-                | "nothing"
-                | "null"
-                | "open"
-                | "out"
-                | "outer"
-                | "override"
-                | "overrides"
-                | "protected"
-                | "read"
-                | "read*"
-                | "record"
-                | "super"
-                | "switch"
-                | "this"
-                | "throw"
-                | "trace"
-                | "true"
-                | "typealias"
-                | "unknown"
-                | "vararg"
-                | "when"
-        )
-    }
-
-    /// Escape a name if it's a keyword
+    /// Escape a name if it's a keyword, or isn't a valid Pkl identifier (`foo-bar`, `1st`...)
     fn escape_name(&self, name: &str) -> String {
-        if self.is_pkl_keyword(name) {
-            format!("`{}`", name)
-        } else {
-            name.to_string()
+        escape_identifier(name)
+    }
+
+    /// Returns the Pkl type name for a named type
+    fn type_name(&self, name: &str) -> String {
+        self.escape_name(&self.to_pascal_case(name))
+    }
+
+    /// Renders a literal as a Pkl value, escaping strings.
+    fn render_literal_value(&self, value: &LiteralValue) -> String {
+        match value {
+            LiteralValue::Bool(b) => b.to_string(),
+            LiteralValue::Int(i) => i.to_string(),
+            LiteralValue::UInt(u) => u.to_string(),
+            LiteralValue::F32(f) => render_float(*f as f64),
+            LiteralValue::F64(f) => render_float(*f),
+            LiteralValue::String(s) => format!("\"{}\"", escape_string(s)),
         }
     }
 
-    fn render_union_default(&self, schema: &Schema) -> String {
-        // TODO: Implement union default rendering
-        String::new()
+    /// Renders the default of a union-typed property from the variant the default belongs to.
+    ///
+    /// Container variants take their contents from the type's default instance when there is one.
+    fn render_union_default(&self, field_name: &str, schema: &Schema) -> String {
+        let SchemaType::Union(union) = &schema.ty else {
+            return String::new();
+        };
+        let Some(variant) = union_default_index(union).map(|index| &union.variants_types[index]) else {
+            return String::new();
+        };
+        if variant.ty.get_default().is_none()
+            && let Some(value) = self.instance_default(field_name).filter(|value| !value.is_null())
+        {
+            return format!(" = {}", self.render_value(value, variant));
+        }
+        self.render_variant_default(variant)
+            .map(|value| format!(" = {}", value))
+            .unwrap_or_default()
+    }
+
+    /// The value a default union variant stands for: its own default, the literal itself, or an empty
+    /// `Listing`, `Mapping` or object for container variants.
+    fn render_variant_default(&self, variant: &Schema) -> Option<String> {
+        if let Some(default) = variant.ty.get_default() {
+            return Some(self.render_literal_value(default));
+        }
+        match &variant.ty {
+            SchemaType::Literal(literal) => Some(self.render_literal_value(&literal.value)),
+            SchemaType::Array(_) => Some("new Listing {}".to_string()),
+            SchemaType::Object(_) => Some("new Mapping {}".to_string()),
+            SchemaType::Struct(_) => Some(match &variant.name {
+                Some(name) => format!("new {} {{}}", self.qualified_type_name(name)),
+                None => "new Dynamic {}".to_string(),
+            }),
+            SchemaType::Union(union) => {
+                union_default_index(union).and_then(|index| self.render_variant_default(&union.variants_types[index]))
+            }
+            SchemaType::Reference(name) => self.schemas.get(name).and_then(|schema| {
+                let named = Schema { name: Some(name.clone()), ..schema.clone() };
+                self.render_variant_default(&named)
+            }),
+            _ => None,
+        }
     }
 
     fn set_number_constraints(&self, schema: &Schema) -> String {
@@ -393,30 +461,30 @@ impl PklSchemaRenderer {
         // Extract the number type based on schema type
         let (minimum, maximum, minimum_exclusive, maximum_exclusive, multiple_of) = match &schema.ty {
             SchemaType::Integer(int_type) => (
-                int_type.minimum.as_ref(),
-                int_type.maximum.as_ref(),
-                int_type.minimum_exclusive.as_ref(),
-                int_type.maximum_exclusive.as_ref(),
-                int_type.multiple_of.as_ref(),
+                int_type.min.map(|v| v.to_string()),
+                int_type.max.map(|v| v.to_string()),
+                int_type.min_exclusive.map(|v| v.to_string()),
+                int_type.max_exclusive.map(|v| v.to_string()),
+                int_type.multiple_of.map(|v| v.to_string()),
             ),
             SchemaType::Float(float_type) => (
-                float_type.minimum.as_ref(),
-                float_type.maximum.as_ref(),
-                float_type.minimum_exclusive.as_ref(),
-                float_type.maximum_exclusive.as_ref(),
-                float_type.multiple_of.as_ref(),
+                float_type.min.map(render_float),
+                float_type.max.map(render_float),
+                float_type.min_exclusive.map(render_float),
+                float_type.max_exclusive.map(render_float),
+                float_type.multiple_of.map(render_float),
             ),
             _ => return String::new(),
         };
 
         // Min/max constraints (inclusive)
-        if let Some(min) = minimum {
-            if let Some(max) = maximum {
+        if let Some(min) = &minimum {
+            if let Some(max) = &maximum {
                 constraints.push(format!("isBetween({}, {})", min, max));
             } else {
                 constraints.push(format!("this >= {}", min));
             }
-        } else if let Some(max) = maximum {
+        } else if let Some(max) = &maximum {
             constraints.push(format!("this <= {}", max));
         }
 
@@ -440,32 +508,32 @@ impl PklSchemaRenderer {
         }
     }
 
+    /// Length constraints shared by strings, listings and mappings
+    fn length_constraints(&self, min_length: Option<usize>, max_length: Option<usize>, constraints: &mut Vec<String>) {
+        match (min_length, max_length) {
+            (Some(min_len), Some(max_len)) => {
+                constraints.push(format!("length.isBetween({}, {})", min_len, max_len));
+            }
+            // the idiomatic way to say "not empty"
+            (Some(1), None) => constraints.push("!isEmpty".to_string()),
+            (Some(min_len), None) => constraints.push(format!("length >= {}", min_len)),
+            (None, Some(max_len)) => constraints.push(format!("length <= {}", max_len)),
+            (None, None) => {}
+        }
+    }
+
     fn render_constraints(&self, schema: &Schema) -> String {
         if !self.options.include_constraints {
             return String::new();
         }
 
+        let mut constraints = Vec::new();
         match &schema.ty {
-            SchemaType::Integer(int_type) => {
-                return self.set_number_constraints(&schema);
-            }
-            SchemaType::Float(float_type) => {
-                return self.set_number_constraints(&schema);
+            SchemaType::Integer(_) | SchemaType::Float(_) => {
+                return self.set_number_constraints(schema);
             }
             SchemaType::String(string_type) => {
-                let mut constraints = Vec::new();
-
-                // Length constraints
-                if let Some(min_len) = &string_type.min_length {
-                    if let Some(max_len) = &string_type.max_length {
-                        constraints
-                            .push(format!("this.length.isBetween({}, {})", min_len, max_len));
-                    } else {
-                        constraints.push(format!("this.length >= {}", min_len));
-                    }
-                } else if let Some(max_len) = &string_type.max_length {
-                    constraints.push(format!("this.length <= {}", max_len));
-                }
+                self.length_constraints(string_type.min_length, string_type.max_length, &mut constraints);
 
                 // Pattern constraint
                 if let Some(pattern) = &string_type.pattern {
@@ -477,363 +545,272 @@ impl PklSchemaRenderer {
                     match format.as_str() {
                     "email" => constraints.push("contains(\"@\")".to_string()),
                     "uri" | "url" => constraints.push("startsWith(\"http\")".to_string()),
-                    "uuid" => constraints.push("matches(Regex(#\"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$\"#))".to_string()),
+                    "uuid" => constraints.push("matches(Regex(#\"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$\"#))".to_string()),
                     "ipv4" => constraints.push("matches(Regex(#\"^((25[0-5]|(2[0-4]|1\\d|[1-9]|)\\d)\\.?\\b){4}$\"#))".to_string()),
                     _ => {}
                   }
                 }
-
-                // Non-empty constraint for min_length = 1
-                if let Some(min_len) = &string_type.min_length {
-                    if *min_len == 1 && !constraints.iter().any(|c| c.contains("length")) {
-                        constraints.push("!isBlank".to_string());
-                    }
-                }
-
-                if !constraints.is_empty() {
-                    return format!("({})", constraints.join(" && "));
-                }
             }
             SchemaType::Array(array_type) => {
-                let mut constraints = Vec::new();
-
-                // Length constraints
-                if let Some(min_len) = &array_type.min_length {
-                    if let Some(max_len) = &array_type.max_length {
-                        constraints
-                            .push(format!("this.length.isBetween({}, {})", min_len, max_len));
-                    } else {
-                        constraints.push(format!("this.length >= {}", min_len));
-                    }
-                } else if let Some(max_len) = &array_type.max_length {
-                    constraints.push(format!("this.length <= {}", max_len));
-                }
+                self.length_constraints(array_type.min_length, array_type.max_length, &mut constraints);
 
                 // Uniqueness constraint
-                if let Some(unique) = &array_type.unique {
-                    if *unique {
-                        constraints.push("this.isDistinct".to_string());
-                    }
-                }
-
-                // Special length constraints for single element arrays
-                if let Some(min_len) = &array_type.min_length {
-                    if let Some(max_len) = &array_type.max_length {
-                        if *min_len == 1 && *max_len == 1 {
-                            constraints.clear(); // Replace length constraint
-                            constraints.push("this.single".to_string());
-                        }
-                    }
-                }
-
-                // Check for singleOrNull (0 or 1 elements)
-                if let Some(max_len) = &array_type.max_length {
-                    if *max_len == 1 && array_type.min_length.is_none() {
-                        constraints.retain(|c| !c.contains("length")); // Remove length constraint
-                        let single_constraint = if schema.optional {
-                            "this.singleOrNull".to_string()
-                        } else {
-                            "this.single".to_string()
-                        };
-                        constraints.push(single_constraint);
-                    }
-                }
-
-                if !constraints.is_empty() {
-                    return format!("({})", constraints.join(" && "));
+                if array_type.unique == Some(true) {
+                    constraints.push("isDistinct".to_string());
                 }
             }
             SchemaType::Object(obj_type) => {
-                let mut constraints = Vec::new();
-
                 // Length constraints (key-value pairs)
-                if let Some(min_len) = &obj_type.min_length {
-                    if let Some(max_len) = &obj_type.max_length {
-                        constraints
-                            .push(format!("this.length.isBetween({}, {})", min_len, max_len));
-                    } else {
-                        constraints.push(format!("this.length >= {}", min_len));
-                    }
-                } else if let Some(max_len) = &obj_type.max_length {
-                    constraints.push(format!("this.length <= {}", max_len));
-                }
+                self.length_constraints(obj_type.min_length, obj_type.max_length, &mut constraints);
 
                 // Required keys constraint
-                if let Some(required_keys) = &obj_type.required {
-                    if !required_keys.is_empty() {
-                        let keys_list = required_keys
-                            .iter()
-                            .map(|k| format!("\"{}\"", k))
-                            .collect::<Vec<_>>()
-                            .join(", ");
-                        constraints.push(format!(
-                            "List({}).every((k) -> this.containsKey(k))",
-                            keys_list
-                        ));
-                    }
-                }
-
-                if !constraints.is_empty() {
-                    return format!("({})", constraints.join(" && "));
+                if let Some(required_keys) = obj_type.required.as_ref().filter(|keys| !keys.is_empty()) {
+                    let keys_list = required_keys
+                        .iter()
+                        .map(|k| format!("\"{}\"", escape_string(k)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    constraints.push(format!(
+                        "List({}).every((k) -> containsKey(k))",
+                        keys_list
+                    ));
                 }
             }
-            _ => return String::new(),
+            _ => {}
         }
 
-        String::new()
+        if !constraints.is_empty() {
+            format!("({})", constraints.join(" && "))
+        } else {
+            String::new()
+        }
     }
 
-    fn render_default_value(&self, schema: &Schema) -> String {
+    /// Renders ` = value` for a property with a default: scalars from the schema, `Listing`s and
+    /// `Mapping`s from [`PklSchemaOptions::default_values`].
+    fn render_default_value(&self, field_name: &str, schema: &Schema) -> String {
         if !self.options.include_defaults {
             return String::new();
         }
 
-        // Check for defaults in the schema's inner types
         match &schema.ty {
-            SchemaType::Boolean(bool_type) => {
-                if let Some(default) = &bool_type.default {
-                    return format!(" = {}", default);
+            SchemaType::Boolean(_)
+            | SchemaType::Integer(_)
+            | SchemaType::Float(_)
+            | SchemaType::String(_)
+            | SchemaType::Enum(_) => {
+                if let Some(default) = schema.ty.get_default() {
+                    return format!(" = {}", self.render_literal_value(default));
                 }
             }
-            SchemaType::Integer(int_type) => {
-                if let Some(default) = &int_type.default {
-                    return format!(" = {}", default);
+            SchemaType::Array(_) | SchemaType::Object(_) | SchemaType::Tuple(_) => {
+                // empty ones are Pkl's default already
+                if let Some(value) = self.instance_default(field_name).filter(|value| !is_empty_value(value)) {
+                    return format!(" = {}", self.render_value(value, schema));
                 }
             }
-            SchemaType::Float(float_type) => {
-                if let Some(default) = &float_type.default {
-                    return format!(" = {}", default);
-                }
+            SchemaType::Union(_) => return self.render_union_default(field_name, schema),
+            _ => {}
+        }
+
+        String::new()
+    }
+
+    /// The value of `field_name` in the default instance of the type being rendered
+    fn instance_default(&self, field_name: &str) -> Option<&Value> {
+        let current_type = self.current_type.as_ref()?;
+        self.default_values.get(current_type)?.get(field_name)
+    }
+
+    /// Renders a serialized value as a Pkl literal of the given type.
+    fn render_value(&self, value: &Value, schema: &Schema) -> String {
+        match (&schema.ty, value) {
+            (_, Value::Null) => "null".to_string(),
+            (SchemaType::Reference(name), _) => match self.schemas.get(name) {
+                Some(target) => self.render_value(value, &Schema { name: Some(name.clone()), ..target.clone() }),
+                None => self.render_untyped_value(value),
+            },
+            (SchemaType::Union(union), _) => match union.variants_types.iter().find(|variant| self.value_fits(value, variant)) {
+                Some(variant) => self.render_value(value, variant),
+                None => self.render_untyped_value(value),
+            },
+            (SchemaType::Array(array), Value::Array(items)) => {
+                let elements: Vec<String> = items.iter().map(|item| self.render_value(item, &array.items_type)).collect();
+                render_object_body("new Listing", &elements)
             }
-            SchemaType::String(string_type) => {
-                if let Some(default) = &string_type.default {
-                    return format!(" = \"{}\"", default);
+            (SchemaType::Tuple(tuple), Value::Array(items)) if tuple.items_types.len() == items.len() => {
+                let elements: Vec<String> = items
+                    .iter()
+                    .zip(&tuple.items_types)
+                    .map(|(item, item_type)| self.render_value(item, item_type))
+                    .collect();
+                if elements.len() == 2 {
+                    format!("Pair({}, {})", elements[0], elements[1])
+                } else {
+                    render_object_body("new Listing", &elements)
                 }
             }
-            SchemaType::Array(array_type) => {
-                if array_type.default.is_some() {
-                    return " = new Listing {}".to_string();
-                }
+            (SchemaType::Object(object), Value::Object(entries)) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, entry)| format!("[\"{}\"] = {}", escape_string(key), self.render_value(entry, &object.value_type)))
+                    .collect();
+                render_object_body("new Mapping", &entries)
             }
-            SchemaType::Object(obj_type) => {
-                if obj_type.default.is_some() {
-                    return " = new Mapping {}".to_string();
-                }
+            (SchemaType::Struct(structure), Value::Object(entries)) => {
+                let properties: Vec<String> = entries
+                    .iter()
+                    .filter(|(_, entry)| !entry.is_null())
+                    .filter_map(|(key, entry)| {
+                        let field = structure.fields.get(key)?;
+                        Some(format!("{} = {}", self.escape_name(key), self.render_value(entry, &field.schema)))
+                    })
+                    .collect();
+                let class_name = schema.name.as_deref().map(|name| self.qualified_type_name(name));
+                render_object_body(&format!("new {}", class_name.as_deref().unwrap_or("Dynamic")), &properties)
             }
-            SchemaType::Enum(enum_type) => {
-                if let Some(default) = &enum_type.default {
-                    match default {
-                        LiteralValue::String(s) => return format!(" = \"{}\"", s),
-                        LiteralValue::Integer(i) => return format!(" = {}", i),
-                        LiteralValue::Float(f) => return format!(" = {}", f),
-                        LiteralValue::Boolean(b) => return format!(" = {}", b),
-                    }
-                }
+            (SchemaType::Float(_), Value::Number(number)) => number.as_f64().map(render_float).unwrap_or_else(|| number.to_string()),
+            _ => self.render_untyped_value(value),
+        }
+    }
+
+    /// Renders a serialized value without a type to go on: arrays become `Listing`s, objects `Mapping`s.
+    fn render_untyped_value(&self, value: &Value) -> String {
+        match value {
+            Value::Null => "null".to_string(),
+            Value::Bool(boolean) => boolean.to_string(),
+            Value::Number(number) => number.to_string(),
+            Value::String(string) => format!("\"{}\"", escape_string(string)),
+            Value::Array(items) => {
+                let elements: Vec<String> = items.iter().map(|item| self.render_untyped_value(item)).collect();
+                render_object_body("new Listing", &elements)
+            }
+            Value::Object(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, entry)| format!("[\"{}\"] = {}", escape_string(key), self.render_untyped_value(entry)))
+                    .collect();
+                render_object_body("new Mapping", &entries)
             }
-            _ => {}
         }
+    }
 
-        String::new()
+    /// Whether a serialized value has the shape of a (union variant) type
+    fn value_fits(&self, value: &Value, schema: &Schema) -> bool {
+        match (&schema.ty, value) {
+            (SchemaType::Reference(name), _) => self.schemas.get(name).is_some_and(|target| self.value_fits(value, target)),
+            (SchemaType::Union(union), _) => union.variants_types.iter().any(|variant| self.value_fits(value, variant)),
+            (SchemaType::Literal(literal), _) => literal_matches(&literal.value, value),
+            (SchemaType::Enum(enum_type), _) => enum_type.values.iter().any(|known| literal_matches(known, value)),
+            (SchemaType::Null, Value::Null)
+            | (SchemaType::Boolean(_), Value::Bool(_))
+            | (SchemaType::String(_), Value::String(_))
+            | (SchemaType::Array(_) | SchemaType::Tuple(_), Value::Array(_))
+            | (SchemaType::Object(_) | SchemaType::Struct(_), Value::Object(_)) => true,
+            (SchemaType::Integer(_), Value::Number(number)) => number.is_i64() || number.is_u64(),
+            (SchemaType::Float(_), Value::Number(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Adds a generated typealias, reusing an existing one with the same body.
+    fn add_typealias(&mut self, prefix: &str, body: String) -> String {
+        if let Some((name, _)) = self.typealiases.iter().find(|(_, existing)| **existing == body) {
+            return name.clone();
+        }
+        let alias_name = format!("{}{}", prefix, self.typealiases.len());
+        self.typealiases.insert(alias_name.clone(), body);
+        alias_name
     }
 
     fn render_field_type(&mut self, schema: &Schema) -> RenderResult<String> {
-        let (base_type, has_default) = match &schema.ty {
-            SchemaType::Boolean(_) => ("Boolean".to_string(), false),
-            SchemaType::Integer(int_type) => {
+        // named types are referenced by name, not rendered again
+        if let Some(name) = &schema.name
+            && self.is_reference(name)
+            && !schema.ty.is_reference()
+        {
+            return self.render_reference(name, schema);
+        }
+
+        let base_type = match &schema.ty {
+            SchemaType::Boolean(boolean) => self.render_boolean(boolean, schema)?,
+            SchemaType::Integer(integer) => {
                 // Check for enum values first
-                if let Some(enum_values) = &int_type.enum_values {
+                if let Some(enum_values) = &integer.enum_values {
                     let variants: Vec<String> = enum_values.iter().map(|v| v.to_string()).collect();
-                    let enum_type = variants.join("|");
-                    let alias_name = format!("IntegerEnum{}", self.typealiases.len());
-                    self.typealiases.insert(alias_name.clone(), enum_type);
-                    return Ok(alias_name);
+                    return Ok(self.enum_type_or_alias("IntegerEnum", variants.join(" | ")));
                 }
-
-                // Check for special integer types based on min/max
-                let type_name =
-                    if let (Some(min), Some(max)) = (&int_type.minimum, &int_type.maximum) {
-                        match (min, max) {
-                            (0, 255) => "UInt8".to_string(),
-                            (0, 65535) => "UInt16".to_string(),
-                            (0, 4294967295) => "UInt32".to_string(),
-                            (-128, 127) => "Int8".to_string(),
-                            (-32768, 32767) => "Int16".to_string(),
-                            (-2147483648, 2147483647) => "Int32".to_string(),
-                            _ => "Int".to_string(),
-                        }
-                    } else {
-                        "Int".to_string()
-                    };
-                (type_name, int_type.default.is_some())
+                self.render_integer(integer, schema)?
             }
-            SchemaType::Float(float_type) => {
+            SchemaType::Float(float) => {
                 // Check for enum values first
-                if let Some(enum_values) = &float_type.enum_values {
-                    let variants: Vec<String> = enum_values.iter().map(|v| v.to_string()).collect();
-                    let enum_type = variants.join("|");
-                    let alias_name = format!("FloatEnum{}", self.typealiases.len());
-                    self.typealiases.insert(alias_name.clone(), enum_type);
-                    return Ok(alias_name);
+                if let Some(enum_values) = &float.enum_values {
+                    let variants: Vec<String> = enum_values.iter().map(|v| render_float(*v)).collect();
+                    return Ok(self.enum_type_or_alias("FloatEnum", variants.join(" | ")));
                 }
-
-                ("Number".to_string(), float_type.default.is_some())
+                self.render_float(float, schema)?
             }
-            SchemaType::String(string_type) => {
+            SchemaType::String(string) => {
                 // Check for enum values first
-                if let Some(enum_values) = &string_type.enum_values {
-                    let variants: Vec<String> =
-                        enum_values.iter().map(|v| format!("\"{}\"", v)).collect();
-                    let enum_type = variants.join("|");
-                    let alias_name = format!("StringEnum{}", self.typealiases.len());
-                    self.typealiases.insert(alias_name.clone(), enum_type);
-                    return Ok(alias_name);
+                if let Some(enum_values) = &string.enum_values {
+                    let variants: Vec<String> = enum_values
+                        .iter()
+                        .map(|v| format!("\"{}\"", escape_string(v)))
+                        .collect();
+                    return Ok(self.enum_type_or_alias("StringEnum", variants.join(" | ")));
                 }
-
-                // Check for special string formats that could be Duration or DataSize
-                let type_name = if let Some(format) = &string_type.format {
-                    match format.as_str() {
-                        "duration" => {
-                            if let Some(duration) = &string_type.duration {
-                                format!("Duration<{}>", duration.to_lowercase())
-                            } else {
-                                "Duration".to_string()
-                            }
-                        }
-                        "data-size" | "datasize" => {
-                            if let Some(data_size) = &string_type.data_size {
-                                format!("DataSize<{}>", data_size.to_lowercase())
-                            } else {
-                                "DataSize".to_string()
-                            }
-                        }
-                        _ => "String".to_string(),
-                    }
-                } else {
-                    "String".to_string()
-                };
-                (type_name, string_type.default.is_some())
-            }
-            SchemaType::Array(array) => {
-                let item_type = self.render_field_type(&array.items_type)?;
-                (format!("Listing<{}>", item_type), array.default.is_some())
+                self.render_string(string, schema)?
             }
-            SchemaType::Object(obj) => {
-                let key_type = self.render_field_type(&obj.key_type)?;
-                let value_type = self.render_field_type(&obj.value_type)?;
-                (
-                    format!("Mapping<{}, {}>", key_type, value_type),
-                    obj.default.is_some(),
-                )
-            }
-            SchemaType::Tuple(tuple) => {
-                // Pkl doesn't have tuples, use Pair for 2-element or Listing for more
-                let type_name = if tuple.items_types.len() == 2 {
-                    let first = self.render_field_type(&tuple.items_types[0])?;
-                    let second = self.render_field_type(&tuple.items_types[1])?;
-                    format!("Pair<{}, {}>", first, second)
+            SchemaType::Array(array) => self.render_array(array, schema)?,
+            SchemaType::Object(object) => self.render_object(object, schema)?,
+            SchemaType::Tuple(tuple) => self.render_tuple(tuple, schema)?,
+            SchemaType::Union(union) => self.render_union(union, schema)?,
+            SchemaType::Enum(enum_type) => {
+                let variants = self.render_enum(enum_type, schema)?;
+                if !self.options.enum_translation.use_typealias() {
+                    variants
                 } else {
-                  // TODO: Handle this union
-                    let item_type = self.render_field_type(&tuple.items_types)?;
-                    format!("Listing<{}>", item_type)
-                };
-                (type_name, false)
-            }
-            SchemaType::Union(union) => {
-                let mut types: Vec<String> = Vec::new();
-                let mut default_type_index = None;
-
-                // Check if any variant has a default value
-                for (i, variant) in union.variants_types.iter().enumerate() {
-                    let variant_type = self.render_field_type(variant)?;
-                    let has_default = match &variant.ty {
-                        SchemaType::Boolean(b) => b.default.is_some(),
-                        SchemaType::Integer(int) => int.default.is_some(),
-                        SchemaType::Float(f) => f.default.is_some(),
-                        SchemaType::String(s) => s.default.is_some(),
-                        SchemaType::Array(a) => a.default.is_some(),
-                        SchemaType::Object(o) => o.default.is_some(),
-                        _ => false,
-                    };
-
-                    if has_default && default_type_index.is_none() {
-                        default_type_index = Some(i);
-                        types.push(format!("*{}", variant_type));
-                    } else {
-                        types.push(variant_type);
+                    // Create a typealias for the enum
+                    match &schema.name {
+                        Some(name) => {
+                            let alias_name = self.type_name(name);
+                            self.typealiases.entry(alias_name.clone()).or_insert(variants);
+                            alias_name
+                        }
+                        None => self.add_typealias("EnumType", variants),
                     }
                 }
-
-                let union_type = types.join("|");
-
-                // If it's a complex union, consider creating a typealias
-                let final_type = if union.variants_types.len() > 3 {
-                    let alias_name = format!("UnionType{}", self.typealiases.len());
-                    self.typealiases
-                        .insert(alias_name.clone(), union_type.clone());
-                    alias_name
-                } else {
-                    union_type
-                };
-
-                (final_type, default_type_index.is_some())
             }
-            SchemaType::Enum(enum_type) => {
-                let mut variants: Vec<String> = enum_type
-                    .values
-                    .iter()
-                    .map(|v| match v {
-                        LiteralValue::String(s) => format!("\"{}\"", s),
-                        LiteralValue::Integer(i) => i.to_string(),
-                        LiteralValue::Float(f) => f.to_string(),
-                        LiteralValue::Boolean(b) => b.to_string(),
-                    })
-                    .collect();
-
-                // If there's a default, mark the corresponding type with *
-                if let Some(default_val) = &enum_type.default {
-                    // Find the index of the default value in the variants
-                    let default_index = enum_type.values.iter().position(|v| v == default_val).unwrap_or(0);
-                    if default_index < variants.len() {
-                        variants[default_index] = format!("*{}", variants[default_index]);
-                    }
-                }
+            SchemaType::Literal(literal) => self.render_literal(literal, schema)?,
+            SchemaType::Struct(structure) => self.render_struct(structure, schema)?,
+            SchemaType::Reference(reference) => self.render_reference(reference, schema)?,
+            SchemaType::Null => self.render_null(schema)?,
+            SchemaType::Unknown => self.render_unknown(schema)?,
+        };
 
-                let enum_type_str = variants.join("|");
+        Ok(base_type)
+    }
 
-                // Create a typealias for the enum
-                let alias_name = if enum_type.name.is_empty() {
-                    format!("EnumType{}", self.typealiases.len())
-                } else {
-                    self.to_pascal_case(&enum_type.name.clone())
-                };
-                if self.typealiases.contains_key(&alias_name)
-                    && enum_type_str == self.typealiases[&alias_name]
-                {
-                    return Ok(alias_name);
-                }
-                self.typealiases.insert(alias_name.clone(), enum_type_str);
-                (alias_name, enum_type.default.is_some())
-            }
-            SchemaType::Literal(literal) => {
-                let literal_str = match &literal.value {
-                    LiteralValue::String(s) => format!("\"{}\"", s),
-                    LiteralValue::Integer(i) => i.to_string(),
-                    LiteralValue::Float(f) => f.to_string(),
-                    LiteralValue::Boolean(b) => b.to_string(),
-                };
-                (literal_str, false)
-            }
-            SchemaType::Struct(_) => {
-              // TODO: Replace with class implementation
-                ("Dynamic".to_string(), false)
-            }
-            SchemaType::Reference(reference) => (self.to_pascal_case(&reference.name), false),
-            SchemaType::Null => ("nothing".to_string(), false),
-            SchemaType::Unknown => ("unknown".to_string(), false),
-        };
+    /// Inline literal unions, or a generated typealias for them, depending on [`PklSchemaOptions::enum_translation`]
+    fn enum_type_or_alias(&mut self, prefix: &str, variants: String) -> String {
+        if self.options.enum_translation.use_typealias() {
+            self.add_typealias(prefix, variants)
+        } else {
+            format!("({})", variants)
+        }
+    }
 
-        let constraints = self.render_constraints(schema);
-        Ok(format!("{}{}", base_type, constraints))
+    /// Applies [`PklSchemaOptions::optional_format`] to an optional property type.
+    fn render_optional(&self, field_type: &str) -> String {
+        if field_type.ends_with('?') {
+            return field_type.to_string();
+        }
+        // `A | B?` would only make `B` nullable
+        if field_type.contains(" | ") && !field_type.starts_with('(') {
+            format!("({})?", field_type)
+        } else {
+            format!("{}?", field_type)
+        }
     }
 
     fn render_docs(&self, description: Option<&str>) -> String {
@@ -841,20 +818,97 @@ impl PklSchemaRenderer {
             return String::new();
         }
 
-        if let Some(desc) = description {
-            if !desc.is_empty() {
-                return format!("{}/// {}\n", self.indent(), desc);
+        let Some(desc) = description.map(str::trim).filter(|desc| !desc.is_empty()) else {
+            return String::new();
+        };
+        let desc = self.resolve_doc_references(desc);
+
+        desc.trim()
+            .lines()
+            .map(|line| format!("{}/// {}", self.indent(), line.trim_end()).trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+            + "\n"
+    }
+
+    /// Turns rustdoc intra-doc links in a description into Pkldoc member links to the rendered types.
+    ///
+    /// `[Bar]` and [`Bar`] become `[Bar]`, `[text](Bar)`, ``[text](`Bar`)`` and `[text][Bar]` become
+    /// `[text][Bar]`, and paths like `Bar::field` link to the property (`[Bar.field]`), or to the type when the
+    /// rest of the path can't be found. Reference definitions (`[b]: Bar`) are followed and dropped. Links to
+    /// anything that isn't rendered lose their brackets, and URLs are left alone.
+    pub fn resolve_doc_references(&self, text: &str) -> String {
+        static DEFINITION: OnceLock<Regex> = OnceLock::new();
+        static LINK: OnceLock<Regex> = OnceLock::new();
+        let definition = DEFINITION.get_or_init(|| Regex::new(r"(?m)^[ \t]*\[([^\[\]]+)\]:[ \t]*(\S+)[ \t]*$").unwrap());
+        let link = LINK.get_or_init(|| {
+            Regex::new(r"\[(?P<label>[^\[\]]+)\](?:\((?P<inline>[^()\s]+)\)|\[(?P<reference>[^\[\]]+)\])?").unwrap()
+        });
+
+        let definitions: HashMap<&str, &str> = definition
+            .captures_iter(text)
+            .filter_map(|caps| Some((caps.get(1)?.as_str(), caps.get(2)?.as_str())))
+            .collect();
+        let text = definition.replace_all(text, "");
+
+        link.replace_all(&text, |caps: &regex::Captures| {
+            let original = &caps[0];
+            let label = &caps["label"];
+            let (target, text) = match (caps.name("inline"), caps.name("reference")) {
+                (Some(inline), _) => (inline.as_str(), Some(label)),
+                (_, Some(reference)) => {
+                    let reference = reference.as_str();
+                    (definitions.get(reference).copied().unwrap_or(reference), Some(label))
+                }
+                (None, None) => (label, None),
+            };
+            let path = target.trim_matches('`');
+            if !is_doc_path(path) {
+                return original.to_string();
             }
-        }
+            match (self.resolve_doc_path(path), text) {
+                (Some((target, true)), None) => format!("[{}]", target),
+                (Some((target, _)), None) => format!("[{}][{}]", path, target),
+                (Some((target, _)), Some(text)) => format!("[{}][{}]", text, target),
+                // bare brackets are just brackets, and inline targets may be relative URLs
+                (None, _) if !label.starts_with('`') && !target.starts_with('`') && caps.name("reference").is_none() => {
+                    original.to_string()
+                }
+                (None, _) => text.unwrap_or(label).to_string(),
+            }
+        })
+        .into_owned()
+    }
 
-        String::new()
+    /// Finds the Pkldoc target of a rustdoc path, with whether it's the whole path (and not a fallback to its type)
+    fn resolve_doc_path(&self, path: &str) -> Option<(String, bool)> {
+        let mut segments = path.split("::").flat_map(|segment| segment.split('.'));
+        let root = segments.next()?;
+        let schema = self.schemas.get(root)?;
+        // a root type is its module, whose properties are linked by name alone from inside it
+        let roots = self.roots();
+        let root_module = roots.get(root).filter(|_| self.options.config_translation.as_module());
+        let is_module = root_module.is_some_and(|module| self.current_module.as_ref().is_none_or(|current| current == module));
+        let type_name = match root_module {
+            Some(module) => module.clone(),
+            None => self.qualified_type_name(root),
+        };
+        let rest: Vec<&str> = segments.collect();
+        match (rest.as_slice(), &schema.ty) {
+            ([], _) => Some((type_name, true)),
+            ([field], SchemaType::Struct(structure)) if structure.fields.contains_key(*field) => {
+                let field = self.escape_name(field);
+                Some((if is_module { field } else { format!("{}.{}", type_name, field) }, true))
+            }
+            _ => Some((type_name, false)),
+        }
     }
 
     fn render_deprecation(&self, schema: &Schema, field: Option<&SchemaField>) -> String {
         // Check for deprecation in both Schema and SchemaField
         let deprecated = field
             .and_then(|f| f.deprecated.as_ref())
-            .or_else(|| schema.deprecated.as_ref());
+            .or(schema.deprecated.as_ref());
 
         if let Some(deprecated_msg) = deprecated {
             if deprecated_msg.is_empty() {
@@ -865,108 +919,115 @@ impl PklSchemaRenderer {
                 let mut parts = Vec::new();
 
                 // Try to extract "since" information
-                if let Some(since_match) = deprecated_msg.strip_prefix("since ") {
-                    if let Some(version) = since_match.split_whitespace().next() {
-                        parts.push(format!(
-                            "since = \"{}\"",
-                            version.trim_matches(&['v', 'V'][..])
-                        ));
-                    }
+                if let Some(version) = deprecated_msg
+                    .strip_prefix("since ")
+                    .and_then(|since_match| since_match.split_whitespace().next())
+                {
+                    parts.push(format!(
+                        "since = \"{}\"",
+                        version.trim_matches(&['v', 'V'][..])
+                    ));
                 }
 
                 // Use the full message as the message field
-                parts.push(format!("message = \"{}\"", deprecated_msg));
+                parts.push(format!("message = \"{}\"", escape_string(deprecated_msg)));
 
-                if parts.len() == 1 {
-                    return format!("{}@Deprecated {{ {} }}\n", self.indent(), parts[0]);
-                } else {
-                    return format!("{}@Deprecated {{ {} }}\n", self.indent(), parts.join("; "));
-                }
+                return format!("{}@Deprecated {{ {} }}\n", self.indent(), parts.join("; "));
             }
         }
 
         String::new()
     }
 
-    /// Convert to camelCase for properties
-    fn to_camel_case(&self, name: &str) -> String {
-        if name.is_empty() {
-            return name.to_string();
+    /// Whether a struct field ends up in the output
+    fn include_field(&self, field_name: &str, field: &SchemaField) -> bool {
+        // Skip hidden fields
+        if field.hidden || self.options.exclude_properties.iter().any(|excluded| excluded == field_name) {
+            return false;
         }
+        self.options.include_deprecated || (field.deprecated.is_none() && field.schema.deprecated.is_none())
+    }
 
-        let mut result = String::new();
-        let mut capitalize_next = false;
-        let mut first_char = true;
+    /// Renders one property: docs, deprecation, then `name: Type = default`.
+    fn render_property(&mut self, field_name: &str, field: &SchemaField, structure: &StructType) -> RenderResult<String> {
+        let mut output = String::new();
 
-        for ch in name.chars() {
-            if ch == '_' || ch == '-' {
-                capitalize_next = true;
-            } else if capitalize_next {
-                result.push(ch.to_uppercase().next().unwrap_or(ch));
-                capitalize_next = false;
-            } else if first_char {
-                result.push(ch.to_lowercase().next().unwrap_or(ch));
-                first_char = false;
-            } else {
-                result.push(ch);
-            }
-        }
-
-        result
-    }
+        // Field documentation (use comment from SchemaField, fallback to schema description)
+        let field_description = field.comment.as_ref().or(field.schema.description.as_ref());
+        output.push_str(&self.render_docs(field_description.map(String::as_str)));
 
-    fn render_struct_as_module(
-        &mut self,
-        name: &str,
-        structure: &StructType,
-        schema: &Schema,
-    ) -> RenderResult<String> {
-        let mut output = Vec::new();
-        let module_name = self.to_pascal_case(name);
+        // Add deprecation annotation after the docs, right before the property
+        if self.options.include_deprecated {
+            output.push_str(&self.render_deprecation(&field.schema, Some(field)));
+        }
 
-        // Add module documentation
-        if let Some(description) = &schema.description {
-            output.push(self.render_docs(Some(description)));
+        let is_required = structure
+            .required
+            .as_ref()
+            .is_some_and(|required| required.iter().any(|name| name == field_name));
+        let optional = field.optional
+            || field.nullable
+            || field.schema.nullable
+            || (self.options.property_default.is_optional() && !is_required);
+
+        let mut field_type = self.render_field_type(&field.schema)?;
+        let mut default_value = self.render_default_value(field_name, &field.schema);
+        if optional {
+            field_type = self.render_optional(&field_type);
+            if default_value.is_empty() && self.options.optional_format.is_explicit() {
+                default_value = " = null".to_string();
+            }
         }
 
-        // Start module definition
-        output.push(format!("module {}", self.escape_name(&module_name)));
-        output.push(String::new()); // Empty line after module declaration
+        let property = format!("{}: {}{}", self.escape_name(field_name), field_type, default_value);
+        if optional && self.options.comment_out_optional {
+            output.push_str(&format!("{}// {}", self.indent(), property));
+        } else {
+            output.push_str(&format!("{}{}", self.indent(), property));
+        }
+        Ok(output)
+    }
 
-        // Render fields as module properties
+    fn render_properties(&mut self, structure: &StructType) -> RenderResult<String> {
+        let mut properties = Vec::new();
         for (field_name, field) in &structure.fields {
-            // Skip hidden fields
-            if field.hidden {
-                continue;
+            if self.include_field(field_name, field) {
+                properties.push(self.render_property(field_name, field, structure)?);
             }
+        }
+        Ok(properties.join("\n\n"))
+    }
 
-            // Add deprecation annotation first
-            output.push(self.render_deprecation(&field.schema, Some(field)));
-
-            // Field documentation (use comment from SchemaField, fallback to schema description)
-            let field_description = field.comment.as_ref().or(field.schema.description.as_ref());
-            if let Some(description) = field_description {
-                output.push(self.render_docs(Some(description)));
-            }
+    fn render_module_header(&self, module_name: &str, root: Option<&Schema>) -> String {
+        let mut output = String::new();
 
-            // Determine if field should be hidden
-            let hidden_modifier = if field.hidden { "hidden " } else { "" };
+        // Add module documentation
+        if let Some(schema) = root {
+            output.push_str(&self.render_docs(schema.description.as_deref()));
+        }
 
-            // Field type declaration
-            let field_type = self.render_field_type(&field.schema)?;
-            let field_name_camel = self.to_camel_case(field_name);
-            let escaped_name = self.escape_name(&field_name_camel);
-            let optional_marker = if field.optional { "?" } else { "" };
-            let default_value = self.render_default_value(&field.schema);
+        let modifier = if self.options.open_module.is_open() { "open " } else { "" };
+        output.push_str(&format!("{}module {}", modifier, self.type_name(module_name)));
 
-            output.push(format!(
-                "{}{}: {}{}{}",
-                hidden_modifier, escaped_name, field_type, optional_marker, default_value
-            ));
-            output.push(String::new()); // Empty line between properties
+        let mut imports: Vec<String> = self
+            .options
+            .added_imports
+            .iter()
+            .map(|import| {
+                if import.starts_with("import ") {
+                    import.trim().to_string() // already formatted
+                } else {
+                    format!("import \"{}\"", import.trim().trim_matches(['"', '\'']))
+                }
+            })
+            .collect();
+        imports.extend(self.module_imports(module_name));
+        if !imports.is_empty() {
+            output.push_str("\n\n");
+            output.push_str(&imports.join("\n"));
         }
 
-        Ok(output.join("\n"))
+        output
     }
 
     fn render_as_class(
@@ -975,80 +1036,618 @@ impl PklSchemaRenderer {
         structure: &StructType,
         schema: &Schema,
     ) -> RenderResult<String> {
-        let mut output = Vec::new();
-        let class_name = self.to_pascal_case(name);
+        let mut output = String::new();
 
         // Add class documentation
-        if let Some(description) = &schema.description {
-            output.push(self.render_docs(Some(description)));
+        output.push_str(&self.render_docs(schema.description.as_deref()));
+        if self.options.include_deprecated {
+            output.push_str(&self.render_deprecation(schema, None));
         }
 
-        // Start class definition
-        output.push(format!("class {}", self.escape_name(&class_name)));
-        output.push(String::new()); // Empty line after class declaration
+        let modifier = if self.options.open_structs.is_open() { "open " } else { "" };
+        output.push_str(&format!("{}class {} {{", modifier, self.type_name(name)));
 
         // Render fields as class properties
         self.depth += 1;
-        for (field_name, field) in &structure.fields {
-            // Skip hidden fields
-            if field.hidden {
-                continue;
+        let current_type = self.current_type.replace(name.to_string());
+        let properties = self.render_properties(structure);
+        self.current_type = current_type;
+        self.depth -= 1;
+        let properties = properties?;
+
+        if properties.is_empty() {
+            output.push('}');
+        } else {
+            output.push('\n');
+            output.push_str(&properties);
+            output.push_str("\n}");
+        }
+        Ok(output)
+    }
+
+    fn render_tuple_item_types(&mut self, tuple: &TupleType) -> RenderResult<Vec<String>> {
+        let mut item_types = Vec::with_capacity(tuple.items_types.len());
+        for item in &tuple.items_types {
+            let item_type = self.render_field_type(item)?;
+            item_types.push(if item.nullable { self.render_optional(&item_type) } else { item_type });
+        }
+        Ok(item_types)
+    }
+
+    /// Renders a tuple's items as positional properties (`_0`, `_1`, ...) of a class body
+    fn render_tuple_properties(&mut self, tuple: &TupleType) -> RenderResult<String> {
+        let item_types = self.render_tuple_item_types(tuple)?;
+
+        let depth = std::mem::replace(&mut self.depth, 1);
+        let properties: Vec<String> = tuple
+            .items_types
+            .iter()
+            .zip(item_types)
+            .enumerate()
+            .map(|(index, (item, item_type))| {
+                format!("{}{}_{}: {}", self.render_docs(item.description.as_deref()), self.indent(), index, item_type)
+            })
+            .collect();
+        self.depth = depth;
+        Ok(properties.join("\n\n"))
+    }
+
+    fn render_tuple_class(&self, name: &str, properties: &str, description: Option<&str>) -> String {
+        let modifier = if self.options.open_structs.is_open() { "open " } else { "" };
+        format!("{}{}class {} {{\n{}\n}}", self.render_docs(description), modifier, name, properties)
+    }
+
+    fn render_typealiases(&self) -> String {
+        self.typealiases
+            .iter()
+            .map(|(alias_name, alias_type)| {
+                let docs = self.typealias_docs.get(alias_name).map(String::as_str).unwrap_or_default();
+                format!("{}typealias {} = {}", docs, alias_name, alias_type)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Renders the body of a named, non-struct type as a typealias
+    fn render_named_typealias(&mut self, name: &str, schema: &Schema) -> RenderResult<()> {
+        let alias_name = self.type_name(name);
+        if self.typealiases.contains_key(&alias_name) {
+            return Ok(());
+        }
+        // render the definition itself, not a reference to it
+        let body = Schema { name: None, ..schema.clone() };
+        let body = if let SchemaType::Enum(enum_type) = &body.ty {
+            self.render_enum(enum_type, &body)?
+        } else {
+            self.render_field_type(&body)?
+        };
+        let mut docs = self.render_docs(schema.description.as_deref());
+        if self.options.include_deprecated {
+            docs.push_str(&self.render_deprecation(schema, None));
+        }
+        if !docs.is_empty() {
+            self.typealias_docs.insert(alias_name.clone(), docs);
+        }
+        self.typealiases.insert(alias_name, body);
+        Ok(())
+    }
+
+    /// Loads the `TypeMap` to render, leaving out [`PklSchemaOptions::exclude_properties`].
+    ///
+    /// [`SchemaRenderer::render`] does this itself; call it to use [`resolve_doc_references`](Self::resolve_doc_references)
+    /// on its own.
+    pub fn load_schemas(&mut self, schemas: TypeMap) {
+        self.schemas = schemas
+            .into_iter()
+            .filter(|(name, _)| !self.options.exclude_properties.contains(name))
+            .collect();
+        self.module_assignments.clear();
+        self.package_roots.clear();
+        self.default_values = self.options.default_values.clone();
+        for (name, value) in self.options.default_values.clone() {
+            self.collect_nested_defaults(&name, &value);
+        }
+    }
+
+    /// Adds the default instances of the structs held by `value`, the default instance of `name`, so
+    /// e.g. `VcsConfig` gets its defaults from `WorkspaceConfig`'s. The first instance found wins.
+    fn collect_nested_defaults(&mut self, name: &str, value: &Value) {
+        let Some(Schema { ty: SchemaType::Struct(structure), .. }) = self.schemas.get(name) else {
+            return;
+        };
+        let nested: Vec<(String, Value)> = structure
+            .fields
+            .iter()
+            .filter_map(|(key, field)| {
+                let value = value.get(key).filter(|value| value.is_object())?;
+                Some((self.struct_reference(&field.schema)?, value.clone()))
+            })
+            .collect();
+        for (target, value) in nested {
+            if !self.default_values.contains_key(&target) {
+                self.default_values.insert(target.clone(), value.clone());
+                self.collect_nested_defaults(&target, &value);
+            }
+        }
+    }
+
+    /// The name of the struct a field holds, directly or as the one non-null variant of an optional
+    fn struct_reference(&self, schema: &Schema) -> Option<String> {
+        match &schema.ty {
+            SchemaType::Reference(name) => {
+                matches!(self.schemas.get(name)?.ty, SchemaType::Struct(_)).then(|| name.clone())
+            }
+            SchemaType::Struct(_) => schema.name.clone().filter(|name| self.schemas.contains_key(name)),
+            SchemaType::Union(union) => {
+                let mut variants = union.variants_types.iter().filter(|variant| !matches!(variant.ty, SchemaType::Null));
+                match (variants.next(), variants.next()) {
+                    (Some(variant), None) => self.struct_reference(variant),
+                    _ => None,
+                }
             }
+            _ => None,
+        }
+    }
 
-            // Add deprecation annotation first
-            output.push(self.render_deprecation(&field.schema, Some(field)));
+    /// Returns the root type's name and the name of the module it becomes.
+    fn root_names(&self) -> (String, String) {
+        self.root_of(&self.options.config_name, &self.schemas)
+    }
 
-            // Field documentation
-            let field_description = field.comment.as_ref().or(field.schema.description.as_ref());
-            if let Some(description) = field_description {
-                output.push(self.render_docs(Some(description)));
+    /// Returns the name of the root type `config` names in `schemas`, and the name of the module it becomes.
+    fn root_of(&self, config: &LoadedConfig, schemas: &TypeMap) -> (String, String) {
+        let root_name = config.attempt_to_resolve_name(Some(schemas.clone()));
+        let module_name = match config {
+            LoadedConfig::Unknown(_) => self.to_pascal_case(&root_name),
+            config => self.to_pascal_case(&config.config_type_name(None)),
+        };
+        (root_name, module_name)
+    }
+
+    /// The root types being rendered, each with the module it becomes: the roots of a package, or the one
+    /// [`PklSchemaOptions::config_name`] names.
+    fn roots(&self) -> IndexMap<String, String> {
+        if !self.package_roots.is_empty() {
+            return self.package_roots.clone();
+        }
+        let (root_name, module_name) = self.root_names();
+        IndexMap::from([(root_name, module_name)])
+    }
+
+    /// The module shared types land in when splitting (default: `Common`).
+    fn common_module_name(&self) -> String {
+        if self.options.common_module.is_empty() {
+            "Common".to_string()
+        } else {
+            self.to_pascal_case(&self.options.common_module)
+        }
+    }
+
+    /// Whether a type is rendered into `module`; always true for a single module.
+    fn in_module(&self, name: &str, module: &str) -> bool {
+        self.module_assignments.get(name).is_none_or(|assigned| assigned == module)
+    }
+
+    /// Collects the named types a nested schema points at. A schema carrying the name of a
+    /// type in the `TypeMap` is a reference, so we stop there rather than walking its fields.
+    fn collect_references(&self, schema: &Schema, found: &mut IndexSet<String>) {
+        if let Some(name) = &schema.name
+            && self.schemas.contains_key(name)
+        {
+            found.insert(name.clone());
+            return;
+        }
+        self.collect_type_references(&schema.ty, found);
+    }
+
+    /// Walks a `SchemaType` for references. See [`PklSchemaRenderer::collect_references`].
+    fn collect_type_references(&self, ty: &SchemaType, found: &mut IndexSet<String>) {
+        match ty {
+            SchemaType::Reference(name) if self.schemas.contains_key(name) => {
+                found.insert(name.clone());
+            }
+            SchemaType::Array(array) => self.collect_references(&array.items_type, found),
+            SchemaType::Object(object) => {
+                self.collect_references(&object.key_type, found);
+                self.collect_references(&object.value_type, found);
+            }
+            SchemaType::Struct(structure) => {
+                for field in structure.fields.values() {
+                    self.collect_references(&field.schema, found);
+                }
+            }
+            SchemaType::Enum(enum_type) => {
+                for field in enum_type.variants.iter().flat_map(|variants| variants.values()) {
+                    self.collect_references(&field.schema, found);
+                }
             }
+            SchemaType::Tuple(tuple) => {
+                for item in &tuple.items_types {
+                    self.collect_references(item, found);
+                }
+            }
+            SchemaType::Union(union) => {
+                for variant in &union.variants_types {
+                    self.collect_references(variant, found);
+                }
+            }
+            _ => {}
+        }
+    }
 
-            // Determine if field should be hidden
-            let hidden_modifier = if field.hidden { "hidden " } else { "" };
+    /// Returns the names of the types a named type references directly.
+    fn references_of(&self, name: &str) -> IndexSet<String> {
+        let mut found = IndexSet::new();
+        if let Some(schema) = self.schemas.get(name) {
+            self.collect_type_references(&schema.ty, &mut found);
+        }
+        found.shift_remove(name);
+        found
+    }
 
-            // Field type declaration
-            let field_type = self.render_field_type(&field.schema)?;
-            let field_name_camel = self.to_camel_case(field_name);
-            let escaped_name = self.escape_name(&field_name_camel);
-            let optional_marker = if field.optional { "?" } else { "" };
-            let default_value = self.render_default_value(&field.schema);
+    /// Decides which module every type is rendered into.
+    ///
+    /// 1. Types listed in [`PklSchemaOptions::module_groups`] go where they're told.
+    /// 2. Each root config goes to its own module (e.g. `Project`).
+    /// 3. Everything else follows its users: if only one module reaches a type, it lives there;
+    ///    if several do (or none), it goes to the common module so nobody duplicates it.
+    fn plan_modules(&mut self) {
+        self.module_assignments.clear();
+        for (module, names) in &self.options.module_groups {
+            for name in names {
+                if self.schemas.contains_key(name) && !self.module_assignments.contains_key(name) {
+                    self.module_assignments.insert(name.clone(), self.to_pascal_case(module));
+                }
+            }
+        }
 
-            output.push(format!(
-                "{}{}{}: {}{}{}",
-                self.indent(), hidden_modifier, escaped_name, field_type, optional_marker, default_value
-            ));
-            output.push(String::new()); // Empty line between properties
+        for (root_name, root_module) in self.roots() {
+            if self.schemas.contains_key(&root_name) && !self.module_assignments.contains_key(&root_name) {
+                self.module_assignments.insert(root_name, root_module);
+            }
         }
-        self.depth -= 1;
 
-        Ok(output.join("\n"))
+        // Walk out from each anchored type. Other anchored types are module boundaries,
+        // so we don't walk through them.
+        let mut users: IndexMap<String, IndexSet<String>> = IndexMap::new();
+        for (anchor, module) in &self.module_assignments {
+            let mut queue = vec![anchor.clone()];
+            let mut seen = HashSet::new();
+            while let Some(name) = queue.pop() {
+                if !seen.insert(name.clone()) {
+                    continue;
+                }
+                for reference in self.references_of(&name) {
+                    if self.module_assignments.contains_key(&reference) {
+                        continue;
+                    }
+                    users.entry(reference.clone()).or_default().insert(module.clone());
+                    queue.push(reference);
+                }
+            }
+        }
+
+        let common_module = self.common_module_name();
+        let unassigned: Vec<String> = self
+            .schemas
+            .keys()
+            .filter(|name| !self.module_assignments.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in unassigned {
+            let module = match users.get(&name) {
+                Some(modules) if modules.len() == 1 => modules[0].clone(),
+                _ => common_module.clone(),
+            };
+            self.module_assignments.insert(name, module);
+        }
     }
 
-    fn render_typealiases(&self) -> String {
-        if self.typealiases.is_empty() {
-            return String::new();
+    /// Returns the Pkl name for a referenced type, qualified with its module (e.g. `Common.DependencyScope`)
+    /// when it lives somewhere other than the module being rendered.
+    fn qualified_type_name(&self, name: &str) -> String {
+        let type_name = self.type_name(name);
+        match (self.module_assignments.get(name), &self.current_module) {
+            // a root is its module's properties, not a class, so other modules name the module itself
+            (Some(module), Some(current)) if module != current && self.roots().get(name) == Some(module) => {
+                module.clone()
+            }
+            (Some(module), Some(current)) if module != current => format!("{}.{}", module, type_name),
+            _ => type_name,
+        }
+    }
+
+    /// Returns the `import` statements a module needs for the types it borrows from its siblings.
+    fn module_imports(&self, module: &str) -> Vec<String> {
+        let mut imports: Vec<&String> = self
+            .module_assignments
+            .iter()
+            .filter(|(_, assigned)| *assigned == module)
+            .flat_map(|(name, _)| self.references_of(name))
+            .filter_map(|reference| self.module_assignments.get(&reference))
+            .filter(|target| *target != module)
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .collect();
+        imports.sort();
+        imports.into_iter().map(|target| format!("import \"{}.pkl\"", target)).collect()
+    }
+
+    /// Renders one module: the header, the root type's properties if the root lives here, then the
+    /// classes and typealiases for the types assigned to it (every type, for a single module).
+    fn render_module(&mut self, module_name: &str) -> RenderResult {
+        self.typealiases.clear();
+        self.typealias_docs.clear();
+        self.references.clear();
+        self.tuple_classes.clear();
+        self.current_module = Some(module_name.to_string());
+
+        let root_name = self
+            .roots()
+            .into_iter()
+            .find(|(name, module)| module == module_name && self.in_module(name, module_name))
+            .map(|(name, _)| name)
+            .unwrap_or_default();
+        let root = self.schemas.get(&root_name).cloned();
+        let root_struct = match &root {
+            Some(Schema { ty: SchemaType::Struct(structure), .. }) if self.options.config_translation.as_module() => {
+                Some(structure.clone())
+            }
+            _ => None,
+        };
+
+        let mut output = vec![self.render_module_header(module_name, root.as_ref())];
+
+        // Module properties come from the root struct
+        if let Some(structure) = &root_struct {
+            self.current_type = Some(root_name.clone());
+            let properties = self.render_properties(structure);
+            self.current_type = None;
+            let properties = properties?;
+            if !properties.is_empty() {
+                output.push(properties);
+            }
+        }
+
+        // Render classes, collecting typealiases as we go
+        let schemas = self.schemas.clone();
+        for (name, schema) in &schemas {
+            if !self.in_module(name, module_name) {
+                continue;
+            }
+            match &schema.ty {
+                SchemaType::Struct(structure) => {
+                    if root_struct.is_some() && *name == root_name {
+                        continue;
+                    }
+                    output.push(self.render_as_class(name, structure, schema)?);
+                }
+                SchemaType::Tuple(tuple) if tuple.items_types.len() > 2 && self.options.tuple_translation.as_class() => {
+                    let properties = self.render_tuple_properties(tuple)?;
+                    let class_name = self.type_name(name);
+                    output.push(self.render_tuple_class(&class_name, &properties, schema.description.as_deref()));
+                }
+                _ => self.render_named_typealias(name, schema)?,
+            }
+        }
+        let tuple_classes = std::mem::take(&mut self.tuple_classes);
+        for (name, properties) in &tuple_classes {
+            output.push(self.render_tuple_class(name, properties, None));
+        }
+
+        let typealiases = self.render_typealiases();
+        if !typealiases.is_empty() {
+            output.push(typealiases);
+        }
+        self.current_module = None;
+
+        Ok(output.join("\n\n") + "\n")
+    }
+
+    /// Renders the `TypeMap` as a package of modules, keyed by file name (`Common.pkl`, `Project.pkl`...).
+    ///
+    /// With [`ModuleLayout::Single`] you get one entry with the same output as [`SchemaRenderer::render`].
+    /// With [`ModuleLayout::Split`] every module comes back with the `import`s it needs, and references
+    /// across modules are qualified (`Common.DependencyScope`), so the files can be written side by side.
+    pub fn render_modules(&mut self, schemas: TypeMap) -> RenderResult<IndexMap<String, String>> {
+        self.load_schemas(schemas);
+        let (_, root_module) = self.root_names();
+        if !self.options.module_layout.is_split() {
+            let output = self.render_module(&root_module)?;
+            return Ok(IndexMap::from([(format!("{}.pkl", root_module), output)]));
+        }
+        self.render_split()
+    }
+
+    /// Renders several root types as one package of modules, keyed by file name. Each root is named by a
+    /// [`LoadedConfig`], the way [`PklSchemaOptions::config_name`] names one, with the `TypeMap` it's found in, and
+    /// gets a module of its own. The types are planned together, so the ones roots share land in a single common
+    /// module (see [`PklSchemaOptions::module_groups`]); types with the same name are taken to be the same type.
+    /// This always splits, whatever [`PklSchemaOptions::module_layout`] says.
+    pub fn render_package(&mut self, roots: Vec<(LoadedConfig, TypeMap)>) -> RenderResult<IndexMap<String, String>> {
+        let mut schemas = TypeMap::new();
+        let mut package_roots = IndexMap::new();
+        for (config, types) in roots {
+            let (root_name, module_name) = self.root_of(&config, &types);
+            package_roots.insert(root_name, module_name);
+            for (name, schema) in types {
+                schemas.entry(name).or_insert(schema);
+            }
         }
+        self.load_schemas(schemas);
+        self.package_roots = package_roots;
+        let rendered = self.render_split();
+        self.package_roots.clear();
+        rendered
+    }
 
-        let mut output = Vec::new();
+    /// Plans the modules, then renders each of them: roots first, then the rest in the order we found them.
+    fn render_split(&mut self) -> RenderResult<IndexMap<String, String>> {
+        self.plan_modules();
+        let mut modules: IndexSet<String> = self
+            .roots()
+            .into_values()
+            .filter(|root_module| self.module_assignments.values().any(|module| module == root_module))
+            .collect();
+        modules.extend(self.module_assignments.values().cloned());
 
-        for (alias_name, alias_type) in &self.typealiases {
-            output.push(format!("typealias {} = {}", alias_name, alias_type));
+        let mut output = IndexMap::new();
+        for module in modules {
+            let rendered = self.render_module(&module)?;
+            output.insert(format!("{}.pkl", module), rendered);
         }
+        Ok(output)
+    }
+}
+
+/// Whether a link target is a Rust path (`Bar`, `Bar::field`) rather than a URL or prose
+fn is_doc_path(target: &str) -> bool {
+    !target.is_empty()
+        && target.split("::").flat_map(|segment| segment.split('.')).all(|segment| {
+            segment.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+/// Which variant of a union holds its default: `default_index` when set, otherwise the first variant
+/// that has a default of its own.
+fn union_default_index(union: &UnionType) -> Option<usize> {
+    union
+        .default_index
+        .filter(|index| *index < union.variants_types.len())
+        .or_else(|| {
+            union.variants_types.iter().position(|variant| match &variant.ty {
+                SchemaType::Union(inner) => union_default_index(inner).is_some(),
+                ty => ty.get_default().is_some(),
+            })
+        })
+}
+
+/// Check if a name is a Pkl keyword and needs escaping
+fn is_pkl_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "abstract"
+            | "amends"
+            | "as"
+            | "case"
+            | "class"
+            | "const"
+            | "default"
+            | "delete"
+            | "else"
+            | "extends"
+            | "external"
+            | "false"
+            | "fixed"
+            | "for"
+            | "function"
+            | "hidden"
+            | "if"
+            | "import"
+            | "import*"
+            | "in"
+            | "is"
+            | "let"
+            | "local"
+            | "module"
+            | "new"
+            | "nothing"
+            | "null"
+            | "open"
+            | "out"
+            | "outer"
+            | "override"
+            | "overrides"
+            | "protected"
+            | "read"
+            | "read*"
+            | "record"
+            | "super"
+            | "switch"
+            | "this"
+            | "throw"
+            | "trace"
+            | "true"
+            | "typealias"
+            | "unknown"
+            | "vararg"
+            | "when"
+    )
+}
 
-        output.push(String::new()); // Empty line after typealiases
-        output.join("\n")
+/// Escape a name if it's a keyword, or isn't a valid Pkl identifier (`foo-bar`, `1st`...)
+pub(crate) fn escape_identifier(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '$');
+    if is_pkl_keyword(name) || !is_identifier {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
     }
 }
 
+/// `new Listing { a; b }`-style object bodies, on one line
+fn render_object_body(head: &str, members: &[String]) -> String {
+    if members.is_empty() {
+        format!("{} {{}}", head)
+    } else {
+        format!("{} {{ {} }}", head, members.join("; "))
+    }
+}
+
+/// Empty (or missing) defaults need no rendering
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(entries) => entries.is_empty(),
+        _ => false,
+    }
+}
+
+fn literal_matches(literal: &LiteralValue, value: &Value) -> bool {
+    match (literal, value) {
+        (LiteralValue::Bool(expected), Value::Bool(actual)) => expected == actual,
+        (LiteralValue::String(expected), Value::String(actual)) => expected == actual,
+        (LiteralValue::Int(expected), Value::Number(actual)) => actual.as_i64() == Some(*expected as i64),
+        (LiteralValue::UInt(expected), Value::Number(actual)) => actual.as_u64() == Some(*expected as u64),
+        (LiteralValue::F32(expected), Value::Number(actual)) => actual.as_f64() == Some(*expected as f64),
+        (LiteralValue::F64(expected), Value::Number(actual)) => actual.as_f64() == Some(*expected),
+        _ => false,
+    }
+}
+
+/// Formats a float so Pkl reads it as a `Float` (`1.0`, not `1`).
+pub(crate) fn render_float(value: f64) -> String {
+    if value.fract() == 0.0 && value.is_finite() {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escapes a string for a Pkl string literal.
+pub(crate) fn escape_string(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
 impl SchemaRenderer<String> for PklSchemaRenderer {
     fn is_reference(&self, name: &str) -> bool {
         self.schemas.contains_key(name)
     }
 
-    fn render_array(&mut self, _array: &ArrayType, _schema: &Schema) -> RenderResult<String> {
-        // Arrays are handled in render_field_type
-        Ok("Listing<unknown>".to_string())
+    fn render_array(&mut self, array: &ArrayType, schema: &Schema) -> RenderResult<String> {
+        let item_type = self.render_field_type(&array.items_type)?;
+        Ok(format!("Listing<{}>{}", item_type, self.render_constraints(schema)))
     }
 
     fn render_boolean(&mut self, _boolean: &BooleanType, _schema: &Schema) -> RenderResult<String> {
@@ -1059,148 +1658,190 @@ impl SchemaRenderer<String> for PklSchemaRenderer {
         let variants: Vec<String> = enum_type
             .values
             .iter()
-            .map(|v| match v {
-                LiteralValue::String(s) => format!("\"{}\"", s),
-                LiteralValue::Integer(i) => i.to_string(),
-                LiteralValue::Float(f) => f.to_string(),
-                LiteralValue::Boolean(b) => b.to_string(),
+            .enumerate()
+            .map(|(index, value)| {
+                // If there's a default, mark the corresponding value with *
+                let marker = if enum_type.default_index == Some(index) { "*" } else { "" };
+                format!("{}{}", marker, self.render_literal_value(value))
             })
             .collect();
-        Ok(variants.join("|"))
+        Ok(variants.join(" | "))
     }
 
-    fn render_float(&mut self, _float: &FloatType, _schema: &Schema) -> RenderResult<String> {
-        Ok("Number".to_string())
+    fn render_float(&mut self, _float: &FloatType, schema: &Schema) -> RenderResult<String> {
+        Ok(format!("Number{}", self.render_constraints(schema)))
     }
 
-    fn render_integer(&mut self, _integer: &IntegerType, _schema: &Schema) -> RenderResult<String> {
-        Ok("Int".to_string())
+    fn render_integer(&mut self, integer: &IntegerType, schema: &Schema) -> RenderResult<String> {
+        // Pkl has sized integer types, which carry their own range checks
+        let type_name = match integer.kind {
+            IntegerKind::I8 => "Int8",
+            IntegerKind::I16 => "Int16",
+            IntegerKind::I32 => "Int32",
+            IntegerKind::U8 => "UInt8",
+            IntegerKind::U16 => "UInt16",
+            IntegerKind::U32 => "UInt32",
+            _ => match (integer.min, integer.max) {
+                (Some(0), Some(255)) => "UInt8",
+                (Some(0), Some(65535)) => "UInt16",
+                (Some(0), Some(4294967295)) => "UInt32",
+                (Some(-128), Some(127)) => "Int8",
+                (Some(-32768), Some(32767)) => "Int16",
+                (Some(-2147483648), Some(2147483647)) => "Int32",
+                _ => "Int",
+            },
+        };
+        Ok(format!("{}{}", type_name, self.render_constraints(schema)))
     }
 
     fn render_literal(&mut self, literal: &LiteralType, _schema: &Schema) -> RenderResult<String> {
-        match &literal.value {
-            LiteralValue::String(s) => Ok(format!("\"{}\"", s)),
-            LiteralValue::Integer(i) => Ok(i.to_string()),
-            LiteralValue::Float(f) => Ok(f.to_string()),
-            LiteralValue::Boolean(b) => Ok(b.to_string()),
-        }
+        Ok(self.render_literal_value(&literal.value))
     }
 
     fn render_null(&mut self, _schema: &Schema) -> RenderResult<String> {
-        Ok("nothing".to_string())
+        Ok("Null".to_string())
     }
 
-    fn render_object(&mut self, _object: &ObjectType, _schema: &Schema) -> RenderResult<String> {
-        // Objects are handled in render_field_type
-        Ok("Mapping<String, unknown>".to_string())
+    fn render_object(&mut self, object: &ObjectType, schema: &Schema) -> RenderResult<String> {
+        let key_type = self.render_field_type(&object.key_type)?;
+        let value_type = self.render_field_type(&object.value_type)?;
+        Ok(format!("Mapping<{}, {}>{}", key_type, value_type, self.render_constraints(schema)))
     }
 
     fn render_reference(&mut self, reference: &str, _schema: &Schema) -> RenderResult<String> {
-        Ok(self.to_pascal_case(reference))
+        let Some(target) = self.schemas.get(reference).cloned() else {
+            return Ok(self.type_name(reference));
+        };
+        // literal unions and disabled references render the target inline, unless we're already inside it
+        let inline = match &target.ty {
+            SchemaType::Struct(_) => false,
+            SchemaType::Enum(_) => self.options.disable_references || !self.options.enum_translation.use_typealias(),
+            _ => self.options.disable_references,
+        };
+        if inline && self.references.insert(reference.to_string()) {
+            let body = Schema { name: None, ..target };
+            let rendered = self.render_field_type(&body);
+            self.references.remove(reference);
+            let rendered = rendered?;
+            return Ok(if rendered.contains(" | ") { format!("({})", rendered) } else { rendered });
+        }
+        Ok(self.qualified_type_name(reference))
     }
 
-    fn render_string(&mut self, _string: &StringType, _schema: &Schema) -> RenderResult<String> {
-        Ok("String".to_string())
+    fn render_string(&mut self, string: &StringType, schema: &Schema) -> RenderResult<String> {
+        // Check for special string formats that could be Duration or DataSize, e.g. `duration` or `duration:ms`
+        let type_name = match string.format.as_deref().map(|format| format.split_once(':').unwrap_or((format, ""))) {
+            Some(("duration", unit)) if DURATION_UNITS.contains(&unit) => format!("Duration(unit == \"{}\")", unit),
+            Some(("duration", _)) => "Duration".to_string(),
+            Some(("data-size" | "datasize", unit)) if DATA_SIZE_UNITS.contains(&unit) => {
+                format!("DataSize(unit == \"{}\")", unit)
+            }
+            Some(("data-size" | "datasize", _)) => "DataSize".to_string(),
+            _ => return Ok(format!("String{}", self.render_constraints(schema))),
+        };
+        Ok(type_name)
     }
 
-    fn render_struct(&mut self, structure: &StructType, schema: &Schema) -> RenderResult<String> {
-        // For inline structs, render as anonymous type (simplified)
-        let mut fields = Vec::new();
-        for (field_name, field) in &structure.fields {
-            let field_type = self.render_field_type(&field.schema)?;
-            let field_name_camel = self.to_camel_case(field_name);
-            let escaped_name = self.escape_name(&field_name_camel);
-            let optional_marker = if field.optional { "?" } else { "" };
-            fields.push(format!(
-                "{}: {}{}",
-                escaped_name, field_type, optional_marker
-            ));
-        }
-
-        Ok(format!("{{{}}}", fields.join(", ")))
+    fn render_struct(&mut self, _structure: &StructType, _schema: &Schema) -> RenderResult<String> {
+        // Pkl has no anonymous classes; inline structs become `Dynamic`, named structs get classes
+        Ok("Dynamic".to_string())
     }
 
+    /// Pkl has no tuples: two items make a `Pair`, and longer tuples follow [`PklSchemaOptions::tuple_translation`]
     fn render_tuple(&mut self, tuple: &TupleType, _schema: &Schema) -> RenderResult<String> {
-        if tuple.items_types.len() == 2 {
+        let length = tuple.items_types.len();
+        if length == 0 {
+            return Ok("Dynamic".to_string());
+        }
+        if length == 2 {
             let first = self.render_field_type(&tuple.items_types[0])?;
             let second = self.render_field_type(&tuple.items_types[1])?;
-            Ok(format!("Pair<{}, {}>", first, second))
-        } else if tuple.items_types.len() == 1 {
-            let item_type = self.render_field_type(&tuple.items_types[0])?;
-            Ok(format!("Listing<{}>", item_type))
-        } else if tuple.items_types.len() > 2 {
-            // For more than 2 items, treat as dynamic
-            return Err(RenderError::UnsupportedSchemaType(
-                "Tuples with more than 2 items are not supported in Pkl".to_string(),
-            ));
-        } else {
-            Ok("Dynamic".to_string())
+            return Ok(format!("Pair<{}, {}>", first, second));
+        }
+        if length > 2 && self.options.tuple_translation.as_class() {
+            let properties = self.render_tuple_properties(tuple)?;
+            if let Some((name, _)) = self.tuple_classes.iter().find(|(_, existing)| **existing == properties) {
+                return Ok(name.clone());
+            }
+            let class_name = format!("Tuple{}", self.tuple_classes.len());
+            self.tuple_classes.insert(class_name.clone(), properties);
+            return Ok(class_name);
         }
-    }
 
-    fn render_union(&mut self, union: &UnionType, _schema: &Schema) -> RenderResult<String> {
-        let types: Result<Vec<_>, _> = union
-            .variants_types
+        let item_types = self.render_tuple_item_types(tuple)?;
+        // one element type for all items only needs the length checked
+        if item_types.iter().all(|item_type| *item_type == item_types[0]) {
+            return Ok(format!("Listing<{}>(length == {})", item_types[0], length));
+        }
+        let checks: Vec<String> = item_types
             .iter()
-            .map(|t| self.render_field_type(t))
+            .enumerate()
+            .map(|(index, item_type)| {
+                if item_type.contains(" | ") && !item_type.starts_with('(') {
+                    format!("this[{}] is ({})", index, item_type)
+                } else {
+                    format!("this[{}] is {}", index, item_type)
+                }
+            })
             .collect();
-        Ok(types?.join("|"))
-    }
-
-    fn render_unknown(&mut self, _schema: &Schema) -> RenderResult<String> {
-        Ok("unknown".to_string())
+        Ok(format!("Listing(length == {} && {})", length, checks.join(" && ")))
     }
 
-    fn find_root_schema(&mut self, schemas: &IndexMap<String, Schema>) -> Option<(&String, &Schema)> {
-       //
-    }
-
-    fn render(&mut self, schemas: IndexMap<String, Schema>) -> RenderResult {
-        self.schemas = schemas.clone();
-
-        let mut output = Vec::new();
-
-        // Find the root schema and render as module
-        let root_name = self
-            .options
-            .module_name
-            .as_deref()
-            .or_else(|| schemas.keys().next().map(|s| s.as_str()))
-            .unwrap_or("Config");
-
-        if let Some((_, root_schema)) = schemas.iter().next() {
-            match &root_schema.ty {
-                SchemaType::Struct(structure) => {
-                    output.push(self.render_struct_as_module(root_name, structure, root_schema)?);
-                }
-                _ => {
-                    // For non-struct roots, create a simple module with a single property
-                    let module_name = self.to_pascal_case(root_name);
-                    output.push(format!("module {}", self.escape_name(&module_name)));
-                    output.push(String::new());
-                    output.push(format!("value: {}", self.render_field_type(root_schema)?));
-                }
+    fn render_union(&mut self, union: &UnionType, _schema: &Schema) -> RenderResult<String> {
+        let mut types: Vec<String> = Vec::new();
+        let mut nullable = false;
+        let default_index = union_default_index(union);
+
+        for (index, variant) in union.variants_types.iter().enumerate() {
+            // `null` variants make the whole type nullable instead
+            if variant.ty.is_null() {
+                nullable = true;
+                continue;
             }
-        }
+            let variant_type = self.render_field_type(variant)?;
+            let variant_type = if variant_type.contains(" | ") && !variant_type.starts_with('(') {
+                format!("({})", variant_type)
+            } else {
+                variant_type
+            };
 
-        // Render nested classes
-        for (name, schema) in schemas.iter().skip(1) {
-            if let SchemaType::Struct(structure) = &schema.ty {
-                output.push(self.render_as_class(name, structure, schema)?);
+            // Mark the variant the default belongs to
+            if default_index == Some(index) && !variant_type.starts_with('*') {
+                types.push(format!("*{}", variant_type));
+            } else {
+                types.push(variant_type);
             }
         }
 
-        // Add typealiases at the beginning (after module but before classes)
-        let typealiases = self.render_typealiases();
-        if !typealiases.is_empty() {
-            // Insert typealiases after the module declaration
-            let module_end = output
-                .iter()
-                .position(|line| line.trim().is_empty())
-                .unwrap_or(1);
-            output.insert(module_end + 1, typealiases);
-        }
+        let variant_count = types.len();
+        let union_type = match variant_count {
+            0 => "Null".to_string(),
+            1 => types.remove(0).trim_start_matches('*').to_string(),
+            _ => types.join(" | "),
+        };
+
+        // If it's a complex union, consider creating a typealias
+        let final_type = if variant_count > 3 {
+            self.add_typealias("UnionType", union_type)
+        } else {
+            union_type
+        };
+
+        Ok(if nullable { self.render_optional(&final_type) } else { final_type })
+    }
 
-        Ok(output.join("\n"))
+    fn render_unknown(&mut self, _schema: &Schema) -> RenderResult<String> {
+        Ok("unknown".to_string())
+    }
+
+    /// Renders the `TypeMap` as one module.
+    ///
+    /// The root type (see [`PklSchemaOptions::config_name`]) becomes the module and its fields the module's
+    /// properties, other structs become classes, and other named types become typealiases, in the order
+    /// of the [Pkl Style Guide](https://pkl-lang.org/main/current/style-guide/index.html#module-body).
+    fn render(&mut self, schemas: IndexMap<String, Schema>) -> RenderResult {
+        self.load_schemas(schemas);
+        let (_, module_name) = self.root_names();
+        self.render_module(&module_name)
     }
 }
@@ -0,0 +1,720 @@
+//! Pkl Serializer
+//!
+//! A [`serde::Serializer`] that writes any `Serialize` value -- a `ProjectConfig`, a `WorkspaceConfig`, or
+//! anything else -- as Pkl object syntax. The output is static Pkl (PCF), so you can evaluate it as-is,
+//! or point it at a schema with [`PklSerializerOptions::amends`] and have Pkl type-check it.
+//!
+//! ## How things map
+//!
+//! - Structs become properties (`name = value`), with names that aren't identifiers in backticks.
+//! - Maps become entries (`["key"] = value`), except at the top level, where string keys are properties.
+//! - Nested structs and maps are amended in place (`foo { ... }`), and sequences are listings
+//!   (`tags { "a" "b" }`), one element per line. Objects inside a listing are `new { ... }`.
+//! - Empty sequences and maps replace the value instead (`tags = new Listing {}`), so no default elements survive.
+//! - `None` struct fields are left out, so the schema's default applies. Anywhere else, they're `null`.
+//! - Enum unit variants are strings; other variants are externally tagged (`Variant { ... }`).
+//!
+//! ```rust,ignore
+//! use space_pklr::pkl_serializer::{PklSerializerOptions, to_pkl_string_with_options};
+//!
+//! let options = PklSerializerOptions {
+//!     amends: Some("package://example.com/moon@1.0.0#/Project.pkl".to_string()),
+//!     ..Default::default()
+//! };
+//! let pkl = to_pkl_string_with_options(&ProjectConfig::default(), &options)?;
+//! ```
+
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+
+use crate::pkl_renderer::{escape_identifier, escape_string, render_float};
+use crate::types::{CliError, Result};
+
+/// A Pkl value, as the serializer sees it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PklValue {
+    Null,
+    Boolean(bool),
+    Int(i128),
+    Float(f64),
+    String(String),
+    /// Elements of a `Listing`
+    Listing(Vec<PklValue>),
+    /// Entries of a `Mapping`
+    Mapping(Vec<(PklValue, PklValue)>),
+    /// Properties of an object
+    Object(Vec<(String, PklValue)>),
+}
+
+impl PklValue {
+    fn is_container(&self) -> bool {
+        matches!(self, PklValue::Listing(_) | PklValue::Mapping(_) | PklValue::Object(_))
+    }
+}
+
+/// Lets a [`PklValue`] be written in other formats too: listings are sequences, and objects and mappings are both maps.
+impl Serialize for PklValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use ser::{SerializeMap as _, SerializeSeq as _};
+
+        match self {
+            PklValue::Null => serializer.serialize_none(),
+            PklValue::Boolean(boolean) => serializer.serialize_bool(*boolean),
+            // not every format takes 128-bit integers
+            PklValue::Int(int) => match (i64::try_from(*int), u64::try_from(*int)) {
+                (Ok(int), _) => serializer.serialize_i64(int),
+                (_, Ok(int)) => serializer.serialize_u64(int),
+                _ => serializer.serialize_i128(*int),
+            },
+            PklValue::Float(float) => serializer.serialize_f64(*float),
+            PklValue::String(string) => serializer.serialize_str(string),
+            PklValue::Listing(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            PklValue::Mapping(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            PklValue::Object(properties) => {
+                let mut map = serializer.serialize_map(Some(properties.len()))?;
+                for (name, value) in properties {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Options for [`to_pkl_string_with_options`].
+#[derive(Debug, Clone)]
+pub struct PklSerializerOptions {
+    /// Writes `amends "<path>"` first, so Pkl checks the values against that module (a schema, usually)
+    pub amends: Option<String>,
+    /// Indentation string (default: 2 spaces)
+    pub indent: String,
+}
+
+impl Default for PklSerializerOptions {
+    fn default() -> Self {
+        Self {
+            amends: None,
+            indent: "  ".to_string(),
+        }
+    }
+}
+
+/// Serializes a value as a Pkl module.
+pub fn to_pkl_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    to_pkl_string_with_options(value, &PklSerializerOptions::default())
+}
+
+/// Serializes a value as a Pkl module, with options.
+pub fn to_pkl_string_with_options<T: Serialize + ?Sized>(value: &T, options: &PklSerializerOptions) -> Result<String> {
+    pkl_value_to_string(to_pkl_value(value)?, options)
+}
+
+/// Writes a [`PklValue`] tree as a Pkl module, with options.
+pub fn pkl_value_to_string(value: PklValue, options: &PklSerializerOptions) -> Result<String> {
+    let properties = match value {
+        PklValue::Object(properties) => properties,
+        // a module can't hold entries, so top-level maps need string keys to become properties
+        PklValue::Mapping(entries) => entries
+            .into_iter()
+            .map(|(key, value)| match key {
+                PklValue::String(key) => Ok((key, value)),
+                key => Err(CliError::PklSerializeError {
+                    reason: format!("top-level key {} isn't a string", render_scalar(&key)),
+                }),
+            })
+            .collect::<Result<Vec<_>>>()?,
+        other => {
+            return Err(CliError::PklSerializeError {
+                reason: format!("a module needs a struct or map, not {}", describe(&other)),
+            });
+        }
+    };
+
+    let mut output = String::new();
+    if let Some(amends) = &options.amends {
+        output.push_str(&format!("amends \"{}\"\n\n", escape_string(amends)));
+    }
+    let writer = PklWriter { indent: &options.indent };
+    writer.write_members(&PklValue::Object(properties), 0, &mut output);
+    Ok(output)
+}
+
+/// Serializes a value into a [`PklValue`] tree.
+pub fn to_pkl_value<T: Serialize + ?Sized>(value: &T) -> Result<PklValue> {
+    value.serialize(PklValueSerializer)
+}
+
+struct PklWriter<'a> {
+    indent: &'a str,
+}
+
+impl PklWriter<'_> {
+    /// Writes the members of a container, one per line, at `depth`
+    fn write_members(&self, container: &PklValue, depth: usize, output: &mut String) {
+        match container {
+            PklValue::Object(properties) => {
+                for (name, value) in properties {
+                    self.write_member(&escape_identifier(name), value, depth, output);
+                }
+            }
+            PklValue::Mapping(entries) => {
+                for (key, value) in entries {
+                    self.write_member(&format!("[{}]", render_scalar(key)), value, depth, output);
+                }
+            }
+            PklValue::Listing(elements) => {
+                for element in elements {
+                    output.push_str(&self.indent.repeat(depth));
+                    if let Some(empty) = empty_collection(element) {
+                        output.push_str(empty);
+                    } else if element.is_container() {
+                        output.push_str("new ");
+                        self.write_body(element, depth, output);
+                    } else {
+                        output.push_str(&render_scalar(element));
+                    }
+                    output.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `head = value` for scalars, `head { ... }` for containers
+    fn write_member(&self, head: &str, value: &PklValue, depth: usize, output: &mut String) {
+        output.push_str(&self.indent.repeat(depth));
+        output.push_str(head);
+        if let Some(empty) = empty_collection(value) {
+            output.push_str(" = ");
+            output.push_str(empty);
+        } else if value.is_container() {
+            output.push(' ');
+            self.write_body(value, depth, output);
+        } else {
+            output.push_str(" = ");
+            output.push_str(&render_scalar(value));
+        }
+        output.push('\n');
+    }
+
+    fn write_body(&self, container: &PklValue, depth: usize, output: &mut String) {
+        let is_empty = match container {
+            PklValue::Listing(elements) => elements.is_empty(),
+            PklValue::Mapping(entries) => entries.is_empty(),
+            PklValue::Object(properties) => properties.is_empty(),
+            _ => true,
+        };
+        if is_empty {
+            output.push_str("{}");
+            return;
+        }
+        output.push_str("{\n");
+        self.write_members(container, depth + 1, output);
+        output.push_str(&self.indent.repeat(depth));
+        output.push('}');
+    }
+}
+
+/// Empty listings and mappings are replaced rather than amended, so they don't keep a default's contents
+/// (and so a reader can tell them from an empty object).
+fn empty_collection(value: &PklValue) -> Option<&'static str> {
+    match value {
+        PklValue::Listing(elements) if elements.is_empty() => Some("new Listing {}"),
+        PklValue::Mapping(entries) if entries.is_empty() => Some("new Mapping {}"),
+        _ => None,
+    }
+}
+
+fn render_scalar(value: &PklValue) -> String {
+    match value {
+        PklValue::Null => "null".to_string(),
+        PklValue::Boolean(boolean) => boolean.to_string(),
+        PklValue::Int(int) => int.to_string(),
+        PklValue::Float(float) if float.is_nan() => "NaN".to_string(),
+        PklValue::Float(float) if float.is_infinite() => {
+            if float.is_sign_negative() { "-Infinity" } else { "Infinity" }.to_string()
+        }
+        PklValue::Float(float) => render_float(*float),
+        PklValue::String(string) => format!("\"{}\"", escape_string(string)),
+        container => describe(container).to_string(),
+    }
+}
+
+pub(crate) fn describe(value: &PklValue) -> &'static str {
+    match value {
+        PklValue::Null => "null",
+        PklValue::Boolean(_) => "a boolean",
+        PklValue::Int(_) => "an integer",
+        PklValue::Float(_) => "a float",
+        PklValue::String(_) => "a string",
+        PklValue::Listing(_) => "a listing",
+        PklValue::Mapping(_) => "a mapping",
+        PklValue::Object(_) => "an object",
+    }
+}
+
+/// Serializes into a [`PklValue`].
+struct PklValueSerializer;
+
+impl ser::Serializer for PklValueSerializer {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    type SerializeSeq = SerializeListing;
+    type SerializeTuple = SerializeListing;
+    type SerializeTupleStruct = SerializeListing;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeMapping;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, v: bool) -> Result<PklValue> {
+        Ok(PklValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<PklValue> {
+        Ok(PklValue::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<PklValue> {
+        Ok(PklValue::Int(v.into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<PklValue> {
+        i128::try_from(v).map(PklValue::Int).map_err(|_| CliError::PklSerializeError {
+            reason: format!("{v} is too large for a Pkl Int"),
+        })
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<PklValue> {
+        Ok(PklValue::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<PklValue> {
+        Ok(PklValue::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<PklValue> {
+        Ok(PklValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<PklValue> {
+        Ok(PklValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<PklValue> {
+        Ok(PklValue::Listing(v.iter().map(|byte| PklValue::Int((*byte).into())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<PklValue> {
+        Ok(PklValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<PklValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<PklValue> {
+        Ok(PklValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<PklValue> {
+        Ok(PklValue::Null)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<PklValue> {
+        Ok(PklValue::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<PklValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<PklValue> {
+        Ok(PklValue::Object(vec![(variant.to_string(), value.serialize(self)?)]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeListing> {
+        Ok(SerializeListing(Vec::with_capacity(len.unwrap_or_default())))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeListing> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeListing> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTupleVariant> {
+        Ok(SerializeTupleVariant {
+            variant,
+            elements: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMapping> {
+        Ok(SerializeMapping {
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject> {
+        Ok(SerializeObject(Vec::with_capacity(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructVariant> {
+        Ok(SerializeStructVariant {
+            variant,
+            properties: SerializeObject(Vec::with_capacity(len)),
+        })
+    }
+}
+
+struct SerializeListing(Vec<PklValue>);
+
+impl ser::SerializeSeq for SerializeListing {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.0.push(value.serialize(PklValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<PklValue> {
+        Ok(PklValue::Listing(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SerializeListing {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<PklValue> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeListing {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<PklValue> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeTupleVariant {
+    variant: &'static str,
+    elements: Vec<PklValue>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.elements.push(value.serialize(PklValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<PklValue> {
+        Ok(PklValue::Object(vec![(self.variant.to_string(), PklValue::Listing(self.elements))]))
+    }
+}
+
+struct SerializeMapping {
+    entries: Vec<(PklValue, PklValue)>,
+    key: Option<PklValue>,
+}
+
+impl ser::SerializeMap for SerializeMapping {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(MappingKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or_else(|| CliError::PklSerializeError {
+            reason: "a map value came without a key".to_string(),
+        })?;
+        self.entries.push((key, value.serialize(PklValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<PklValue> {
+        Ok(PklValue::Mapping(self.entries))
+    }
+}
+
+struct SerializeObject(Vec<(String, PklValue)>);
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        let value = value.serialize(PklValueSerializer)?;
+        // leave the property out, so the schema's default applies
+        if value != PklValue::Null {
+            self.0.push((key.to_string(), value));
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<PklValue> {
+        Ok(PklValue::Object(self.0))
+    }
+}
+
+struct SerializeStructVariant {
+    variant: &'static str,
+    properties: SerializeObject,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        ser::SerializeStruct::serialize_field(&mut self.properties, key, value)
+    }
+
+    fn end(self) -> Result<PklValue> {
+        let properties = ser::SerializeStruct::end(self.properties)?;
+        Ok(PklValue::Object(vec![(self.variant.to_string(), properties)]))
+    }
+}
+
+/// Mapping keys have to be scalars; anything else is an error.
+struct MappingKeySerializer;
+
+impl MappingKeySerializer {
+    fn unsupported(kind: &str) -> CliError {
+        CliError::PklSerializeError {
+            reason: format!("mapping keys must be strings, numbers or booleans, not {kind}"),
+        }
+    }
+}
+
+impl ser::Serializer for MappingKeySerializer {
+    type Ok = PklValue;
+    type Error = CliError;
+
+    type SerializeSeq = Impossible<PklValue, CliError>;
+    type SerializeTuple = Impossible<PklValue, CliError>;
+    type SerializeTupleStruct = Impossible<PklValue, CliError>;
+    type SerializeTupleVariant = Impossible<PklValue, CliError>;
+    type SerializeMap = Impossible<PklValue, CliError>;
+    type SerializeStruct = Impossible<PklValue, CliError>;
+    type SerializeStructVariant = Impossible<PklValue, CliError>;
+
+    fn serialize_bool(self, v: bool) -> Result<PklValue> {
+        PklValueSerializer.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<PklValue> {
+        PklValueSerializer.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<PklValue> {
+        PklValueSerializer.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<PklValue> {
+        PklValueSerializer.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<PklValue> {
+        PklValueSerializer.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<PklValue> {
+        PklValueSerializer.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<PklValue> {
+        PklValueSerializer.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<PklValue> {
+        PklValueSerializer.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<PklValue> {
+        PklValueSerializer.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<PklValue> {
+        PklValueSerializer.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<PklValue> {
+        PklValueSerializer.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<PklValue> {
+        PklValueSerializer.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<PklValue> {
+        PklValueSerializer.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<PklValue> {
+        PklValueSerializer.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<PklValue> {
+        PklValueSerializer.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<PklValue> {
+        Err(Self::unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<PklValue> {
+        Err(Self::unsupported("null"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<PklValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<PklValue> {
+        Err(Self::unsupported("null"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<PklValue> {
+        Err(Self::unsupported("null"))
+    }
+
+    fn serialize_unit_variant(self, name: &'static str, index: u32, variant: &'static str) -> Result<PklValue> {
+        PklValueSerializer.serialize_unit_variant(name, index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<PklValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<PklValue> {
+        Err(Self::unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Self::unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Self::unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(Self::unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Self::unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Self::unsupported("a map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Self::unsupported("a struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Self::unsupported("an enum variant with data"))
+    }
+}
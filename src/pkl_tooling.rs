@@ -4,7 +4,8 @@
 //! for consistent toolchain management.
 
 use miette::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Pkl CLI representation.
 #[derive(Debug, Clone)]
@@ -25,13 +26,45 @@ pub enum PklSource {
     Manual(PathBuf),
 }
 
+/// Options for installing Pkl CLI
+#[derive(Debug, Clone, Default)]
+pub struct PklInstallOptions {
+    /// SHA-256 of the release archive, for versions and platforms the checksum manifest doesn't list
+    pub checksum: Option<String>,
+    /// Base URL to download release archives from instead of GitHub, laid out as `<mirror>/<version>/<archive>`
+    pub mirror: Option<String>,
+    /// A release archive already on disk, installed instead of downloading one
+    pub from_archive: Option<PathBuf>,
+    /// How downloads are retried
+    pub retry: RetryPolicy,
+}
+
 /// Install Pkl CLI with proto-first approach
 ///
 /// Implements proto-first installation strategy with fallbacks as specified in
-pub async fn install_pkl(version: Option<String>) -> Result<PklCli> {
+pub async fn install_pkl(version: Option<String>, options: &PklInstallOptions) -> Result<PklCli> {
+    use crate::pkl_versions::{PklVersions, VersionRequest};
     use crate::types::CliError;
 
-    let target_version = version.unwrap_or_else(|| get_recommended_pkl_version().to_string());
+    // a version, a range or `latest-compatible`
+    let request: VersionRequest = version.as_deref().unwrap_or(get_recommended_pkl_version()).parse()?;
+    let installed = PklVersions::open()
+        .and_then(|versions| versions.installed())
+        .unwrap_or_default();
+    let target_version = request
+        .install_version(installed.iter().map(|pkl| pkl.version.as_str()))
+        .ok_or_else(|| CliError::UnresolvedPklVersion {
+            request: request.to_string(),
+            tested: get_compatible_pkl_versions().into_iter().map(str::to_string).collect(),
+        })?;
+    if target_version != request.to_string() {
+        println!("🔎 Resolved Pkl version {} to {}", request, target_version);
+    }
+
+    // A local archive is exactly what's wanted; nothing else is tried
+    if options.from_archive.is_some() {
+        return install_pkl_archive(&target_version, options).await;
+    }
 
     // 1. Try proto installation first
     if is_proto_available().await {
@@ -52,32 +85,29 @@ pub async fn install_pkl(version: Option<String>) -> Result<PklCli> {
     }
 
     // 2. Check system PATH as fallback
-    if let Ok(Some(existing_pkl)) = find_pkl_executable().await {
-        if let Some(existing_version) = &existing_pkl.version {
-            if existing_version == &target_version {
-                println!("✅ Found compatible Pkl CLI in system PATH");
-                return Ok(existing_pkl);
-            } else {
-                println!(
-                    "⚠️  Found Pkl CLI version {}, but need version {}",
-                    existing_version, target_version
-                );
-            }
+    if let Ok(Some(existing_pkl)) = find_pkl_executable().await
+        && let Some(existing_version) = &existing_pkl.version
+    {
+        if request.installs(existing_version) {
+            println!("✅ Found compatible Pkl CLI in system PATH");
+            return Ok(existing_pkl);
+        } else {
+            println!(
+                "⚠️  Found Pkl CLI version {}, but need version {}",
+                existing_version, request
+            );
         }
     }
 
     // 3. Direct download as last resort
     println!("📥 Downloading Pkl CLI {} directly...", target_version);
-    match download_pkl_binary(&target_version).await {
-        Ok(pkl_path) => {
-            let pkl_cli = PklCli {
-                path: pkl_path,
-                source: PklSource::Manual(get_pkl_install_dir(&target_version)?),
-                version: Some(target_version),
-            };
+    match install_pkl_archive(&target_version, options).await {
+        Ok(pkl_cli) => {
             println!("✅ Successfully downloaded and installed Pkl CLI");
             Ok(pkl_cli)
         }
+        // a bad download is reported as such, not as one more failed method
+        Err(e) if matches!(e.downcast_ref(), Some(CliError::ChecksumMismatch { .. })) => Err(e),
         Err(e) => Err(miette::Report::new(CliError::PklInstallFailed {
             reason: format!("All installation methods failed. Last error: {}", e),
             help: Some(
@@ -89,55 +119,97 @@ pub async fn install_pkl(version: Option<String>) -> Result<PklCli> {
 
 /// Find existing Pkl executable
 ///
-/// Searches for Pkl CLI in order of preference: proto -> system PATH -> manual installations
+/// Finds the Pkl CLI for the current directory; see [`find_pkl_executable_in`].
 pub async fn find_pkl_executable() -> Result<Option<PklCli>> {
     use crate::types::CliError;
 
-    // 1. Check proto-managed Pkl first
-    if is_proto_available().await {
-        if let Ok(pkl_cli) = check_proto_pkl().await {
-            return Ok(Some(pkl_cli));
-        }
+    let current_dir = std::env::current_dir().map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: "Reading the current directory".to_string(),
+            source: e,
+        })
+    })?;
+    find_pkl_executable_in(&current_dir).await
+}
+
+/// Find the Pkl executable to use in `dir`
+///
+/// When a version is pinned for `dir` (by `.pkl-version` or `.prototools`), or else a default is set with
+/// `spklr pkl-me use`, only versions it accepts are: the newest such spklr-installed one, then proto's or the
+/// system PATH's. Otherwise the search is proto -> system PATH -> the newest spklr-installed one. Versions
+/// outside the compatibility matrix are used, with a warning.
+pub async fn find_pkl_executable_in(dir: &Path) -> Result<Option<PklCli>> {
+    let pkl_cli = find_requested_pkl(dir).await?;
+    if let Some(warning) = pkl_cli
+        .as_ref()
+        .and_then(|pkl_cli| pkl_cli.version.as_deref())
+        .and_then(untested_pkl_version_warning)
+    {
+        tracing::warn!("{}", warning);
     }
+    Ok(pkl_cli)
+}
 
-    // 2. Check system PATH
-    if let Ok(pkl_path) = which::which("pkl") {
-        if let Ok(version) = get_pkl_version(&pkl_path).await {
-            return Ok(Some(PklCli {
-                path: pkl_path,
-                source: PklSource::SystemPath,
-                version: Some(version),
-            }));
-        }
+async fn find_requested_pkl(dir: &Path) -> Result<Option<PklCli>> {
+    use crate::pkl_versions::{PklVersions, find_pinned_version};
+
+    let versions = PklVersions::open().ok();
+    let requested = match &versions {
+        Some(versions) => versions.requested_version(dir)?,
+        None => find_pinned_version(dir)?,
+    };
+    let wanted = requested.as_ref().map(|requested| requested.request()).transpose()?;
+    let accepts = |pkl_cli: &PklCli| match &wanted {
+        Some(wanted) => pkl_cli.version.as_deref().is_some_and(|version| wanted.matches(version)),
+        None => true,
+    };
+
+    // 1. The newest installed version the request accepts, if spklr installed it
+    if let Some(wanted) = &wanted
+        && let Some(installed) = versions.as_ref().and_then(|versions| versions.find(wanted).ok().flatten())
+    {
+        return Ok(Some(PklCli {
+            path: installed.path,
+            source: PklSource::Manual(installed.dir),
+            version: Some(installed.version),
+        }));
     }
 
-    // 3. Check manual installation locations
-    if let Ok(home_dir) = dirs::home_dir()
-        .ok_or_else(|| CliError::Generic("Could not find home directory".to_string()))
+    // 2. Check proto-managed Pkl
+    if is_proto_available().await
+        && let Ok(pkl_cli) = check_proto_pkl().await
+        && accepts(&pkl_cli)
     {
-        let pkl_tools_dir = home_dir.join(".moon").join("tools").join("pkl");
-
-        if pkl_tools_dir.exists() {
-            // Look for any version directory
-            if let Ok(entries) = std::fs::read_dir(&pkl_tools_dir) {
-                for entry in entries.flatten() {
-                    if entry.file_type().map_or(false, |ft| ft.is_dir()) {
-                        let pkl_path = entry.path().join("pkl");
-                        if pkl_path.exists() {
-                            if let Ok(version) = get_pkl_version(&pkl_path).await {
-                                return Ok(Some(PklCli {
-                                    path: pkl_path,
-                                    source: PklSource::Manual(entry.path()),
-                                    version: Some(version),
-                                }));
-                            }
-                        }
-                    }
-                }
-            }
+        return Ok(Some(pkl_cli));
+    }
+
+    // 3. Check system PATH
+    if let Ok(pkl_path) = which::which("pkl")
+        && let Ok(version) = get_pkl_version(&pkl_path).await
+    {
+        let pkl_cli = PklCli {
+            path: pkl_path,
+            source: PklSource::SystemPath,
+            version: Some(version),
+        };
+        if accepts(&pkl_cli) {
+            return Ok(Some(pkl_cli));
         }
     }
 
+    // 4. Otherwise the newest manual installation
+    if wanted.is_none()
+        && let Some(installed) = versions
+            .and_then(|versions| versions.installed().ok())
+            .and_then(|installed| installed.into_iter().next())
+    {
+        return Ok(Some(PklCli {
+            path: installed.path,
+            source: PklSource::Manual(installed.dir),
+            version: Some(installed.version),
+        }));
+    }
+
     Ok(None)
 }
 
@@ -147,17 +219,20 @@ async fn install_via_proto(version: &str) -> Result<PklCli> {
     use std::process::Command;
 
     let mut cmd = Command::new("proto");
-    cmd.args(&["install", &format!("pkl@{}", version)]);
+    cmd.args(["install", &format!("pkl@{}", version)]);
 
-    let output = cmd.output().map_err(|e| CliError::PklInstallFailed {
-        reason: format!("Failed to execute proto install: {}", e),
-        help: Some("Check that proto is properly installed".to_string()),
-    })?;
+    // installing downloads Pkl, so it gets longer than other commands
+    let timeout = configured_timeout(PROTO_INSTALL_TIMEOUT);
+    let output = run_process(cmd, timeout, |_, line| tracing::info!(target: "proto", "{}", line))
+        .await
+        .map_err(|e| CliError::PklInstallFailed {
+            reason: format!("Failed to execute proto install: {}", e),
+            help: Some("Check that proto is properly installed".to_string()),
+        })?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(miette::Report::new(CliError::PklInstallFailed {
-            reason: format!("Proto install failed: {}", stderr),
+            reason: format!("Proto install failed: {}", output.stderr),
             help: Some("Try running the proto command manually to diagnose the issue".to_string()),
         }));
     }
@@ -172,16 +247,17 @@ async fn check_proto_pkl() -> Result<PklCli> {
     use std::process::Command;
 
     let mut cmd = Command::new("proto");
-    cmd.args(&["run", "pkl", "--", "--version"]);
+    cmd.args(["run", "pkl", "--", "--version"]);
 
-    let output = cmd.output().map_err(|e| CliError::PklInstallFailed {
-        reason: format!("Failed to check proto-managed Pkl: {}", e),
-        help: Some("Check that proto and Pkl are properly installed".to_string()),
-    })?;
+    let output = run_process(cmd, configured_timeout(DEFAULT_PROCESS_TIMEOUT), |_, _| {})
+        .await
+        .map_err(|e| CliError::PklInstallFailed {
+            reason: format!("Failed to check proto-managed Pkl: {}", e),
+            help: Some("Check that proto and Pkl are properly installed".to_string()),
+        })?;
 
     if output.status.success() {
-        let version_output = String::from_utf8_lossy(&output.stdout);
-        let version = parse_pkl_version(&version_output);
+        let version = parse_pkl_version(&output.stdout);
 
         Ok(PklCli {
             path: PathBuf::from("pkl"), // Proto manages the path
@@ -200,16 +276,16 @@ async fn check_proto_pkl() -> Result<PklCli> {
 async fn get_pkl_version(pkl_path: &PathBuf) -> Result<String> {
     use std::process::Command;
 
-    let output = Command::new(pkl_path)
-        .arg("--version")
-        .output()
+    let mut cmd = Command::new(pkl_path);
+    cmd.arg("--version");
+    let output = run_process(cmd, configured_timeout(DEFAULT_PROCESS_TIMEOUT), |_, _| {})
+        .await
         .map_err(|e| {
             crate::types::CliError::Generic(format!("Failed to get Pkl version: {}", e))
         })?;
 
     if output.status.success() {
-        let version_output = String::from_utf8_lossy(&output.stdout);
-        parse_pkl_version(&version_output).ok_or_else(|| {
+        parse_pkl_version(&output.stdout).ok_or_else(|| {
             miette::Report::new(crate::types::CliError::Generic(
                 "Could not parse Pkl version output".to_string(),
             ))
@@ -224,130 +300,160 @@ async fn get_pkl_version(pkl_path: &PathBuf) -> Result<String> {
 /// Parse version string from Pkl --version output
 fn parse_pkl_version(output: &str) -> Option<String> {
     // Look for version pattern like "Pkl 0.26.0"
+    let pattern = regex::Regex::new(r"Pkl\s+(\d+\.\d+\.\d+)").ok()?;
     for line in output.lines() {
-        if let Some(captures) = regex::Regex::new(r"Pkl\s+(\d+\.\d+\.\d+)")
-            .ok()?
-            .captures(line)
-        {
+        if let Some(captures) = pattern.captures(line) {
             return captures.get(1).map(|m| m.as_str().to_string());
         }
     }
     None
 }
 
-/// Extract ZIP archive (Windows)
-#[cfg(target_os = "windows")]
-async fn extract_zip_archive(archive_bytes: &[u8], target_dir: &PathBuf) -> Result<PathBuf> {
+/// Unpack a Pkl release archive into `target_dir` and return the `pkl` executable inside it. `.zip` archives
+/// and `.tar.gz`/`.tgz` ones are read in-process, whatever the host.
+pub fn extract_archive(archive_bytes: &[u8], archive_name: &str, target_dir: &Path) -> Result<PathBuf> {
+    if archive_name.ends_with(".zip") {
+        extract_zip_archive(archive_bytes, target_dir)
+    } else if archive_name.ends_with(".tar.gz") || archive_name.ends_with(".tgz") {
+        extract_tar_gz_archive(archive_bytes, target_dir)
+    } else {
+        Err(miette::Report::new(crate::types::CliError::UnsupportedFormat {
+            format: archive_name.to_string(),
+            available: vec!["zip", "tar.gz", "tgz"],
+        }))
+    }
+}
+
+/// Extract a ZIP archive into `target_dir`, returning the `pkl` executable inside it
+pub fn extract_zip_archive(archive_bytes: &[u8], target_dir: &Path) -> Result<PathBuf> {
     use crate::types::CliError;
 
-    // For simplicity in this implementation, we'll use a basic approach
-    // In production, you'd want to use a proper ZIP library like `zip`
-    let archive_path = target_dir.join("pkl-cli.zip");
-    tokio::fs::write(&archive_path, archive_bytes)
-        .await
-        .map_err(|e| {
-            miette::Report::new(CliError::IoError {
-                context: "Writing ZIP archive".to_string(),
-                source: e,
-            })
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive_bytes))
+        .map_err(|e| CliError::Generic(format!("Failed to read ZIP archive: {}", e)))?;
+
+    for index in 0..archive.len() {
+        let mut file = archive
+            .by_index(index)
+            .map_err(|e| CliError::Generic(format!("Failed to read ZIP archive: {}", e)))?;
+        let Some(relative) = file.enclosed_name() else {
+            return Err(unsafe_entry(file.name(), target_dir));
+        };
+        let destination = target_dir.join(relative);
+
+        if file.is_dir() {
+            create_dir(&destination)?;
+            continue;
+        }
+        if let Some(parent) = destination.parent() {
+            create_dir(parent)?;
+        }
+        let mut output = std::fs::File::create(&destination).map_err(|e| CliError::IoError {
+            context: format!("Creating {}", destination.display()),
+            source: e,
         })?;
-
-    // Use system unzip command as fallback
-    let output = std::process::Command::new("powershell")
-        .args(&[
-            "-Command",
-            &format!(
-                "Expand-Archive -Path '{}' -DestinationPath '{}' -Force",
-                archive_path.display(),
-                target_dir.display()
-            ),
-        ])
-        .output()
-        .map_err(|e| {
-            miette::Report::new(CliError::Generic(format!("Failed to extract ZIP: {}", e)))
+        std::io::copy(&mut file, &mut output).map_err(|e| CliError::IoError {
+            context: format!("Extracting {}", destination.display()),
+            source: e,
         })?;
 
-    if !output.status.success() {
-        return Err(miette::Report::new(CliError::Generic(
-            "ZIP extraction failed".to_string(),
-        )));
+        #[cfg(unix)]
+        if let Some(mode) = file.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&destination, std::fs::Permissions::from_mode(mode)).map_err(|e| {
+                CliError::IoError {
+                    context: "Setting file permissions".to_string(),
+                    source: e,
+                }
+            })?;
+        }
     }
 
-    // Clean up archive file
-    let _ = tokio::fs::remove_file(&archive_path).await;
-
-    // Find the pkl executable
-    Ok(target_dir.join("pkl.exe"))
+    find_pkl_in(target_dir)
 }
 
-/// Extract ZIP archive (Non-Windows fallback)
-#[cfg(not(target_os = "windows"))]
-async fn extract_zip_archive(_archive_bytes: &[u8], _target_dir: &PathBuf) -> Result<PathBuf> {
-    Err(miette::Report::new(crate::types::CliError::Generic(
-        "ZIP extraction not implemented for this platform".to_string(),
-    )))
-}
-
-/// Extract tar.gz archive (Unix-like systems)
-#[cfg(not(target_os = "windows"))]
-async fn extract_tar_gz_archive(archive_bytes: &[u8], target_dir: &PathBuf) -> Result<PathBuf> {
+/// Extract a tar.gz archive into `target_dir`, returning the `pkl` executable inside it. Only files and
+/// directories are extracted; links and the like are skipped.
+pub fn extract_tar_gz_archive(archive_bytes: &[u8], target_dir: &Path) -> Result<PathBuf> {
     use crate::types::CliError;
+    use std::path::Component;
 
-    let archive_path = target_dir.join("pkl-cli.tar.gz");
-    tokio::fs::write(&archive_path, archive_bytes)
-        .await
-        .map_err(|e| {
-            miette::Report::new(CliError::IoError {
-                context: "Writing tar.gz archive".to_string(),
-                source: e,
-            })
-        })?;
-
-    // Use system tar command
-    let output = std::process::Command::new("tar")
-        .args(&[
-            "-xzf",
-            &archive_path.to_string_lossy(),
-            "-C",
-            &target_dir.to_string_lossy(),
-        ])
-        .output()
-        .map_err(|e| {
-            miette::Report::new(CliError::Generic(format!(
-                "Failed to extract tar.gz: {}",
-                e
-            )))
-        })?;
+    let read_error = |e: std::io::Error| CliError::IoError {
+        context: "Reading tar.gz archive".to_string(),
+        source: e,
+    };
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive_bytes));
+
+    for entry in archive.entries().map_err(read_error)? {
+        let mut entry = entry.map_err(read_error)?;
+        let path = entry.path().map_err(read_error)?.into_owned();
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(unsafe_entry(&path.display().to_string(), target_dir));
+        }
 
-    if !output.status.success() {
-        return Err(miette::Report::new(CliError::Generic(
-            "tar.gz extraction failed".to_string(),
-        )));
+        let destination = target_dir.join(&path);
+        match entry.header().entry_type() {
+            tar::EntryType::Directory => create_dir(&destination)?,
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                if let Some(parent) = destination.parent() {
+                    create_dir(parent)?;
+                }
+                entry.unpack(&destination).map_err(|e| CliError::IoError {
+                    context: format!("Extracting {}", destination.display()),
+                    source: e,
+                })?;
+            }
+            other => tracing::debug!("Skipping {:?} entry {} in the Pkl archive", other, path.display()),
+        }
     }
 
-    // Clean up archive file
-    let _ = tokio::fs::remove_file(&archive_path).await;
+    find_pkl_in(target_dir)
+}
+
+/// Find the `pkl` executable under `dir`, however deeply the archive nested it; the shallowest wins
+pub fn find_pkl_in(dir: &Path) -> Result<PathBuf> {
+    let executable = if cfg!(windows) { "pkl.exe" } else { "pkl" };
+    walkdir::WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| matches!(entry.file_name().to_str(), Some("pkl" | "pkl.exe")))
+        .min_by_key(|entry| (entry.file_name() != executable, entry.depth()))
+        .map(walkdir::DirEntry::into_path)
+        .ok_or_else(|| {
+            miette::Report::new(crate::types::CliError::PklInstallFailed {
+                reason: format!("No pkl executable in the archive extracted to {}", dir.display()),
+                help: Some("Check that the release archive is for this platform".to_string()),
+            })
+        })
+}
 
-    // Find the pkl executable
-    Ok(target_dir.join("pkl"))
+fn create_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).map_err(|e| {
+        miette::Report::new(crate::types::CliError::IoError {
+            context: format!("Creating directory: {}", dir.display()),
+            source: e,
+        })
+    })
 }
 
-/// Extract tar.gz archive (Windows fallback)
-#[cfg(target_os = "windows")]
-async fn extract_tar_gz_archive(_archive_bytes: &[u8], _target_dir: &PathBuf) -> Result<PathBuf> {
-    Err(miette::Report::new(crate::types::CliError::Generic(
-        "tar.gz extraction not implemented for Windows".to_string(),
-    )))
+/// An archive entry that would land outside the directory it's extracted to
+fn unsafe_entry(entry: &str, target_dir: &Path) -> miette::Report {
+    miette::Report::new(crate::types::CliError::PklInstallFailed {
+        reason: format!("Archive entry {} would be extracted outside {}", entry, target_dir.display()),
+        help: Some("The archive may be corrupt or tampered with; download it again".to_string()),
+    })
 }
 
-/// Execute a Pkl CLI command
-///
-/// Executes Pkl CLI with proper handling based on installation source
-pub async fn execute_pkl_command(pkl_cli: &PklCli, args: &[String]) -> Result<String> {
-    use crate::types::{CliError, pkl_execution_error};
+/// Build the command that runs Pkl with `args`: through `proto run` for proto installations, and the binary
+/// itself otherwise
+pub fn pkl_command(pkl_cli: &PklCli, args: &[String]) -> std::process::Command {
     use std::process::Command;
 
-    let mut cmd = match &pkl_cli.source {
+    match &pkl_cli.source {
         PklSource::Proto => {
             let mut command = Command::new("proto");
             command.arg("run");
@@ -365,35 +471,205 @@ pub async fn execute_pkl_command(pkl_cli: &PklCli, args: &[String]) -> Result<St
             command.args(args);
             command
         }
-    };
+    }
+}
 
-    let output = cmd.output().map_err(|e| CliError::PklExecutionFailed {
-        command: format!("{:?}", cmd),
-        stderr: e.to_string(),
-        help: Some("Check that Pkl CLI is properly installed and accessible".to_string()),
-    })?;
+/// Execute a Pkl CLI command
+///
+/// Executes Pkl CLI with proper handling based on installation source, within the configured timeout. Each
+/// call starts a new process; to evaluate many modules, use a
+/// [`PklEvaluator`](crate::pkl_evaluator::PklEvaluator) instead.
+pub async fn execute_pkl_command(pkl_cli: &PklCli, args: &[String]) -> Result<String> {
+    execute_pkl_command_with_timeout(pkl_cli, args, configured_timeout(DEFAULT_PROCESS_TIMEOUT)).await
+}
+
+/// Execute a Pkl CLI command, killing it if it runs longer than `timeout`
+pub async fn execute_pkl_command_with_timeout(
+    pkl_cli: &PklCli,
+    args: &[String],
+    timeout: Duration,
+) -> Result<String> {
+    use crate::types::pkl_execution_error;
+
+    let cmd = pkl_command(pkl_cli, args);
+    let command = describe_command(&cmd);
+    let output = run_process(cmd, timeout, |stream, line| match stream {
+        OutputStream::Stdout => tracing::trace!(target: "pkl", "{}", line),
+        OutputStream::Stderr => tracing::debug!(target: "pkl", "{}", line),
+    })
+    .await?;
 
     if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        Ok(output.stdout)
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         Err(miette::Report::new(pkl_execution_error(
-            format!("{:?}", cmd),
-            stderr.to_string(),
+            command,
+            output.stderr,
             Some("Check Pkl syntax and file paths".to_string()),
         )))
     }
 }
 
-/// Download Pkl CLI binary for the current platform
+/// How long a Pkl or proto command may run before it's killed, unless [`PKL_TIMEOUT_ENV`] says otherwise
+pub const DEFAULT_PROCESS_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long `proto install` may run, unless [`PKL_TIMEOUT_ENV`] says otherwise
+const PROTO_INSTALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Environment variable giving the timeout of Pkl and proto commands, in seconds
+pub const PKL_TIMEOUT_ENV: &str = "SPKLR_PKL_TIMEOUT";
+
+/// The timeout set with [`PKL_TIMEOUT_ENV`], or else `default`
+pub fn configured_timeout(default: Duration) -> Duration {
+    std::env::var(PKL_TIMEOUT_ENV)
+        .ok()
+        .and_then(|seconds| seconds.trim().parse::<f64>().ok())
+        .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+        .map(Duration::from_secs_f64)
+        .unwrap_or(default)
+}
+
+/// Which of a process's outputs a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// What a finished process wrote, and how it exited
+#[derive(Debug)]
+pub struct ProcessOutput {
+    pub status: std::process::ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run `command` to completion without blocking the runtime, passing each line of its output to `on_line` as
+/// it's written
 ///
-/// Downloads and extracts Pkl CLI from GitHub releases to ~/.moon/tools/pkl/<version>/
-async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
+/// The process is killed once it has run for `timeout`, failing with
+/// [`CliError::ProcessTimeout`](crate::types::CliError::ProcessTimeout), and also if the returned future is
+/// dropped before it finishes, so cancelling a command (with `tokio::select!`, say) doesn't leave it running.
+pub async fn run_process(
+    command: std::process::Command,
+    timeout: Duration,
+    mut on_line: impl FnMut(OutputStream, &str),
+) -> Result<ProcessOutput, crate::types::CliError> {
+    use crate::types::CliError;
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let description = describe_command(&command);
+    let mut command = tokio::process::Command::from(command);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let started = std::time::Instant::now();
+    let mut child = command.spawn().map_err(|e| CliError::PklExecutionFailed {
+        command: description.clone(),
+        stderr: e.to_string(),
+        help: Some("Check that Pkl CLI is properly installed and accessible".to_string()),
+    })?;
+    let mut stdout_reader = child.stdout.take().map(BufReader::new);
+    let mut stderr_reader = child.stderr.take().map(BufReader::new);
+
+    let finished = async {
+        let (mut stdout, mut stderr) = (String::new(), String::new());
+        let (mut stdout_line, mut stderr_line) = (Vec::new(), Vec::new());
+        while stdout_reader.is_some() || stderr_reader.is_some() {
+            // a line stays in its buffer until the rest of it arrives, or the output ends
+            let (stream, line, output) = tokio::select! {
+                read = async { stdout_reader.as_mut().unwrap().read_until(b'\n', &mut stdout_line).await },
+                    if stdout_reader.is_some() =>
+                {
+                    if read? == 0 {
+                        stdout_reader = None;
+                        if stdout_line.is_empty() {
+                            continue;
+                        }
+                    }
+                    (OutputStream::Stdout, &mut stdout_line, &mut stdout)
+                }
+                read = async { stderr_reader.as_mut().unwrap().read_until(b'\n', &mut stderr_line).await },
+                    if stderr_reader.is_some() =>
+                {
+                    if read? == 0 {
+                        stderr_reader = None;
+                        if stderr_line.is_empty() {
+                            continue;
+                        }
+                    }
+                    (OutputStream::Stderr, &mut stderr_line, &mut stderr)
+                }
+            };
+            let text = String::from_utf8_lossy(line);
+            on_line(stream, text.trim_end_matches(['\r', '\n']));
+            output.push_str(&text);
+            line.clear();
+        }
+        let status = child.wait().await?;
+        Ok::<_, std::io::Error>(ProcessOutput {
+            status,
+            stdout,
+            stderr,
+        })
+    };
+
+    match tokio::time::timeout(timeout, finished).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(e)) => Err(CliError::IoError {
+            context: format!("Running {}", description),
+            source: e,
+        }),
+        Err(_) => {
+            // the child may have exited just now; either way it's gone after this
+            let _ = child.kill().await;
+            Err(CliError::ProcessTimeout {
+                command: description,
+                elapsed: started.elapsed(),
+            })
+        }
+    }
+}
+
+/// A command as it would be typed: the program, then its arguments
+fn describe_command(command: &std::process::Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Where Pkl release archives are downloaded from when no mirror is configured
+pub const DEFAULT_PKL_MIRROR: &str = "https://github.com/apple/pkl/releases/download";
+
+/// Environment variable giving a mirror of [`DEFAULT_PKL_MIRROR`], laid out as `<mirror>/<version>/<archive name>`
+pub const PKL_MIRROR_ENV: &str = "SPKLR_PKL_MIRROR";
+
+/// The name of the Pkl CLI release archive for the current platform, e.g. `pkl-cli-linux-amd64.tar.gz`
+pub fn pkl_archive_name() -> Result<String> {
+    pkl_archive_name_for(std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// The platforms Pkl publishes release archives for, as Rust's `(OS, ARCH)` names
+pub const PKL_RELEASE_PLATFORMS: [(&str, &str); 5] = [
+    ("linux", "x86_64"),
+    ("linux", "aarch64"),
+    ("macos", "x86_64"),
+    ("macos", "aarch64"),
+    ("windows", "x86_64"),
+];
+
+/// The release archive name for a platform, given as Rust's `std::env::consts::{OS, ARCH}` names
+pub fn pkl_archive_name_for(os: &str, arch: &str) -> Result<String> {
     use crate::types::CliError;
-    use std::env;
 
     // Platform detection
-    let (os, arch) = match (env::consts::OS, env::consts::ARCH) {
+    let (os, arch) = match (os, arch) {
         ("linux", "x86_64") => ("linux", "amd64"),
         ("linux", "aarch64") => ("linux", "aarch64"),
         ("macos", "x86_64") => ("macos", "amd64"),
@@ -407,106 +683,422 @@ async fn download_pkl_binary(version: &str) -> Result<PathBuf> {
         }
     };
 
-    // Create installation directory
-    let install_dir = get_pkl_install_dir(version)?;
-    tokio::fs::create_dir_all(&install_dir).await.map_err(|e| {
-        miette::Report::new(CliError::IoError {
-            context: format!(
-                "Creating Pkl installation directory: {}",
-                install_dir.display()
-            ),
-            source: e,
-        })
-    })?;
-
-    // Construct download URL
-    let file_extension = if env::consts::OS == "windows" {
+    let file_extension = if os == "windows" {
         "zip"
     } else {
         "tar.gz"
     };
-    let archive_name = format!("pkl-cli-{}-{}.{}", os, arch, file_extension);
-    let download_url = format!(
-        "https://github.com/apple/pkl/releases/download/{}/{}",
-        version, archive_name
-    );
+    Ok(format!("pkl-cli-{}-{}.{}", os, arch, file_extension))
+}
 
-    println!("📥 Downloading from: {}", download_url);
+/// The download URL of `archive_name` for Pkl `version`. The base is `mirror` if given, then
+/// [`PKL_MIRROR_ENV`], then GitHub releases.
+pub fn pkl_download_url(mirror: Option<&str>, version: &str, archive_name: &str) -> String {
+    let base = mirror
+        .map(str::to_string)
+        .or_else(|| std::env::var(PKL_MIRROR_ENV).ok())
+        .filter(|base| !base.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PKL_MIRROR.to_string());
+    format!("{}/{}/{}", base.trim().trim_end_matches('/'), version, archive_name)
+}
 
-    // Download with retry logic
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&download_url)
-        .send()
-        .await
-        .map_err(|e| miette::Report::new(CliError::NetworkError(e.to_string())))?;
+/// Install Pkl CLI `version` straight from its release archive, without trying proto or the system PATH
+///
+/// The archive is read from `options.from_archive` if set, and downloaded from the configured mirror
+/// otherwise. Either way it's checked against its SHA-256 and extracted to ~/.moon/tools/pkl/<version>/.
+pub async fn install_pkl_archive(version: &str, options: &PklInstallOptions) -> Result<PklCli> {
+    let pkl_path = install_pkl_binary(version, options).await?;
+    Ok(PklCli {
+        path: pkl_path,
+        source: PklSource::Manual(get_pkl_install_dir(version)?),
+        version: Some(version.to_string()),
+    })
+}
 
-    if !response.status().is_success() {
-        return Err(miette::Report::new(CliError::PklInstallFailed {
-            reason: format!("Download failed with status: {}", response.status()),
-            help: Some(format!(
-                "Check if version {} exists at {}",
-                version, download_url
-            )),
-        }));
+/// Fetch, verify and extract the Pkl CLI release archive, returning the installed executable
+async fn install_pkl_binary(version: &str, options: &PklInstallOptions) -> Result<PathBuf> {
+    use crate::pkl_versions::PklVersions;
+    use crate::types::CliError;
+
+    let archive_name = match &options.from_archive {
+        Some(path) => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                miette::Report::new(CliError::PklInstallFailed {
+                    reason: format!("{} is not an archive file", path.display()),
+                    help: Some("Pass the path of a Pkl CLI release .tar.gz or .zip".to_string()),
+                })
+            })?,
+        None => pkl_archive_name()?,
+    };
+
+    // Refuse up front anything we couldn't verify
+    let expected_checksum = match &options.checksum {
+        Some(checksum) => checksum.clone(),
+        None => known_checksum(version, &archive_name)
+            .ok_or_else(|| {
+                miette::Report::new(CliError::PklInstallFailed {
+                    reason: format!("No known checksum for {} {}", version, archive_name),
+                    help: Some(format!(
+                        "Pass the archive's SHA-256 with --checksum, or install one of the tested versions: {}",
+                        get_compatible_pkl_versions().join(", ")
+                    )),
+                })
+            })?
+            .to_string(),
+    };
+
+    let archive_path = match &options.from_archive {
+        Some(path) => {
+            println!("📦 Installing from archive: {}", path.display());
+            path.clone()
+        }
+        None => {
+            let download_url = pkl_download_url(options.mirror.as_deref(), version, &archive_name);
+            println!("📥 Downloading from: {}", download_url);
+            let path = PklVersions::open()?
+                .tools_dir()
+                .join(DOWNLOADS_DIR)
+                .join(version)
+                .join(&archive_name);
+            download_file(&download_url, &path, &options.retry).await?;
+            path
+        }
+    };
+    let archive_bytes = tokio::fs::read(&archive_path).await.map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Reading Pkl archive: {}", archive_path.display()),
+            source: e,
+        })
+    })?;
+
+    let downloaded = options.from_archive.is_none();
+    if let Err(e) = verify_checksum(&archive_bytes, &expected_checksum, &archive_name) {
+        // a bad download mustn't be resumed next time
+        if downloaded {
+            let _ = tokio::fs::remove_file(&archive_path).await;
+        }
+        return Err(e);
     }
 
-    let archive_bytes = response
-        .bytes()
-        .await
-        .map_err(|e| miette::Report::new(CliError::NetworkError(e.to_string())))?;
+    let install_dir = get_pkl_install_dir(version)?;
+    let pkl_executable_path = extract_into_place(&archive_bytes, &archive_name, &install_dir)?;
+    if downloaded {
+        let _ = tokio::fs::remove_file(&archive_path).await;
+    }
+    Ok(pkl_executable_path)
+}
 
-    // Extract archive
-    let pkl_executable_path = if env::consts::OS == "windows" {
-        extract_zip_archive(&archive_bytes, &install_dir).await?
-    } else {
-        extract_tar_gz_archive(&archive_bytes, &install_dir).await?
+/// Extract the archive beside `install_dir` and then swap it into place, so a failed or interrupted install
+/// never leaves a half-written version behind
+fn extract_into_place(archive_bytes: &[u8], archive_name: &str, install_dir: &Path) -> Result<PathBuf> {
+    use crate::types::CliError;
+
+    let parent = install_dir.parent().unwrap_or(install_dir);
+    create_dir(parent)?;
+    let temp_dir = |prefix: &str| {
+        tempfile::Builder::new()
+            .prefix(prefix)
+            .tempdir_in(parent)
+            .map_err(|e| CliError::IoError {
+                context: format!("Creating a directory in {}", parent.display()),
+                source: e,
+            })
     };
 
+    let staging = temp_dir(".staging-")?;
+    let staged_executable = extract_archive(archive_bytes, archive_name, staging.path())?;
+
     // Set executable permissions on Unix-like systems
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = tokio::fs::metadata(&pkl_executable_path)
-            .await
-            .map_err(|e| {
-                miette::Report::new(CliError::IoError {
-                    context: "Reading file permissions".to_string(),
-                    source: e,
-                })
-            })?
-            .permissions();
-        perms.set_mode(0o755);
-        tokio::fs::set_permissions(&pkl_executable_path, perms)
-            .await
-            .map_err(|e| {
-                miette::Report::new(CliError::IoError {
-                    context: "Setting file permissions".to_string(),
-                    source: e,
-                })
-            })?;
+        std::fs::set_permissions(&staged_executable, std::fs::Permissions::from_mode(0o755)).map_err(|e| {
+            CliError::IoError {
+                context: "Setting file permissions".to_string(),
+                source: e,
+            }
+        })?;
     }
 
-    Ok(pkl_executable_path)
+    let rename = |from: &Path, to: &Path| {
+        std::fs::rename(from, to).map_err(|e| CliError::IoError {
+            context: format!("Moving {} to {}", from.display(), to.display()),
+            source: e,
+        })
+    };
+    // a reinstall moves the old version aside first; dropping `replaced` removes it
+    let replaced = temp_dir(".replaced-")?;
+    if install_dir.exists() {
+        rename(install_dir, &replaced.path().join("old"))?;
+    }
+    rename(staging.path(), install_dir)?;
+
+    let relative = staged_executable
+        .strip_prefix(staging.path())
+        .unwrap_or(&staged_executable);
+    Ok(install_dir.join(relative))
 }
 
-/// Get the target installation directory for Pkl
+/// Directory in the tools directory that holds downloads until they're installed
+const DOWNLOADS_DIR: &str = ".downloads";
+
+/// Give up on a connection that hasn't sent anything for this long
+const DOWNLOAD_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often and how patiently downloads are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Tries in all, including the first
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each one after it
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait after the `attempt`th try failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// Download `url` to `destination`
 ///
-/// Returns ~/.moon/tools/pkl/<version>/ path
-fn get_pkl_install_dir(version: &str) -> Result<PathBuf> {
+/// The file is streamed to `<destination>.part` and moved into place once complete. Transient failures
+/// (dropped connections, timeouts, 5xx responses) are retried as `retry` says, resuming the partial file with
+/// an HTTP `Range` request where the server supports it; a partial file left by an earlier run is resumed too.
+pub async fn download_file(url: &str, destination: &Path, retry: &RetryPolicy) -> Result<()> {
     use crate::types::CliError;
 
-    let home_dir = dirs::home_dir().ok_or_else(|| {
-        miette::Report::new(CliError::Generic(
-            "Could not determine home directory".to_string(),
-        ))
-    })?;
+    if let Some(parent) = destination.parent() {
+        create_dir(parent)?;
+    }
+    let mut partial = destination.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .build()
+        .map_err(CliError::from)?;
+    let progress = download_progress(destination);
+
+    let mut attempt = 1;
+    loop {
+        match download_attempt(&client, url, &partial, &progress).await {
+            Ok(()) => break,
+            Err(DownloadError::Fatal(e)) => {
+                progress.abandon();
+                return Err(e);
+            }
+            Err(DownloadError::Transient(reason)) if attempt < retry.attempts => {
+                let backoff = retry.backoff(attempt);
+                progress.suspend(|| {
+                    println!(
+                        "⚠️  Download interrupted ({}); retrying in {:.1?} (attempt {} of {})",
+                        reason,
+                        backoff,
+                        attempt + 1,
+                        retry.attempts
+                    )
+                });
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(DownloadError::Transient(reason)) => {
+                progress.abandon();
+                return Err(miette::Report::new(CliError::NetworkError(format!(
+                    "{} failed after {} attempts: {}",
+                    url, attempt, reason
+                ))));
+            }
+        }
+    }
+    progress.finish_and_clear();
+
+    tokio::fs::rename(&partial, destination).await.map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Moving the download to {}", destination.display()),
+            source: e,
+        })
+    })
+}
+
+/// Why a download attempt stopped
+enum DownloadError {
+    /// Worth another try
+    Transient(String),
+    /// Won't get better by trying again
+    Fatal(miette::Report),
+}
+
+impl From<reqwest::Error> for DownloadError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_builder() {
+            DownloadError::Fatal(miette::Report::new(crate::types::CliError::from(e)))
+        } else {
+            DownloadError::Transient(e.to_string())
+        }
+    }
+}
+
+/// Fetch `url` into `partial`, continuing from whatever it already holds if the server allows
+async fn download_attempt(
+    client: &reqwest::Client,
+    url: &str,
+    partial: &Path,
+    progress: &indicatif::ProgressBar,
+) -> std::result::Result<(), DownloadError> {
+    use crate::types::CliError;
+    use reqwest::StatusCode;
+    use tokio::io::AsyncWriteExt;
+
+    let io_error = |e: std::io::Error| {
+        DownloadError::Fatal(miette::Report::new(CliError::IoError {
+            context: format!("Writing {}", partial.display()),
+            source: e,
+        }))
+    };
+
+    let offset = tokio::fs::metadata(partial).await.map_or(0, |metadata| metadata.len());
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request.send().await?;
+    let status = response.status();
+
+    let (resumed, total) = if offset > 0 && status == StatusCode::PARTIAL_CONTENT {
+        match content_range(&response) {
+            Some((start, total)) if start == offset => (true, total),
+            _ => {
+                tokio::fs::remove_file(partial).await.map_err(io_error)?;
+                return Err(DownloadError::Transient("the server resumed from the wrong place".to_string()));
+            }
+        }
+    } else if offset > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+        tokio::fs::remove_file(partial).await.map_err(io_error)?;
+        return Err(DownloadError::Transient("the partial download no longer matches".to_string()));
+    } else if status.is_success() {
+        // a server that ignores `Range` sends everything again
+        (false, response.content_length())
+    } else if status.is_server_error() || matches!(status, StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS) {
+        return Err(DownloadError::Transient(format!("status {}", status)));
+    } else {
+        return Err(DownloadError::Fatal(miette::Report::new(CliError::PklInstallFailed {
+            reason: format!("Download failed with status: {}", status),
+            help: Some(format!("Check that {} exists", url)),
+        })));
+    };
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(partial)
+        .await
+        .map_err(io_error)?;
+    let mut written = if resumed { offset } else { 0 };
+    if let Some(total) = total {
+        progress.set_length(total);
+    }
+    progress.set_position(written);
+
+    while let Some(chunk) = tokio::time::timeout(DOWNLOAD_STALL_TIMEOUT, response.chunk())
+        .await
+        .map_err(|_| DownloadError::Transient(format!("no data for {:?}", DOWNLOAD_STALL_TIMEOUT)))??
+    {
+        file.write_all(&chunk).await.map_err(io_error)?;
+        written += chunk.len() as u64;
+        progress.set_position(written);
+    }
+    file.flush().await.map_err(io_error)?;
+
+    match total {
+        Some(total) if written < total => Err(DownloadError::Transient(format!(
+            "the connection closed after {} of {} bytes",
+            written, total
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// The start and total size of a `Content-Range: bytes <start>-<end>/<total>` response
+fn content_range(response: &reqwest::Response) -> Option<(u64, Option<u64>)> {
+    let range = response.headers().get(reqwest::header::CONTENT_RANGE)?.to_str().ok()?;
+    let (span, total) = range.strip_prefix("bytes ")?.split_once('/')?;
+    let start = span.split_once('-')?.0.trim().parse().ok()?;
+    Some((start, total.trim().parse().ok()))
+}
+
+/// A progress bar for downloading `destination`, drawn only on a terminal
+fn download_progress(destination: &Path) -> indicatif::ProgressBar {
+    let progress = indicatif::ProgressBar::no_length();
+    if let Ok(style) =
+        indicatif::ProgressStyle::with_template("{msg} [{bar:30}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+    {
+        progress.set_style(style.progress_chars("=> "));
+    }
+    if let Some(name) = destination.file_name() {
+        progress.set_message(name.to_string_lossy().into_owned());
+    }
+    progress
+}
+
+/// SHA-256 checksums of the release archives, as `<hash>  <version>/<archive name>` lines
+const PKL_CHECKSUMS: &str = include_str!("pkl_checksums.txt");
+
+/// The SHA-256 the checksum manifest lists for `archive_name` of Pkl `version`, if any
+pub fn known_checksum(version: &str, archive_name: &str) -> Option<&'static str> {
+    let wanted = format!("{}/{}", version, archive_name);
+    PKL_CHECKSUMS
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim_start().trim_start_matches('*') == wanted)
+        .map(|(hash, _)| hash)
+}
+
+/// Check `bytes` hash to `expected`, a hex SHA-256 in either case, failing with
+/// [`CliError::ChecksumMismatch`](crate::types::CliError::ChecksumMismatch) if not
+pub fn verify_checksum(bytes: &[u8], expected: &str, file: &str) -> Result<()> {
+    use crate::types::CliError;
+    use sha2::{Digest, Sha256};
+
+    let actual: String = Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect();
+    let expected = expected.trim().to_ascii_lowercase();
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(miette::Report::new(CliError::ChecksumMismatch {
+            file: file.to_string(),
+            expected,
+            actual,
+        }))
+    }
+}
 
-    Ok(home_dir
-        .join(".moon")
-        .join("tools")
-        .join("pkl")
-        .join(version))
+/// Get the target installation directory for Pkl
+///
+/// Returns ~/.moon/tools/pkl/<version>/ path
+fn get_pkl_install_dir(version: &str) -> Result<PathBuf> {
+    Ok(crate::pkl_versions::PklVersions::open()?.version_dir(version))
 }
 
 /// Check if proto is available in the system
@@ -527,39 +1119,114 @@ pub fn get_compatible_pkl_versions() -> Vec<&'static str> {
     vec!["0.28.0", "0.28.1", "0.28.2"] // Updated by CI
 }
 
+/// The newest version in the compatibility matrix, which `latest-compatible` asks for
+pub fn get_latest_compatible_pkl_version() -> &'static str {
+    get_compatible_pkl_versions()
+        .into_iter()
+        .max_by(|a, b| crate::pkl_versions::compare_versions(a, b))
+        .unwrap_or_else(get_recommended_pkl_version)
+}
+
+/// A warning for a Pkl version outside the compatibility matrix, `None` for tested ones
+pub fn untested_pkl_version_warning(version: &str) -> Option<String> {
+    (!get_compatible_pkl_versions().contains(&version)).then(|| {
+        format!(
+            "Pkl CLI {} has not been tested with Space Pklr (tested: {}); conversions may not work as expected",
+            version,
+            get_compatible_pkl_versions().join(", ")
+        )
+    })
+}
+
+/// The outcome of one compatibility check
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FeatureResult {
+    /// What was checked: `version`, `schema:<config>`, `amends:<config>`, `load:<config>` or `facts`
+    pub feature: String,
+    pub passed: bool,
+    /// What Pkl or moon_config said when the check failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Comprehensive compatibility report for Pkl CLI validation
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CompatibilityReport {
-    pub basic_functionality: bool,
-    pub moon_config_integration: bool,
-    pub extend_amend_support: bool,
-    pub schema_generation: bool,
     pub pkl_version: String,
+    /// In the order they were checked
+    pub features: Vec<FeatureResult>,
 }
 
 impl CompatibilityReport {
     pub fn new(pkl_version: String) -> Self {
         Self {
-            basic_functionality: false,
-            moon_config_integration: false,
-            extend_amend_support: false,
-            schema_generation: false,
             pkl_version,
+            features: Vec::new(),
         }
     }
 
+    /// Add a check's result, with the error text if it failed
+    pub fn record(&mut self, feature: impl Into<String>, outcome: std::result::Result<(), String>) {
+        let (passed, error) = match outcome {
+            Ok(()) => (true, None),
+            Err(error) => (false, Some(error)),
+        };
+        self.features.push(FeatureResult {
+            feature: feature.into(),
+            passed,
+            error,
+        });
+    }
+
+    pub fn feature(&self, feature: &str) -> Option<&FeatureResult> {
+        self.features.iter().find(|result| result.feature == feature)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &FeatureResult> {
+        self.features.iter().filter(|result| !result.passed)
+    }
+
+    /// Whether anything was checked and everything passed
     pub fn is_compatible(&self) -> bool {
-        self.basic_functionality
-            && self.moon_config_integration
-            && self.extend_amend_support
-            && self.schema_generation
+        !self.features.is_empty() && self.features.iter().all(|result| result.passed)
     }
+
+    /// The report as pretty-printed JSON, as the version management workflow collects it
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            miette::Report::new(crate::types::CliError::Generic(format!(
+                "Failed to write the compatibility report as JSON: {}",
+                e
+            )))
+        })
+    }
+}
+
+/// `pkl test` facts about the project config the suite writes
+const COMPATIBILITY_FACTS: &str = r#"amends "pkl:test"
+
+import "project.pkl"
+
+facts {
+  ["amended settings are kept"] {
+    project.language == "rust"
+    project.tags.toList() == List("app")
+  }
 }
+"#;
 
 /// Validate Pkl version compatibility with comprehensive testing
+///
+/// For each Moon config type, renders its schema with [`PklSchemaRenderer`](crate::pkl_renderer::PklSchemaRenderer)
+/// and evaluates it, evaluates a config amending it, and loads Pkl's JSON output back with moon_config. Then
+/// `pkl test` runs facts about one of those configs. Every check is recorded, passed or not.
 pub async fn validate_pkl_compatibility(pkl_cli: &PklCli) -> Result<CompatibilityReport> {
+    use crate::types::{CliError, MoonConfig};
 
-
+    let dir = tempfile::TempDir::new().map_err(|e| CliError::IoError {
+        context: "Creating a directory for the compatibility checks".to_string(),
+        source: e,
+    })?;
     let version = pkl_cli
         .version
         .clone()
@@ -571,22 +1238,34 @@ pub async fn validate_pkl_compatibility(pkl_cli: &PklCli) -> Result<Compatibilit
         report.pkl_version
     );
 
-    // Test basic functionality
-    tracing::debug!("Testing basic Pkl functionality...");
-    report.basic_functionality = test_basic_pkl_functionality(pkl_cli).await?;
-
-    // Test moon_config integration
-    tracing::debug!("Testing moon_config integration...");
-    report.moon_config_integration = test_moon_config_integration(pkl_cli).await?;
-
-    // Test extend/amend features
-    tracing::debug!("Testing extend/amend features...");
-    report.extend_amend_support = test_extend_amend_features(pkl_cli).await?;
-
-    // Test schema generation
-    tracing::debug!("Testing schema generation...");
-    report.schema_generation = test_schema_generation(pkl_cli).await?;
+    let version = run_check(pkl_cli, &["--version".to_string()])
+        .await
+        .and_then(|output| match parse_pkl_version(&output) {
+            Some(_) => Ok(()),
+            None => Err(format!("Unexpected `pkl --version` output: {}", output.trim())),
+        });
+    report.record("version", version);
+
+    for config_type in MoonConfig::all_types() {
+        check_config_type(pkl_cli, config_type, dir.path(), &mut report).await;
+    }
 
+    let facts = dir.path().join("facts.pkl");
+    let outcome = match write_check_file(&facts, COMPATIBILITY_FACTS) {
+        Ok(()) => run_check(pkl_cli, &["test".to_string(), facts.to_string_lossy().to_string()])
+            .await
+            .map(drop),
+        Err(error) => Err(error),
+    };
+    report.record("facts", outcome);
+
+    for failure in report.failures() {
+        tracing::warn!(
+            "Compatibility check {} failed: {}",
+            failure.feature,
+            failure.error.as_deref().unwrap_or_default()
+        );
+    }
     tracing::info!(
         "Compatibility validation completed. Compatible: {}",
         report.is_compatible()
@@ -594,195 +1273,142 @@ pub async fn validate_pkl_compatibility(pkl_cli: &PklCli) -> Result<Compatibilit
     Ok(report)
 }
 
-/// Test basic Pkl CLI functionality
-async fn test_basic_pkl_functionality(pkl_cli: &PklCli) -> Result<bool> {
-    match execute_pkl_command(pkl_cli, &["--version".to_string()]).await {
-        Ok(output) => {
-            let has_version = output.contains("Pkl") && output.chars().any(|c| c.is_ascii_digit());
-            tracing::debug!(
-                "Basic functionality test: {}",
-                if has_version { "PASS" } else { "FAIL" }
-            );
-            Ok(has_version)
-        }
-        Err(e) => {
-            tracing::warn!("Basic functionality test failed: {}", e);
-            Ok(false)
-        }
+/// Check `config_type`'s schema, a config amending it, and loading what Pkl makes of that config. A schema
+/// that doesn't evaluate leaves nothing to amend, so the other two checks are left out
+async fn check_config_type(
+    pkl_cli: &PklCli,
+    config_type: crate::types::MoonConfig,
+    dir: &Path,
+    report: &mut CompatibilityReport,
+) {
+    use crate::types::LoadedConfig;
+
+    let schema = match write_moon_schema(config_type, dir) {
+        Ok(schema) => schema,
+        Err(error) => return report.record(format!("schema:{}", config_type), Err(error)),
+    };
+    let args = ["eval", "-x", "true", &schema.to_string_lossy()].map(String::from);
+    let outcome = run_check(pkl_cli, &args).await;
+    let schema_evaluated = outcome.is_ok();
+    report.record(format!("schema:{}", config_type), outcome.map(drop));
+    if !schema_evaluated {
+        return;
     }
-}
-
-/// Test moon_config integration by validating a simple configuration
-async fn test_moon_config_integration(pkl_cli: &PklCli) -> Result<bool> {
-    use std::io::Write;
-    use tempfile::NamedTempFile;
-
-    // Create a simple test configuration
-    let mut temp_file = NamedTempFile::new().map_err(|e| crate::types::CliError::IoError {
-        context: "Creating temporary test file".to_string(),
-        source: e,
-    })?;
-
-    writeln!(
-        temp_file,
-        r#"
-language = "rust"
-type = "library"
-tasks {{
-    build {{
-        command = "cargo build"
-    }}
-}}
-"#
-    )
-    .map_err(|e| crate::types::CliError::IoError {
-        context: "Writing test configuration".to_string(),
-        source: e,
-    })?;
 
-    // Try to evaluate the configuration with Pkl
-    match execute_pkl_command(
-        pkl_cli,
-        &[
-            "eval".to_string(),
-            temp_file.path().to_string_lossy().to_string(),
-        ],
-    )
-    .await
-    {
-        Ok(_) => {
-            tracing::debug!("Moon config integration test: PASS");
-            Ok(true)
-        }
-        Err(e) => {
-            tracing::warn!("Moon config integration test failed: {}", e);
-            Ok(false)
+    let config = match write_amending_config(config_type, dir) {
+        Ok(config) => config,
+        Err(error) => return report.record(format!("amends:{}", config_type), Err(error)),
+    };
+    let args = ["eval", "--format", "json", &config.to_string_lossy()].map(String::from);
+    match run_check(pkl_cli, &args).await {
+        Ok(json) => {
+            report.record(format!("amends:{}", config_type), Ok(()));
+            let loaded = LoadedConfig::from_code(config_type, json, schematic::Format::Json)
+                .map(drop)
+                .map_err(|e| error_text(&e));
+            report.record(format!("load:{}", config_type), loaded);
         }
+        Err(error) => report.record(format!("amends:{}", config_type), Err(error)),
     }
 }
 
-/// Test extend/amend features with a simple inheritance scenario
-async fn test_extend_amend_features(pkl_cli: &PklCli) -> Result<bool> {
-    use std::io::Write;
-    use tempfile::TempDir;
-
-    let temp_dir = TempDir::new().map_err(|e| crate::types::CliError::IoError {
-        context: "Creating temporary directory".to_string(),
-        source: e,
-    })?;
-
-    // Create base configuration
-    let base_path = temp_dir.path().join("base.pkl");
-    let mut base_file =
-        std::fs::File::create(&base_path).map_err(|e| crate::types::CliError::IoError {
-            context: "Creating base test file".to_string(),
-            source: e,
-        })?;
+/// Render `config_type`'s Pkl schema into `dir` as `<Root>.pkl`
+fn write_moon_schema(config_type: crate::types::MoonConfig, dir: &Path) -> std::result::Result<PathBuf, String> {
+    use crate::types::SchemaFormat;
+
+    let (_, root) = config_type
+        .schema_types()
+        .ok_or_else(|| format!("{} has no schema", config_type))?;
+    let schema = crate::custom_types::render_moon_schema(config_type, &SchemaFormat::Pkl)
+        .map_err(|e| error_text(&e))?;
+    let path = dir.join(format!("{}.pkl", root));
+    write_check_file(&path, &schema)?;
+    Ok(path)
+}
 
-    writeln!(
-        base_file,
-        r#"
-language = "rust"
-type = "library"
-"#
-    )
-    .map_err(|e| crate::types::CliError::IoError {
-        context: "Writing base configuration".to_string(),
-        source: e,
-    })?;
+/// Write a `config_type` config into `dir` as `<config type>.pkl`, the way `convert` writes it, so it amends the
+/// `<Root>.pkl` schema beside it
+fn write_amending_config(config_type: crate::types::MoonConfig, dir: &Path) -> std::result::Result<PathBuf, String> {
+    use crate::types::SchemaFormat;
 
-    // Create extending configuration
-    let extend_path = temp_dir.path().join("extend.pkl");
-    let mut extend_file =
-        std::fs::File::create(&extend_path).map_err(|e| crate::types::CliError::IoError {
-            context: "Creating extend test file".to_string(),
-            source: e,
-        })?;
+    let code = crate::_rewrite::render_config_with_schematic(&sample_config(config_type), SchemaFormat::Pkl)
+        .map_err(|e| error_text(&e))?;
+    let path = dir.join(format!("{}.pkl", config_type));
+    write_check_file(&path, &code)?;
+    Ok(path)
+}
 
-    writeln!(
-        extend_file,
-        r#"
-extends "{}"
-
-tasks {{
-    build {{
-        command = "cargo build"
-    }}
-}}
-"#,
-        base_path.to_string_lossy()
-    )
-    .map_err(|e| crate::types::CliError::IoError {
-        context: "Writing extend configuration".to_string(),
-        source: e,
-    })?;
+/// A few settings of each config type on top of its defaults, including what [`COMPATIBILITY_FACTS`] checks
+fn sample_config(config_type: crate::types::MoonConfig) -> crate::types::LoadedConfig {
+    use crate::types::{LoadedConfig, MoonConfig};
+    use moon_common::Id;
+    use moon_config::{
+        InheritedTasksConfig, InputPath, LanguageType, ProjectConfig, TaskArgs, TaskConfig, TemplateConfig,
+        ToolchainConfig, WorkspaceConfig, WorkspaceProjects,
+    };
 
-    // Try to evaluate the extending configuration
-    match execute_pkl_command(
-        pkl_cli,
-        &[
-            "eval".to_string(),
-            extend_path.to_string_lossy().to_string(),
-        ],
-    )
-    .await
-    {
-        Ok(_) => {
-            tracing::debug!("Extend/amend features test: PASS");
-            Ok(true)
-        }
-        Err(e) => {
-            tracing::warn!("Extend/amend features test failed: {}", e);
-            Ok(false)
-        }
+    let build = TaskConfig {
+        command: TaskArgs::String("cargo build".to_string()),
+        inputs: Some(vec![InputPath::ProjectGlob("src/**/*".to_string())]),
+        ..Default::default()
+    };
+    match config_type {
+        MoonConfig::Project => LoadedConfig::Project(ProjectConfig {
+            language: LanguageType::Rust,
+            tags: vec![Id::raw("app")],
+            ..Default::default()
+        }),
+        MoonConfig::Workspace => LoadedConfig::Workspace(WorkspaceConfig {
+            projects: WorkspaceProjects::Globs(vec!["apps/*".to_string()]),
+            ..Default::default()
+        }),
+        MoonConfig::Template => LoadedConfig::Template(TemplateConfig {
+            title: "Example".to_string(),
+            description: "An example template".to_string(),
+            ..Default::default()
+        }),
+        MoonConfig::Task => LoadedConfig::Task(build),
+        MoonConfig::InheritedTasks => LoadedConfig::InheritedTasks(InheritedTasksConfig {
+            tasks: [(Id::raw("build"), build)].into(),
+            ..Default::default()
+        }),
+        MoonConfig::Toolchain | MoonConfig::All => LoadedConfig::Toolchain(ToolchainConfig::default()),
     }
 }
 
-/// Test schema generation capabilities
-async fn test_schema_generation(pkl_cli: &PklCli) -> Result<bool> {
-    use std::io::Write;
-    use tempfile::NamedTempFile;
+fn write_check_file(path: &Path, content: &str) -> std::result::Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("Writing {}: {}", path.display(), e))
+}
 
-    // Create a simple schema test
-    let mut temp_file = NamedTempFile::new().map_err(|e| crate::types::CliError::IoError {
-        context: "Creating schema test file".to_string(),
-        source: e,
-    })?;
+/// Run Pkl for a check, giving its output, or what it printed if it failed
+async fn run_check(pkl_cli: &PklCli, args: &[String]) -> std::result::Result<String, String> {
+    let command = pkl_command(pkl_cli, args);
+    let output = run_process(command, configured_timeout(DEFAULT_PROCESS_TIMEOUT), |_, _| {})
+        .await
+        .map_err(|e| error_text(&e))?;
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
 
-    writeln!(
-        temp_file,
-        r#"
-class Config {{
-    language: String
-    type: String
-}}
-"#
-    )
-    .map_err(|e| crate::types::CliError::IoError {
-        context: "Writing schema test file".to_string(),
-        source: e,
-    })?;
+    // `pkl test` reports failed facts on stdout
+    let printed = [output.stderr.trim(), output.stdout.trim()]
+        .into_iter()
+        .find(|text| !text.is_empty());
+    Err(match printed {
+        Some(text) => text.to_string(),
+        None => format!("Pkl exited with {}", output.status),
+    })
+}
 
-    // Try to generate schema
-    match execute_pkl_command(
-        pkl_cli,
-        &[
-            "project".to_string(),
-            "package".to_string(),
-            temp_file.path().to_string_lossy().to_string(),
-        ],
-    )
-    .await
-    {
-        Ok(_) => {
-            tracing::debug!("Schema generation test: PASS");
-            Ok(true)
-        }
-        Err(e) => {
-            tracing::warn!("Schema generation test failed: {}", e);
-            Ok(false)
-        }
+/// An error and its sources, on one line
+fn error_text(error: &dyn std::error::Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        text.push_str(&format!(": {}", error));
+        source = error.source();
     }
+    text
 }
 
 /// Validate Pkl CLI installation
@@ -790,10 +1416,8 @@ class Config {{
 /// Validates installation by running pkl --version and checking output
 pub async fn validate_pkl_installation(pkl_cli: &PklCli) -> Result<bool> {
     match execute_pkl_command(pkl_cli, &["--version".to_string()]).await {
-        Ok(output) => {
-            // Check if output contains version information
-            Ok(output.contains("pkl") && output.chars().any(|c| c.is_ascii_digit()))
-        }
+        // the same check as the compatibility suite's
+        Ok(output) => Ok(parse_pkl_version(&output).is_some()),
         Err(_) => Ok(false),
     }
 }
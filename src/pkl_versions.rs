@@ -0,0 +1,433 @@
+//! Pkl version management for Space Pklr
+//!
+//! Keeps track of the Pkl CLI versions installed under ~/.moon/tools/pkl, the default one chosen with
+//! `spklr pkl-me use`, and the version a project pins with `.pkl-version` or the `pkl` entry of `.prototools`.
+//! Versions are asked for like Cargo dependencies: `0.28.1` also accepts `0.28.2`, and ranges such as `0.28`,
+//! `^0.28` or `>=0.27, <0.29` work too, as does `latest-compatible` for the newest tested version. Installing a
+//! complete version installs exactly that one, though.
+
+use miette::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::types::CliError;
+
+/// Project file holding just the Pkl version to use
+pub const PKL_VERSION_FILE: &str = ".pkl-version";
+
+/// proto's project file, whose `pkl` entry pins the Pkl version
+pub const PROTOTOOLS_FILE: &str = ".prototools";
+
+/// File in the tools directory holding the default version
+const DEFAULT_VERSION_FILE: &str = ".default-version";
+
+/// Asks for the newest version in the tested matrix
+pub const LATEST_COMPATIBLE: &str = "latest-compatible";
+
+/// A Pkl version as asked for with `--version` or in a pin file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionRequest {
+    /// A complete version, like `0.28.1`; later versions compatible with it are accepted too, but it's the one installed
+    Version(semver::Version),
+    /// A range, like `0.28`, `^0.28` or `>=0.27, <0.29`
+    Range(semver::VersionReq),
+    /// The newest version in [`get_compatible_pkl_versions`](crate::pkl_tooling::get_compatible_pkl_versions)
+    LatestCompatible,
+}
+
+impl VersionRequest {
+    /// Whether `version` satisfies the request; anything that isn't a version doesn't
+    pub fn matches(&self, version: &str) -> bool {
+        let Ok(version) = semver::Version::parse(version.trim_start_matches('v')) else {
+            return false;
+        };
+        match self {
+            VersionRequest::Version(wanted) => caret(wanted).matches(&version),
+            VersionRequest::Range(range) => range.matches(&version),
+            VersionRequest::LatestCompatible => {
+                semver::Version::parse(crate::pkl_tooling::get_latest_compatible_pkl_version()).is_ok_and(|latest| latest == version)
+            }
+        }
+    }
+
+    /// The version to use: the newest of `installed` the request accepts, else the newest tested one, else the
+    /// version itself when the request names one exactly
+    pub fn resolve<'a>(&self, installed: impl IntoIterator<Item = &'a str>) -> Option<String> {
+        if *self == VersionRequest::LatestCompatible {
+            return Some(crate::pkl_tooling::get_latest_compatible_pkl_version().to_string());
+        }
+        let newest = |versions: Vec<&str>| {
+            versions
+                .into_iter()
+                .filter(|version| self.matches(version))
+                .max_by(|a, b| compare_versions(a, b))
+                .map(str::to_string)
+        };
+        newest(installed.into_iter().collect())
+            .or_else(|| newest(crate::pkl_tooling::get_compatible_pkl_versions()))
+            .or_else(|| self.exact_version().map(|version| version.to_string()))
+    }
+
+    /// The version installing the request gets: exactly the one it names, else the one [`resolve`](Self::resolve)
+    /// picks
+    pub fn install_version<'a>(&self, installed: impl IntoIterator<Item = &'a str>) -> Option<String> {
+        self.exact_version()
+            .map(|version| version.to_string())
+            .or_else(|| self.resolve(installed))
+    }
+
+    /// Whether `version` is what installing the request asks for: exactly the version it names, else any it
+    /// accepts
+    pub fn installs(&self, version: &str) -> bool {
+        match self.exact_version() {
+            Some(wanted) => semver::Version::parse(version.trim_start_matches('v')).is_ok_and(|version| version == wanted),
+            None => self.matches(version),
+        }
+    }
+
+    /// The one version `0.28.1` or `=0.28.1` names
+    fn exact_version(&self) -> Option<semver::Version> {
+        match self {
+            VersionRequest::Version(version) => Some(version.clone()),
+            VersionRequest::Range(range) => match range.comparators.as_slice() {
+                [semver::Comparator {
+                    op: semver::Op::Exact,
+                    major,
+                    minor: Some(minor),
+                    patch: Some(patch),
+                    pre,
+                }] => Some(semver::Version {
+                    major: *major,
+                    minor: *minor,
+                    patch: *patch,
+                    pre: pre.clone(),
+                    build: semver::BuildMetadata::EMPTY,
+                }),
+                _ => None,
+            },
+            VersionRequest::LatestCompatible => None,
+        }
+    }
+}
+
+/// `^version`: the same major version, or the same minor while the major is 0
+fn caret(version: &semver::Version) -> semver::VersionReq {
+    semver::VersionReq {
+        comparators: vec![semver::Comparator {
+            op: semver::Op::Caret,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: Some(version.patch),
+            pre: version.pre.clone(),
+        }],
+    }
+}
+
+impl FromStr for VersionRequest {
+    type Err = CliError;
+
+    fn from_str(request: &str) -> std::result::Result<Self, Self::Err> {
+        let request = request.trim();
+        if request == LATEST_COMPATIBLE {
+            return Ok(VersionRequest::LatestCompatible);
+        }
+        if let Ok(version) = semver::Version::parse(request.trim_start_matches('v')) {
+            return Ok(VersionRequest::Version(version));
+        }
+        semver::VersionReq::parse(request)
+            .map(VersionRequest::Range)
+            .map_err(|e| CliError::InvalidPklVersion {
+                version: request.to_string(),
+                reason: e.to_string(),
+            })
+    }
+}
+
+impl fmt::Display for VersionRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionRequest::Version(version) => write!(f, "{}", version),
+            VersionRequest::Range(range) => write!(f, "{}", range),
+            VersionRequest::LatestCompatible => write!(f, "{}", LATEST_COMPATIBLE),
+        }
+    }
+}
+
+/// A Pkl CLI version installed by spklr
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledPkl {
+    pub version: String,
+    /// The `pkl` executable
+    pub path: PathBuf,
+    /// The version's directory, removed on uninstall
+    pub dir: PathBuf,
+}
+
+/// Where the version asked for comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSource {
+    /// Pinned by a project file
+    Pinned(PathBuf),
+    /// The default set with `spklr pkl-me use`
+    Default,
+}
+
+impl fmt::Display for VersionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionSource::Pinned(file) => write!(f, "pinned by {}", file.display()),
+            VersionSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// The Pkl version a project asks for, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestedVersion {
+    /// As written, which may be a range
+    pub version: String,
+    pub source: VersionSource,
+}
+
+impl RequestedVersion {
+    pub fn request(&self) -> Result<VersionRequest> {
+        self.version
+            .parse()
+            .map_err(|e| miette::Report::new(e).wrap_err(format!("Pkl version {}", self.source)))
+    }
+}
+
+/// The Pkl CLI versions installed in a tools directory, normally ~/.moon/tools/pkl
+#[derive(Debug, Clone)]
+pub struct PklVersions {
+    tools_dir: PathBuf,
+}
+
+impl PklVersions {
+    /// Versions installed in `tools_dir`, one directory per version
+    pub fn new(tools_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            tools_dir: tools_dir.into(),
+        }
+    }
+
+    /// Versions installed in ~/.moon/tools/pkl
+    pub fn open() -> Result<Self> {
+        let home_dir = dirs::home_dir().ok_or_else(|| {
+            miette::Report::new(CliError::Generic(
+                "Could not determine home directory".to_string(),
+            ))
+        })?;
+        Ok(Self::new(home_dir.join(".moon").join("tools").join("pkl")))
+    }
+
+    pub fn tools_dir(&self) -> &Path {
+        &self.tools_dir
+    }
+
+    /// The directory `version` is (or would be) installed in
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.tools_dir.join(version)
+    }
+
+    /// `version`, if it's installed
+    pub fn get(&self, version: &str) -> Option<InstalledPkl> {
+        // a version names a directory right under the tools directory, and nothing else
+        let plain = !version.is_empty()
+            && !version.starts_with('.')
+            && version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'));
+        if !plain {
+            return None;
+        }
+
+        let dir = self.version_dir(version);
+        let path = crate::pkl_tooling::find_pkl_in(&dir).ok()?;
+        Some(InstalledPkl {
+            version: version.to_string(),
+            path,
+            dir,
+        })
+    }
+
+    /// Every installed version, newest first
+    pub fn installed(&self) -> Result<Vec<InstalledPkl>> {
+        if !self.tools_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(&self.tools_dir).map_err(|e| {
+            miette::Report::new(CliError::IoError {
+                context: format!("Reading {}", self.tools_dir.display()),
+                source: e,
+            })
+        })?;
+        let mut installed: Vec<InstalledPkl> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+            .filter_map(|entry| self.get(&entry.file_name().to_string_lossy()))
+            .collect();
+        installed.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        Ok(installed)
+    }
+
+    /// The default version, if one is set
+    pub fn default_version(&self) -> Result<Option<String>> {
+        let file = self.tools_dir.join(DEFAULT_VERSION_FILE);
+        if !file.exists() {
+            return Ok(None);
+        }
+        read_version_file(&file)
+    }
+
+    /// Make the installed `version` the default
+    pub fn set_default(&self, version: &str) -> Result<InstalledPkl> {
+        let installed = self.require(version)?;
+        let file = self.tools_dir.join(DEFAULT_VERSION_FILE);
+        std::fs::write(&file, format!("{}\n", version)).map_err(|e| {
+            miette::Report::new(CliError::IoError {
+                context: format!("Writing {}", file.display()),
+                source: e,
+            })
+        })?;
+        Ok(installed)
+    }
+
+    /// Remove the installed `version`, clearing the default if it was that
+    pub fn uninstall(&self, version: &str) -> Result<InstalledPkl> {
+        let installed = self.require(version)?;
+        std::fs::remove_dir_all(&installed.dir).map_err(|e| {
+            miette::Report::new(CliError::IoError {
+                context: format!("Removing {}", installed.dir.display()),
+                source: e,
+            })
+        })?;
+
+        if self.default_version()?.as_deref() == Some(version) {
+            let file = self.tools_dir.join(DEFAULT_VERSION_FILE);
+            std::fs::remove_file(&file).map_err(|e| {
+                miette::Report::new(CliError::IoError {
+                    context: format!("Removing {}", file.display()),
+                    source: e,
+                })
+            })?;
+        }
+        Ok(installed)
+    }
+
+    /// The version to use in `dir`: the one pinned there, or else the default
+    pub fn requested_version(&self, dir: &Path) -> Result<Option<RequestedVersion>> {
+        if let Some(pinned) = find_pinned_version(dir)? {
+            return Ok(Some(pinned));
+        }
+        Ok(self.default_version()?.map(|version| RequestedVersion {
+            version,
+            source: VersionSource::Default,
+        }))
+    }
+
+    /// The newest installed version `request` accepts
+    pub fn find(&self, request: &VersionRequest) -> Result<Option<InstalledPkl>> {
+        Ok(self
+            .installed()?
+            .into_iter()
+            .find(|pkl| request.matches(&pkl.version)))
+    }
+
+    fn require(&self, version: &str) -> Result<InstalledPkl> {
+        self.get(version).ok_or_else(|| {
+            miette::Report::new(CliError::PklVersionNotInstalled {
+                version: version.to_string(),
+            })
+        })
+    }
+}
+
+/// The Pkl version pinned for `dir`, by the nearest `.pkl-version` or `.prototools` with a `pkl` entry in it or
+/// above it. Within one directory, `.pkl-version` wins.
+pub fn find_pinned_version(dir: &Path) -> Result<Option<RequestedVersion>> {
+    for dir in dir.ancestors() {
+        let version_file = dir.join(PKL_VERSION_FILE);
+        if version_file.is_file()
+            && let Some(version) = read_version_file(&version_file)?
+        {
+            return Ok(Some(RequestedVersion {
+                version,
+                source: VersionSource::Pinned(version_file),
+            }));
+        }
+
+        let prototools = dir.join(PROTOTOOLS_FILE);
+        if prototools.is_file()
+            && let Some(version) = read_prototools_pin(&prototools)?
+        {
+            return Ok(Some(RequestedVersion {
+                version,
+                source: VersionSource::Pinned(prototools),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Pin `version`, a version, range or `latest-compatible`, for `dir` by writing its `.pkl-version`
+pub fn pin_version(dir: &Path, version: &str) -> Result<PathBuf> {
+    version.parse::<VersionRequest>()?;
+    let file = dir.join(PKL_VERSION_FILE);
+    std::fs::write(&file, format!("{}\n", version)).map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Writing {}", file.display()),
+            source: e,
+        })
+    })?;
+    Ok(file)
+}
+
+/// Order versions by their numeric parts, so `0.28.10` comes after `0.28.9`
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    }
+    parts(a).cmp(&parts(b)).then_with(|| a.cmp(b))
+}
+
+/// The first non-blank line of a version file
+fn read_version_file(file: &Path) -> Result<Option<String>> {
+    let contents = read(file)?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string))
+}
+
+/// The `pkl` entry of a `.prototools` file
+fn read_prototools_pin(file: &Path) -> Result<Option<String>> {
+    let contents = read(file)?;
+    let table: toml::Table = toml::from_str(&contents).map_err(|e| {
+        miette::Report::new(CliError::Generic(format!(
+            "Failed to parse {}: {}",
+            file.display(),
+            e
+        )))
+    })?;
+    Ok(table
+        .get("pkl")
+        .and_then(toml::Value::as_str)
+        .map(|version| version.trim().to_string()))
+}
+
+fn read(file: &Path) -> Result<String> {
+    std::fs::read_to_string(file).map_err(|e| {
+        miette::Report::new(CliError::IoError {
+            context: format!("Reading {}", file.display()),
+            source: e,
+        })
+    })
+}
@@ -1,5 +1,5 @@
 use miette::Diagnostic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Main CLI error type with rich diagnostics
@@ -64,6 +64,42 @@ pub enum CliError {
         help: Option<String>,
     },
 
+    /// A Pkl version that isn't installed was asked for
+    #[error("Pkl CLI {version} is not installed")]
+    #[diagnostic(
+        code(cli::pkl_version_not_installed),
+        help("Install it with `spklr pkl-me pkl --version {version}`, or see what is installed with `spklr pkl-me list`")
+    )]
+    PklVersionNotInstalled { version: String },
+
+    /// A requested Pkl version that isn't a version, range or `latest-compatible`
+    #[error("Invalid Pkl version `{version}`: {reason}")]
+    #[diagnostic(
+        code(cli::invalid_pkl_version),
+        help("Use a version like 0.28.1, a range like 0.28, ^0.28 or >=0.27, <0.29, or latest-compatible")
+    )]
+    InvalidPklVersion { version: String, reason: String },
+
+    /// No installed or tested Pkl version satisfies a range
+    #[error("No known Pkl version matches {request}")]
+    #[diagnostic(
+        code(cli::unresolved_pkl_version),
+        help("Tested versions: {}. Give a complete version, like 0.28.1, to install one outside them", .tested.join(", "))
+    )]
+    UnresolvedPklVersion { request: String, tested: Vec<String> },
+
+    /// A downloaded archive didn't match its expected SHA-256
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    #[diagnostic(
+        code(cli::checksum_mismatch),
+        help("The download was corrupted or tampered with, and nothing was installed. Try again, or check the value given to --checksum")
+    )]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
     /// Pkl execution failed
     #[error("Pkl CLI execution failed: {command}")]
     #[diagnostic(
@@ -76,6 +112,82 @@ pub enum CliError {
         help: Option<String>,
     },
 
+    /// Pkl reported an error evaluating a module
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PklEvaluationFailed(Box<crate::pkl_errors::PklEvaluationError>),
+
+    /// A Pkl or proto command ran past its timeout and was killed
+    #[error("`{command}` timed out after {elapsed:.1?}")]
+    #[diagnostic(
+        code(cli::process_timeout),
+        help("The command was killed. Raise the limit with SPKLR_PKL_TIMEOUT=<seconds> if it needs longer")
+    )]
+    ProcessTimeout {
+        command: String,
+        elapsed: std::time::Duration,
+    },
+
+    /// Pkl source could not be parsed
+    #[error("Failed to parse Pkl: {message}")]
+    #[diagnostic(
+        code(cli::pkl_parse_error),
+        help("Only class, typealias and property declarations are read; check the syntax near the highlighted location")
+    )]
+    PklParseError {
+        message: String,
+        #[source_code]
+        src: miette::NamedSource<String>,
+        #[label("here")]
+        span: miette::SourceSpan,
+    },
+
+    /// Static Pkl (PCF) source could not be parsed
+    #[error("Failed to parse Pkl: {message}")]
+    #[diagnostic(
+        code(cli::pcf_parse_error),
+        help("Only static Pkl is read: literal values, objects, listings and mappings, with an optional `amends` header")
+    )]
+    PcfParseError {
+        message: String,
+        #[source_code]
+        src: miette::NamedSource<String>,
+        #[label("here")]
+        span: miette::SourceSpan,
+    },
+
+    /// A value couldn't be written as Pkl
+    #[error("Failed to serialize to Pkl: {reason}")]
+    #[diagnostic(
+        code(cli::pkl_serialize_error),
+        help("Pkl modules hold properties, so the top-level value must be a struct or a map with string keys")
+    )]
+    PklSerializeError { reason: String },
+
+    /// A Pkl value didn't match the type it was read into
+    #[error("Failed to deserialize Pkl: {reason}")]
+    #[diagnostic(
+        code(cli::pkl_deserialize_error),
+        help("Check that property names and value types match the configuration's schema")
+    )]
+    PklDeserializeError { reason: String },
+
+    /// Schema document could not be imported
+    #[error("Failed to import schema from {source_name}: {reason}")]
+    #[diagnostic(
+        code(cli::schema_import_failed),
+        help("Check that the document is a valid JSON Schema (draft-07 or 2020-12) or a serialized schematic TypeMap")
+    )]
+    SchemaImportFailed { source_name: String, reason: String },
+
+    /// The requested root type isn't in the type map
+    #[error("Root type not found: {name}")]
+    #[diagnostic(
+        code(cli::root_type_not_found),
+        help("Available types: {}", .available.join(", "))
+    )]
+    RootTypeNotFound { name: String, available: Vec<String> },
+
     /// Network/HTTP error during downloads
     #[error("Network error during download: {0}")]
     #[diagnostic(
@@ -112,12 +224,66 @@ pub enum CliError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    /// A config file failed validation; each problem points at its setting in the source
+    #[error("{source_name} is not a valid {config_type} configuration")]
+    #[diagnostic(
+        code(cli::invalid_config),
+        help("Fix the highlighted settings; https://moonrepo.dev/docs/config describes what each one accepts")
+    )]
+    InvalidConfig {
+        source_name: String,
+        config_type: crate::types::MoonConfig,
+        #[related]
+        problems: Vec<ConfigProblem>,
+    },
+
+    /// Some of the files given to `validate` weren't valid
+    #[error("{failed} of {total} configuration files failed validation")]
+    #[diagnostic(code(cli::validation_failed), help("Each failing file is reported above"))]
+    ValidationFailed { failed: usize, total: usize },
+
+    /// Some of the project configs in a workspace couldn't be converted
+    #[error("{failed} of {total} configuration files failed to convert")]
+    #[diagnostic(code(cli::conversion_failed), help("The summary above says what went wrong with each"))]
+    ConversionFailed { failed: usize, total: usize },
+
+    /// The config type couldn't be told from the file's path or settings
+    #[error("Cannot tell which Moon configuration {path} is")]
+    #[diagnostic(
+        code(cli::unknown_config_type),
+        help("Pass --config-type: project, workspace, template, toolchain, task or inherited-tasks")
+    )]
+    UnknownConfigType { path: PathBuf },
+
+    /// A config `extends` something that can't be read
+    #[error("Cannot follow `extends: {target}` in {path}: {reason}")]
+    #[diagnostic(code(cli::unresolved_extends))]
+    UnresolvedExtends {
+        target: String,
+        path: PathBuf,
+        reason: String,
+    },
+
     /// Generic error wrapper
     #[error("Error: {0}")]
     #[diagnostic(code(cli::generic_error))]
     Generic(String),
 }
 
+/// One problem found while validating a config file
+#[derive(Error, Diagnostic, Debug)]
+#[error("{path}: {message}")]
+#[diagnostic(code(cli::config_problem))]
+pub struct ConfigProblem {
+    /// Dotted path to the setting, `.` for the config itself
+    pub path: String,
+    pub message: String,
+    #[source_code]
+    pub src: miette::NamedSource<String>,
+    #[label("here")]
+    pub span: miette::SourceSpan,
+}
+
 /// Result type alias for CLI operations
 pub type Result<T> = miette::Result<T, CliError>;
 
@@ -132,30 +298,44 @@ pub fn io_error_with_context<T>(
 }
 
 /// Helper function to create Pkl execution errors with context
+///
+/// Output in Pkl's error format becomes a [`CliError::PklEvaluationFailed`] pointing at the failing source;
+/// anything else is kept as it was printed.
 pub fn pkl_execution_error(
     command: impl Into<String>,
     stderr: impl Into<String>,
     help: Option<String>,
 ) -> CliError {
-    CliError::PklExecutionFailed {
-        command: command.into(),
-        stderr: stderr.into(),
-        help,
+    let stderr = stderr.into();
+    match crate::pkl_errors::parse_pkl_error(&stderr) {
+        Some(error) => error.into_cli_error(command, help),
+        None => CliError::PklExecutionFailed {
+            command: command.into(),
+            stderr,
+            help,
+        },
+    }
+}
+
+/// Helper function to create validation errors from any error source
+pub fn validation_error(source: impl std::error::Error + Send + Sync + 'static) -> CliError {
+    CliError::ValidationError {
+        source: Box::new(source),
     }
 }
 
 /// Helper function to check if a path exists and is readable
-pub fn ensure_file_exists(path: &PathBuf) -> Result<()> {
+pub fn ensure_file_exists(path: &Path) -> Result<()> {
     if !path.exists() {
-        return Err(CliError::FileNotFound { path: path.clone() });
+        return Err(CliError::FileNotFound { path: path.to_path_buf() });
     }
     Ok(())
 }
 
 /// Helper function to check if output file can be written
-pub fn ensure_output_writable(path: &PathBuf, force: bool) -> Result<()> {
+pub fn ensure_output_writable(path: &Path, force: bool) -> Result<()> {
     if path.exists() && !force {
-        return Err(CliError::OutputFileExists { path: path.clone() });
+        return Err(CliError::OutputFileExists { path: path.to_path_buf() });
     }
     Ok(())
 }
@@ -167,6 +347,24 @@ impl From<reqwest::Error> for CliError {
     }
 }
 
+/// Lets [`CliError`] be the error type of the Pkl serializer
+impl serde::ser::Error for CliError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        CliError::PklSerializeError {
+            reason: msg.to_string(),
+        }
+    }
+}
+
+/// Lets [`CliError`] be the error type of the Pkl deserializer
+impl serde::de::Error for CliError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        CliError::PklDeserializeError {
+            reason: msg.to_string(),
+        }
+    }
+}
+
 /// Convert from anyhow::Error
 impl From<anyhow::Error> for CliError {
     fn from(err: anyhow::Error) -> Self {
@@ -73,26 +73,42 @@ impl FromStr for TemplateFormat {
 }
 
 
+/// Schema and configuration format. `Yaml` and `Toml` are configuration formats only; they have no schema.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SchemaFormat {
     Pkl,
     Json,
+    Yaml,
+    Toml,
     Typescript,
 }
 
 impl SchemaFormat {
     pub fn all_supported_extensions() -> Vec<&'static str> {
-        vec!["pkl", "json", "ts"]
+        vec!["pkl", "pcf", "json", "yml", "yaml", "toml", "ts"]
     }
 
     pub fn is_supported_extension(&self, ext: &str) -> bool {
         Self::all_supported_extensions().contains(&ext)
     }
 
+    /// The extension files in this format get, as Moon names them (`moon.yml`, `moon.pkl`)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SchemaFormat::Pkl => "pkl",
+            SchemaFormat::Json => "json",
+            SchemaFormat::Yaml => "yml",
+            SchemaFormat::Toml => "toml",
+            SchemaFormat::Typescript => "ts",
+        }
+    }
+
     pub fn to_schematic(&self) -> Format {
         match self {
             SchemaFormat::Pkl => Format::Pkl,
             SchemaFormat::Json => Format::Json,
+            SchemaFormat::Yaml => Format::Yaml,
+            SchemaFormat::Toml => Format::Toml,
             SchemaFormat::Typescript => Format::None,
         }
     }
@@ -103,6 +119,8 @@ impl Display for SchemaFormat {
         match self {
             SchemaFormat::Json => write!(f, "json"),
             SchemaFormat::Pkl => write!(f, "pkl"),
+            SchemaFormat::Yaml => write!(f, "yaml"),
+            SchemaFormat::Toml => write!(f, "toml"),
             SchemaFormat::Typescript => write!(f, "typescript"),
         }
     }
@@ -115,10 +133,45 @@ impl FromStr for SchemaFormat {
         match s.to_lowercase().as_str() {
             "json" | "jsonschema" | "json-schema" | "json_schema" => Ok(SchemaFormat::Json),
             "pkl" | "pklr" | "pcf" => Ok(SchemaFormat::Pkl),
+            "yaml" | "yml" | "y" => Ok(SchemaFormat::Yaml),
+            "toml" | "t" => Ok(SchemaFormat::Toml),
             "typescript" | "ts" => Ok(SchemaFormat::Typescript),
             _ => Err(CliError::UnsupportedFormat {
                 format: s.to_string(),
-                available: vec!["json", "pkl", "typescript"],
+                available: vec!["json", "pkl", "yaml", "toml", "typescript"],
+            }),
+        }
+    }
+}
+
+/// Where a [`TypeMap`](crate::types::TypeMap) comes from when it isn't a Moon config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeSource {
+    /// A JSON Schema document (draft-07 or 2020-12)
+    JsonSchema,
+    /// A `TypeMap` serialized with schematic's `schema_serde` feature, as JSON or YAML
+    Schematic,
+}
+
+impl Display for TypeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeSource::JsonSchema => write!(f, "json-schema"),
+            TypeSource::Schematic => write!(f, "schematic"),
+        }
+    }
+}
+
+impl FromStr for TypeSource {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json-schema" | "jsonschema" | "json_schema" | "json" | "js" => Ok(TypeSource::JsonSchema),
+            "schematic" | "typemap" | "type-map" | "type_map" | "types" => Ok(TypeSource::Schematic),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["json-schema", "schematic"],
             }),
         }
     }
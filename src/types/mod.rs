@@ -5,9 +5,9 @@ pub mod moon;
 pub mod pkl;
 
 pub use cli::CliFlag;
-pub use error::{CliError, InternalError, Result, ensure_file_exists, ensure_output_writable, pkl_execution_error};
-pub use formats::{SchemaFormat};
-pub use moon::{LoadedConfig, MoonConfig};
+pub use error::{CliError, ConfigProblem, InternalError, Result, ensure_file_exists, ensure_output_writable, pkl_execution_error, validation_error};
+pub use formats::{SchemaFormat, TemplateFormat, TypeSource};
+pub use moon::{Confidence, LoadedConfig, MoonConfig, TypeInference, UnknownConfig};
 pub use pkl::{
-    ConfigTranslation, EnumTranslation, OpenStructs, OptionalFormat, PropertyDefault, TypeMap,
+    ConfigTranslation, EnumTranslation, ModuleLayout, OpenStructs, OptionalFormat, PropertyDefault, TupleTranslation, TypeMap,
 };
@@ -1,8 +1,11 @@
-use crate::types::{CliError, InternalError, SchemaFormat, TypeMap};
-use moon_config::{ProjectConfig, TaskConfig, TemplateConfig, ToolchainConfig, WorkspaceConfig};
-use schematic_types::SchemaType;
+use crate::custom_types::type_map_of;
+use crate::types::{CliError, InternalError, SchemaFormat, TypeMap, validation_error};
+use moon_config::{InheritedTasksConfig, ProjectConfig, TaskConfig, TemplateConfig, ToolchainConfig, WorkspaceConfig};
+use schematic::{Config, ConfigLoader, Format};
+use schematic_types::{Schema, SchemaType};
 use serde_json::Value;
 use std::collections::HashSet;
+use std::path::Path;
 use std::str::FromStr;
 
 /// Represents supported Moon config formats.
@@ -17,14 +20,15 @@ pub enum MoonConfigFormat {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum MoonType {
     ProjectConfig(ProjectConfig),
     WorkspaceConfig(WorkspaceConfig),
     TemplateConfig(TemplateConfig),
     ToolchainConfig(ToolchainConfig),
     TaskConfig(TaskConfig),
+    InheritedTasksConfig(InheritedTasksConfig),
 }
-//todo  TODO add a function to infer a type from a loaded config
 
 /// Unknown configuration that preserves structure and format information
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -66,6 +70,14 @@ impl UnknownConfig {
         }
     }
 
+    /// Create an empty config that names its root type
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
     /// Create with format information
     pub fn with_format(content: Value, format: SchemaFormat) -> Self {
         Self {
@@ -75,30 +87,110 @@ impl UnknownConfig {
             name: None,
         }
     }
+
+    /// Fill in `type_hint` with the Moon config type the content looks most like, if any
+    pub fn with_type_hint(mut self) -> Self {
+        self.hint_type();
+        self
+    }
+
+    /// Infer which Moon config type the content is, keeping it as the `type_hint`
+    pub fn hint_type(&mut self) -> Option<TypeInference> {
+        let inference = self.infer_type();
+        self.type_hint = inference.as_ref().map(|inference| inference.config_type.to_string());
+        inference
+    }
+
+    /// Infer which Moon config type the content is, from its settings
+    pub fn infer_type(&self) -> Option<TypeInference> {
+        MoonConfig::infer_from_content(&self.content)
+    }
+}
+
+/// How sure a [`TypeInference`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// More than one type fits the settings (or none does), so the best match was picked
+    Low,
+    /// Only this type fits all the settings
+    High,
+    /// The file's name or its `$schema` says which type it is
+    Certain,
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::High => write!(f, "high"),
+            Confidence::Certain => write!(f, "certain"),
+        }
+    }
+}
+
+/// A Moon config type inferred from a config file's path or content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeInference {
+    pub config_type: MoonConfig,
+    pub confidence: Confidence,
+    /// Every type that fits, best first; with [`Confidence::Low`] these are the alternatives
+    pub candidates: Vec<MoonConfig>,
+}
+
+impl TypeInference {
+    /// A type known for sure, from a file's name or `$schema`
+    pub fn certain(config_type: MoonConfig) -> Self {
+        Self {
+            config_type,
+            confidence: Confidence::Certain,
+            candidates: vec![config_type],
+        }
+    }
 }
 
 /// Strongly-typed configuration wrapper
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum LoadedConfig {
     Project(ProjectConfig),
     Workspace(WorkspaceConfig),
     Template(TemplateConfig),
     Toolchain(ToolchainConfig),
     Task(TaskConfig),
+    InheritedTasks(InheritedTasksConfig),
     Unknown(UnknownConfig),
 }
 
 /// Enum to hold any of the config types
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum ConfigValue {
     Project(ProjectConfig),
     Workspace(WorkspaceConfig),
     Template(TemplateConfig),
     Toolchain(ToolchainConfig),
     Task(TaskConfig),
+    InheritedTasks(InheritedTasksConfig),
 }
 
 impl LoadedConfig {
+    /// Load a config of the given type from source code with schematic's `ConfigLoader`, which validates
+    /// it and fills in defaults
+    pub fn from_code(config_type: MoonConfig, code: impl Into<String>, format: Format) -> Result<Self, CliError> {
+        let code = code.into();
+        match config_type {
+            MoonConfig::Project => load_code(code, format).map(LoadedConfig::Project),
+            MoonConfig::Workspace => load_code(code, format).map(LoadedConfig::Workspace),
+            MoonConfig::Toolchain => load_code(code, format).map(LoadedConfig::Toolchain),
+            MoonConfig::Template => load_code(code, format).map(LoadedConfig::Template),
+            MoonConfig::Task => load_code(code, format).map(LoadedConfig::Task),
+            MoonConfig::InheritedTasks => load_code(code, format).map(LoadedConfig::InheritedTasks),
+            MoonConfig::All => Err(CliError::Generic(
+                "Cannot load config with type 'All' - specify a specific config type".to_string(),
+            )),
+        }
+    }
+
     /// Get the config type name for error reporting
     pub fn config_type_name(&self, schemas: Option<TypeMap>) -> String {
         match self {
@@ -107,6 +199,7 @@ impl LoadedConfig {
             LoadedConfig::Template(_) => "template".to_string(),
             LoadedConfig::Toolchain(_) => "toolchain".to_string(),
             LoadedConfig::Task(_) => "task".to_string(),
+            LoadedConfig::InheritedTasks(_) => "inherited-tasks".to_string(),
             LoadedConfig::Unknown(config) if config.name.is_some() => {
                 config.name.clone().unwrap_or_else(|| "unknown".to_string())
             }
@@ -142,18 +235,11 @@ impl LoadedConfig {
             return keys[0].to_string();
         }
 
-        // Find referenced schema names
-        let referenced_names: HashSet<&str> = schemas
-            .values()
-            .filter(|schema| schema.deprecated.is_none() && schema.ty.is_reference())
-            .filter_map(|schema| {
-                if let SchemaType::Reference(name) = &schema.ty {
-                    Some(name.as_str())
-                } else {
-                    None
-                }
-            })
-            .collect();
+        // Find referenced schema names, including references nested in fields, items and variants
+        let mut referenced_names: HashSet<&str> = HashSet::new();
+        for schema in schemas.values().filter(|schema| schema.deprecated.is_none()) {
+            collect_references(&schema.ty, &mut referenced_names);
+        }
 
         // Try to find a non-deprecated struct that isn't referenced by others
         if let Some(root_name) = keys.iter().find(|&name| {
@@ -187,6 +273,7 @@ impl LoadedConfig {
             LoadedConfig::Toolchain(_) => Ok(MoonConfig::Toolchain),
             LoadedConfig::Template(_) => Ok(MoonConfig::Template),
             LoadedConfig::Task(_) => Ok(MoonConfig::Task),
+            LoadedConfig::InheritedTasks(_) => Ok(MoonConfig::InheritedTasks),
             LoadedConfig::Unknown(_) => Err(InternalError::ValueError {
                 message: "Cannot convert UnknownConfig to MoonConfig".to_string(),
                 context: "LoadedConfig::to_moon_config".to_string(),
@@ -202,6 +289,7 @@ impl LoadedConfig {
             LoadedConfig::Template(_) => "TemplateConfig",
             LoadedConfig::Toolchain(_) => "ToolchainConfig",
             LoadedConfig::Task(_) => "TaskConfig",
+            LoadedConfig::InheritedTasks(_) => "InheritedTasksConfig",
             LoadedConfig::Unknown(_) => "UnknownConfig",
         }
     }
@@ -214,6 +302,7 @@ impl LoadedConfig {
             LoadedConfig::Template(config) => Ok(MoonType::TemplateConfig(config.clone())),
             LoadedConfig::Toolchain(config) => Ok(MoonType::ToolchainConfig(config.clone())),
             LoadedConfig::Task(config) => Ok(MoonType::TaskConfig(config.clone())),
+            LoadedConfig::InheritedTasks(config) => Ok(MoonType::InheritedTasksConfig(config.clone())),
             LoadedConfig::Unknown(_config) => Err(InternalError::ValueError {
                 message: "Cannot convert UnknownConfig to MoonType".to_string(),
                 context: "LoadedConfig::moon_type".to_string(),
@@ -229,6 +318,7 @@ impl LoadedConfig {
             LoadedConfig::Template(config) => Ok(ConfigValue::Template(config.clone())),
             LoadedConfig::Toolchain(config) => Ok(ConfigValue::Toolchain(config.clone())),
             LoadedConfig::Task(config) => Ok(ConfigValue::Task(config.clone())),
+            LoadedConfig::InheritedTasks(config) => Ok(ConfigValue::InheritedTasks(config.clone())),
             LoadedConfig::Unknown(_) => Err(InternalError::ValueError {
                 message: "Cannot extract config value from UnknownConfig".to_string(),
                 context: "LoadedConfig::get_config".to_string(),
@@ -237,9 +327,17 @@ impl LoadedConfig {
     }
 }
 
+fn load_code<T: Config>(code: String, format: Format) -> Result<T, CliError> {
+    let result = ConfigLoader::<T>::new()
+        .code(code, format)
+        .and_then(|loader| loader.load())
+        .map_err(validation_error)?;
+    Ok(result.config)
+}
+
 impl MoonConfigFormat {
     /// Get supported moon config formats for variants
-    fn supported_extensions(&self) -> Vec<&'static str> {
+    pub fn supported_extensions(&self) -> Vec<&'static str> {
         match self {
             // `pcf` is a static subset of Pkl.
             MoonConfigFormat::Pkl => vec!["pkl", "pcf"],
@@ -247,11 +345,11 @@ impl MoonConfigFormat {
         }
     }
 
-    fn is_supported_extension(&self, ext: &str) -> bool {
+    pub fn is_supported_extension(&self, ext: &str) -> bool {
         self.supported_extensions().contains(&ext)
     }
 
-    fn all_supported_extensions() -> Vec<&'static str> {
+    pub fn all_supported_extensions() -> Vec<&'static str> {
         vec!["pkl", "pcf", "yaml", "yml"]
     }
 }
@@ -288,6 +386,8 @@ pub enum MoonConfig {
     Toolchain,
     Template,
     Task,
+    /// Tasks every matching project inherits, from `.moon/tasks.*` and `.moon/tasks/**/*`
+    InheritedTasks,
     All, // Generate for all configuration types
 }
 
@@ -299,6 +399,7 @@ impl std::fmt::Display for MoonConfig {
             MoonConfig::Toolchain => write!(f, "toolchain"),
             MoonConfig::Template => write!(f, "template"),
             MoonConfig::Task => write!(f, "task"),
+            MoonConfig::InheritedTasks => write!(f, "inherited-tasks"),
             MoonConfig::All => write!(f, "all"),
         }
     }
@@ -314,6 +415,7 @@ impl FromStr for MoonConfig {
             "toolchain" => Ok(MoonConfig::Toolchain),
             "template" => Ok(MoonConfig::Template),
             "task" => Ok(MoonConfig::Task),
+            "inherited-tasks" | "tasks" => Ok(MoonConfig::InheritedTasks),
             "all" => Ok(MoonConfig::All),
             _ => Err(CliError::UnsupportedFormat {
                 format: s.to_string(),
@@ -335,17 +437,154 @@ impl MoonConfig {
             MoonConfig::Toolchain,
             MoonConfig::Template,
             MoonConfig::Task,
+            MoonConfig::InheritedTasks,
         ]
     }
 
+    /// Infer the config type of a file, from its path when that's conclusive and otherwise from its content
+    pub fn infer(path: Option<&Path>, content: &Value) -> Option<TypeInference> {
+        match path.and_then(MoonConfig::detect_from_path) {
+            Some(config_type) => Some(TypeInference::certain(config_type)),
+            None => MoonConfig::infer_from_content(content),
+        }
+    }
+
+    /// Infer the config type from a config's settings. A `$schema` pointing at one of Moon's schemas settles
+    /// it; otherwise the top-level keys are scored against each type's settings. Types missing any of the keys
+    /// don't fit, since Moon rejects unknown settings, and keys few types have count for more.
+    pub fn infer_from_content(content: &Value) -> Option<TypeInference> {
+        let settings = content.as_object()?;
+        if let Some(schema) = settings.get("$schema").and_then(Value::as_str)
+            && let Some(config_type) = MoonConfig::from_schema_url(schema)
+        {
+            return Some(TypeInference::certain(config_type));
+        }
+
+        let keys: Vec<&str> = settings.keys().map(String::as_str).filter(|key| *key != "$schema").collect();
+        let setting_names: Vec<(MoonConfig, HashSet<String>)> = MoonConfig::all_types()
+            .into_iter()
+            .map(|config_type| (config_type, config_type.setting_names()))
+            .collect();
+        let owners = |key: &str| setting_names.iter().filter(|(_, names)| names.contains(key)).count();
+
+        let mut scores: Vec<(MoonConfig, usize, f64)> = setting_names
+            .iter()
+            .map(|(config_type, names)| {
+                let unknown = keys.iter().filter(|key| !names.contains(**key)).count();
+                let weight: f64 = keys
+                    .iter()
+                    .filter(|key| names.contains(**key))
+                    .map(|key| 1.0 / owners(key) as f64)
+                    .sum();
+                (*config_type, unknown, weight)
+            })
+            .collect();
+        // fewest unknown keys first, then the most telling; ties keep `all_types` order
+        scores.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.total_cmp(&a.2)));
+
+        let fitting: Vec<MoonConfig> = scores
+            .iter()
+            .filter(|(_, unknown, _)| *unknown == 0)
+            .map(|(config_type, ..)| *config_type)
+            .collect();
+        let confidence = if fitting.len() == 1 { Confidence::High } else { Confidence::Low };
+        let candidates = if fitting.is_empty() {
+            scores.iter().map(|(config_type, ..)| *config_type).collect()
+        } else {
+            fitting
+        };
+        Some(TypeInference {
+            config_type: scores.first()?.0,
+            confidence,
+            candidates,
+        })
+    }
+
+    /// The config type whose schema Moon publishes at `url`, like `https://moonrepo.dev/schemas/project.json`
+    fn from_schema_url(url: &str) -> Option<MoonConfig> {
+        match url.rsplit('/').next()?.strip_suffix(".json")? {
+            "project" => Some(MoonConfig::Project),
+            "workspace" => Some(MoonConfig::Workspace),
+            "toolchain" => Some(MoonConfig::Toolchain),
+            "template" => Some(MoonConfig::Template),
+            "tasks" => Some(MoonConfig::InheritedTasks),
+            _ => None,
+        }
+    }
+
+    /// The schema types of this config type and the name of its root struct, `None` for `All`
+    pub fn schema_types(&self) -> Option<(TypeMap, &'static str)> {
+        Some(match self {
+            MoonConfig::Project => (type_map_of::<ProjectConfig>(), "ProjectConfig"),
+            MoonConfig::Workspace => (type_map_of::<WorkspaceConfig>(), "WorkspaceConfig"),
+            MoonConfig::Toolchain => (type_map_of::<ToolchainConfig>(), "ToolchainConfig"),
+            MoonConfig::Template => (type_map_of::<TemplateConfig>(), "TemplateConfig"),
+            MoonConfig::Task => (type_map_of::<TaskConfig>(), "TaskConfig"),
+            MoonConfig::InheritedTasks => (type_map_of::<InheritedTasksConfig>(), "InheritedTasksConfig"),
+            MoonConfig::All => return None,
+        })
+    }
+
+    /// The default instance of this config type, serialized, `None` for `All`. Schemas use it for the
+    /// `Listing` and `Mapping` defaults schematic doesn't record.
+    pub fn default_value(&self) -> Option<Value> {
+        match self {
+            MoonConfig::Project => serde_json::to_value(ProjectConfig::default()).ok(),
+            MoonConfig::Workspace => serde_json::to_value(WorkspaceConfig::default()).ok(),
+            MoonConfig::Toolchain => serde_json::to_value(ToolchainConfig::default()).ok(),
+            MoonConfig::Template => serde_json::to_value(TemplateConfig::default()).ok(),
+            MoonConfig::Task => serde_json::to_value(TaskConfig::default()).ok(),
+            MoonConfig::InheritedTasks => serde_json::to_value(InheritedTasksConfig::default()).ok(),
+            MoonConfig::All => None,
+        }
+    }
+
+    /// The top-level settings of this config type, as files name them
+    fn setting_names(&self) -> HashSet<String> {
+        let Some((types, name)) = self.schema_types() else {
+            return HashSet::new();
+        };
+        match types.get(name).map(|schema| &schema.ty) {
+            Some(SchemaType::Struct(structure)) => structure
+                .fields
+                .iter()
+                .filter(|(_, field)| !field.hidden)
+                .map(|(name, _)| name.clone())
+                .collect(),
+            _ => HashSet::new(),
+        }
+    }
+
+    /// Tell the config type from a file's well-known name: `moon.*`, `template.*`, and `workspace.*`,
+    /// `toolchain.*` and `tasks.*` directly in `.moon/`, or anything under `.moon/tasks/`. Those three names are
+    /// common elsewhere, so outside `.moon/` they say nothing and the content decides.
+    pub fn detect_from_path(path: &Path) -> Option<MoonConfig> {
+        let stem = path.file_stem()?.to_str()?;
+        let in_moon_dir = path.parent().and_then(Path::file_name).is_some_and(|dir| dir == ".moon");
+        match stem {
+            "moon" => Some(MoonConfig::Project),
+            "template" => Some(MoonConfig::Template),
+            "workspace" if in_moon_dir => Some(MoonConfig::Workspace),
+            "toolchain" if in_moon_dir => Some(MoonConfig::Toolchain),
+            "tasks" if in_moon_dir => Some(MoonConfig::InheritedTasks),
+            _ => path
+                .ancestors()
+                .skip(1)
+                .any(|dir| dir.ends_with(".moon/tasks"))
+                .then_some(MoonConfig::InheritedTasks),
+        }
+    }
+
+    /// The file name, without extension, a config type is kept in. Tasks have no file of their own, so `Task`
+    /// shares `tasks` with `InheritedTasks`, whose files hold them.
     pub fn basename(&self) -> Result<&'static str, InternalError> {
         match self {
             MoonConfig::Project => Ok("moon"),
             MoonConfig::Workspace => Ok("workspace"),
             MoonConfig::Toolchain => Ok("toolchain"),
             MoonConfig::Template => Ok("template"),
-            MoonConfig::Task => Ok("tasks"),
-            _ => Err(InternalError::ValueError {
+            MoonConfig::Task | MoonConfig::InheritedTasks => Ok("tasks"),
+            MoonConfig::All => Err(InternalError::ValueError {
               message: (r#"To get basenames for `all` configurations, iterate `MoonConfig.basename()` using `MoonConfig.all_types()`:
 
             ```rust
@@ -359,3 +598,43 @@ impl MoonConfig {
       }
     }
 }
+
+/// Collects the names of every type referenced from `ty`, however deeply nested
+fn collect_references<'a>(ty: &'a SchemaType, names: &mut HashSet<&'a str>) {
+    match ty {
+        SchemaType::Reference(name) => {
+            names.insert(name.as_str());
+        }
+        SchemaType::Array(array) => collect_nested_references(&array.items_type, names),
+        SchemaType::Object(object) => {
+            collect_nested_references(&object.key_type, names);
+            collect_nested_references(&object.value_type, names);
+        }
+        SchemaType::Struct(structure) => {
+            for field in structure.fields.values() {
+                collect_nested_references(&field.schema, names);
+            }
+        }
+        SchemaType::Tuple(tuple) => {
+            for item in &tuple.items_types {
+                collect_nested_references(item, names);
+            }
+        }
+        SchemaType::Union(union) => {
+            for variant in &union.variants_types {
+                collect_nested_references(variant, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Schematic nests named types in full rather than as references, so a name counts as a reference too
+fn collect_nested_references<'a>(schema: &'a Schema, names: &mut HashSet<&'a str>) {
+    match &schema.name {
+        Some(name) => {
+            names.insert(name.as_str());
+        }
+        None => collect_references(&schema.ty, names),
+    }
+}
@@ -169,7 +169,7 @@ pub enum OptionalFormat {
     /// In Pkl, `?` implies default `null`, though `null` can have a [default value](https://pkl-lang.org/main/current/language-reference/index.html#null-coalescing)
     #[default]
     Optional,
-    /// Optional with undefined: `prop: type|nothing = nothing`. You can use 'explicit' as shorthand.
+    /// Optional with an explicit default: `prop: type? = null`. You can use 'explicit' as shorthand.
     OptionalExplicitNothing,
 }
 
@@ -253,3 +253,89 @@ impl PropertyDefault {
         matches!(self, PropertyDefault::Optional)
     }
 }
+
+/// Defines how tuples with more than two items are translated to Pkl, which has no tuple type.
+///
+/// Two-item tuples are always a `Pair<A, B>`. Longer ones become a `Listing` (default) constrained to the tuple's length and the type at each index, or a generated class with one positional property per item (`_0`, `_1`, ...). The listing keeps `[a, b, c]`-shaped data as-is; the class reads better, but your data has to be objects.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TupleTranslation {
+    /// Example: `Listing(length == 3 && this[0] is String && this[1] is Int && this[2] is Boolean)`
+    #[default]
+    Listing,
+    /// Example: `open class Tuple0 { _0: String; _1: Int; _2: Boolean }`
+    Class,
+}
+
+impl FromStr for TupleTranslation {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "listing" | "list" | "l" | "0" => Ok(TupleTranslation::Listing),
+            "class" | "c" | "cls" | "positional" | "1" => Ok(TupleTranslation::Class),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["listing", "class"],
+            }),
+        }
+    }
+}
+
+impl Display for TupleTranslation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TupleTranslation::Listing => write!(f, "listing"),
+            TupleTranslation::Class => write!(f, "class"),
+        }
+    }
+}
+
+impl TupleTranslation {
+    /// Returns true if long tuples become generated classes.
+    pub fn as_class(&self) -> bool {
+        matches!(self, TupleTranslation::Class)
+    }
+}
+
+/// Controls whether the renderer produces one module or a package of modules.
+///
+/// `Single` (default) renders the whole `TypeMap` into one module. `Split` places each group of classes and typealiases into its own module, such as `Common.pkl`, `Tasks.pkl` and `Project.pkl`, and wires them together with `import`s and qualified type names. Use it when several schemas should share one set of types.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModuleLayout {
+    /// Everything in one module.
+    #[default]
+    Single,
+    /// One module per group of types, with cross-module imports.
+    Split,
+}
+
+impl FromStr for ModuleLayout {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "single" | "one" | "module" | "s" | "0" => Ok(ModuleLayout::Single),
+            "split" | "multi" | "multiple" | "package" | "pkg" | "m" | "1" => Ok(ModuleLayout::Split),
+            _ => Err(CliError::UnsupportedFormat {
+                format: s.to_string(),
+                available: vec!["single", "split"],
+            }),
+        }
+    }
+}
+
+impl Display for ModuleLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleLayout::Single => write!(f, "single"),
+            ModuleLayout::Split => write!(f, "split"),
+        }
+    }
+}
+
+impl ModuleLayout {
+    /// Returns true if types are split across multiple modules.
+    pub fn is_split(&self) -> bool {
+        matches!(self, ModuleLayout::Split)
+    }
+}
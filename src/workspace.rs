@@ -0,0 +1,280 @@
+//! Workspace Batches
+//!
+//! Converts or validates every project config in a Moon workspace at once. The projects come from the
+//! `projects` setting of `.moon/workspace.yml` (or `.pkl`, `.json`, `.toml`): its globs are expanded against the
+//! workspace root the way Moon does it, and its sources are taken as they are. Each project's config is then
+//! handled on its own tokio task, as many at a time as there are cores, and Pkl that needs evaluating goes to one
+//! shared `pkl server`.
+//!
+//! ```rust,ignore
+//! use space_pklr::workspace::{BatchAction, BatchOutput, find_project_configs, run_batch, summary_table};
+//!
+//! let root = Path::new(".");
+//! let configs = find_project_configs(root).await?;
+//! let action = BatchAction::Convert { to: SchemaFormat::Pkl, output: BatchOutput::Beside, force: false };
+//! let outcomes = run_batch(root, configs, action).await;
+//! println!("{}", summary_table(&outcomes));
+//! ```
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use moon_config::WorkspaceProjects;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use walkdir::WalkDir;
+
+use crate::_rewrite::{load_config_with_schematic, render_config_with_schematic};
+use crate::config_validator::validate_config_file_with_evaluator;
+use crate::pkl_evaluator::SharedEvaluator;
+use crate::types::{CliError, LoadedConfig, MoonConfig, SchemaFormat, ensure_output_writable};
+
+/// Config file names Moon looks for, in the order they're preferred
+const EXTENSIONS: [&str; 6] = ["yml", "yaml", "pkl", "pcf", "json", "toml"];
+
+/// Directories never searched for projects
+const SKIPPED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// What to do with each project config
+#[derive(Debug, Clone)]
+pub enum BatchAction {
+    /// Validate it
+    Validate,
+    /// Convert it to `to`, writing the result where `output` says
+    Convert {
+        to: SchemaFormat,
+        output: BatchOutput,
+        force: bool,
+    },
+}
+
+/// Where converted project configs go
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutput {
+    /// Nowhere; the conversion is only checked
+    None,
+    /// Beside the original: `apps/web/moon.pkl` for `apps/web/moon.yml`
+    Beside,
+    /// Under a directory, at the same path relative to the workspace root
+    Directory(PathBuf),
+}
+
+/// How one project config fared
+#[derive(Debug)]
+pub struct BatchOutcome {
+    /// The config, relative to the workspace root
+    pub config: PathBuf,
+    /// The file written, if any
+    pub result: Result<Option<PathBuf>, CliError>,
+}
+
+/// Find the workspace config in `root/.moon`
+pub fn find_workspace_config(root: &Path) -> Result<PathBuf, CliError> {
+    find_config_file(&root.join(".moon"), "workspace").ok_or_else(|| CliError::FileNotFound {
+        path: root.join(".moon/workspace.yml"),
+    })
+}
+
+/// Find the config file named `stem` in `dir`, whatever its format
+pub fn find_config_file(dir: &Path, stem: &str) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|extension| dir.join(format!("{stem}.{extension}")))
+        .find(|path| path.is_file())
+}
+
+/// Load the workspace config under `root` and return the config file of every project it lists, sorted.
+/// Projects without a config file are left out; Moon doesn't need one.
+pub async fn find_project_configs(root: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let workspace_path = find_workspace_config(root)?;
+    let LoadedConfig::Workspace(workspace) =
+        load_config_with_schematic(&workspace_path, MoonConfig::Workspace, None).await?
+    else {
+        unreachable!("workspace configs load as workspace configs");
+    };
+
+    let configs = expand_workspace_projects(root, &workspace.projects)?
+        .into_iter()
+        .filter_map(|dir| find_config_file(&dir, "moon"))
+        .collect();
+    Ok(configs)
+}
+
+/// The project directories `projects` names, sorted. Globs are relative to `root` and match directories, or
+/// `moon.yml`/`moon.pkl` files standing for theirs; those starting with `!` exclude what they match.
+pub fn expand_workspace_projects(root: &Path, projects: &WorkspaceProjects) -> Result<BTreeSet<PathBuf>, CliError> {
+    let (globs, sources): (&[String], Vec<&String>) = match projects {
+        WorkspaceProjects::Both(config) => (&config.globs, config.sources.values().collect()),
+        WorkspaceProjects::Globs(globs) => (globs, Vec::new()),
+        WorkspaceProjects::Sources(sources) => (&[], sources.values().collect()),
+    };
+
+    let mut dirs: BTreeSet<PathBuf> = sources
+        .into_iter()
+        .map(|source| root.join(source.trim_start_matches("./")))
+        .collect();
+    dirs.extend(expand_globs(root, globs)?);
+    Ok(dirs)
+}
+
+fn expand_globs(root: &Path, globs: &[String]) -> Result<BTreeSet<PathBuf>, CliError> {
+    let mut dirs = BTreeSet::new();
+    let (excludes, includes): (Vec<&String>, Vec<&String>) = globs.iter().partition(|glob| glob.starts_with('!'));
+    let includes: Vec<&str> = includes
+        .into_iter()
+        .map(|glob| glob.trim_start_matches("./"))
+        .filter(|glob| {
+            // the workspace root can be a project too
+            let is_root = matches!(*glob, "." | "");
+            if is_root {
+                dirs.insert(root.to_path_buf());
+            }
+            !is_root
+        })
+        .collect();
+    if includes.is_empty() {
+        return Ok(dirs);
+    }
+    let includes = glob_set(includes)?;
+    let excludes = glob_set(excludes.into_iter().map(|glob| glob[1..].trim_start_matches("./")))?;
+
+    let entries = WalkDir::new(root).min_depth(1).into_iter().filter_entry(|entry| {
+        let name = entry.file_name().to_string_lossy();
+        !(entry.file_type().is_dir() && (name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref())))
+    });
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        if !includes.is_match(relative) || excludes.is_match(relative) {
+            continue;
+        }
+        if entry.file_type().is_dir() {
+            dirs.insert(entry.path().to_path_buf());
+        } else if let Some(parent) = entry.path().parent()
+            && MoonConfig::detect_from_path(entry.path()) == Some(MoonConfig::Project)
+        {
+            dirs.insert(parent.to_path_buf());
+        }
+    }
+    Ok(dirs)
+}
+
+fn glob_set<'a>(globs: impl IntoIterator<Item = &'a str>) -> Result<GlobSet, CliError> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = GlobBuilder::new(glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|error| CliError::Generic(format!("Invalid project glob `{glob}`: {error}")))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|error| CliError::Generic(format!("Invalid project globs: {error}")))
+}
+
+/// Run `action` on the configs, one per core at a time, returning the outcomes in the order the configs were given
+pub async fn run_batch(root: &Path, configs: Vec<PathBuf>, action: BatchAction) -> Vec<BatchOutcome> {
+    let evaluator = SharedEvaluator::new();
+    // a workspace can have thousands of projects, and each one holds files open while it's handled
+    let workers = std::thread::available_parallelism().map_or(4, usize::from);
+    let permits = Arc::new(Semaphore::new(workers));
+    let mut tasks = JoinSet::new();
+    for (index, config) in configs.into_iter().enumerate() {
+        let permit = Arc::clone(&permits).acquire_owned().await.expect("the semaphore is never closed");
+        let root = root.to_path_buf();
+        let action = action.clone();
+        let evaluator = evaluator.clone();
+        tasks.spawn(async move {
+            let result = run_one(&root, &config, &action, &evaluator).await;
+            drop(permit);
+            let config = config.strip_prefix(&root).map(Path::to_path_buf).unwrap_or(config);
+            (index, BatchOutcome { config, result })
+        });
+    }
+
+    let mut outcomes = tasks.join_all().await;
+    if let Err(error) = evaluator.close().await {
+        tracing::debug!("Closing pkl server failed: {}", error);
+    }
+    outcomes.sort_by_key(|(index, _)| *index);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+async fn run_one(
+    root: &Path,
+    config: &Path,
+    action: &BatchAction,
+    evaluator: &SharedEvaluator,
+) -> Result<Option<PathBuf>, CliError> {
+    let loaded = validate_config_file_with_evaluator(config, Some(MoonConfig::Project), None, Some(evaluator)).await?;
+    let BatchAction::Convert { to, output, force } = action else {
+        return Ok(None);
+    };
+
+    let converted = render_config_with_schematic(&loaded, to.clone())?;
+    let beside = config.with_extension(to.extension());
+    let output_path = match output {
+        BatchOutput::None => return Ok(None),
+        BatchOutput::Beside => beside,
+        BatchOutput::Directory(dir) => dir.join(beside.strip_prefix(root).unwrap_or(&beside)),
+    };
+    if output_path == config {
+        return Err(CliError::Generic(format!(
+            "{} is already {to}; converting it would overwrite it",
+            config.display()
+        )));
+    }
+    ensure_output_writable(&output_path, *force)?;
+
+    if let Some(parent) = output_path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| CliError::IoError {
+            context: format!("Creating output directory: {}", parent.display()),
+            source: e,
+        })?;
+    }
+    tokio::fs::write(&output_path, converted).await.map_err(|e| CliError::IoError {
+        context: format!("Writing output file: {}", output_path.display()),
+        source: e,
+    })?;
+    Ok(Some(output_path))
+}
+
+/// A table with a row for each outcome: whether it worked, the config, and the file written or what went wrong
+pub fn summary_table(outcomes: &[BatchOutcome]) -> String {
+    let width = outcomes
+        .iter()
+        .map(|outcome| outcome.config.display().to_string().len())
+        .chain(["Project config".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut table = format!("    {:<width$}  Result\n", "Project config");
+    for outcome in outcomes {
+        let (status, result) = match &outcome.result {
+            Ok(Some(output)) => ("✅", format!("wrote {}", output.display())),
+            Ok(None) => ("✅", "ok".to_string()),
+            Err(error) => ("❌", error.to_string()),
+        };
+        table.push_str(&format!("{status}  {:<width$}  {result}\n", outcome.config.display().to_string()));
+    }
+
+    let failed = outcomes.iter().filter(|outcome| outcome.result.is_err()).count();
+    table.push_str(&format!("\n{} succeeded, {failed} failed", outcomes.len() - failed));
+    table
+}
+
+/// Print the full diagnostic of every failed outcome, returning how many there were
+pub fn report_failures(outcomes: Vec<BatchOutcome>) -> usize {
+    let mut failed = 0;
+    for outcome in outcomes {
+        if let Err(error) = outcome.result {
+            failed += 1;
+            eprintln!("{:?}", miette::Report::new(error));
+        }
+    }
+    failed
+}
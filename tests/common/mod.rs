@@ -0,0 +1,80 @@
+//! Helpers shared by the integration tests. Each test binary compiles its own copy and uses some of it.
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use tempfile::TempDir;
+
+/// A request the local HTTP stand-in got: its path, and where its `Range` starts if it asked for one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub path: String,
+    pub range: Option<u64>,
+}
+
+/// The requests the stand-in got, in order
+pub type Requests = Arc<Mutex<Vec<Request>>>;
+
+/// A local HTTP stand-in. Answers each request with whatever `respond` writes for it, given how many came before,
+/// then closes the connection. Returns the base URL and the requests it got.
+pub fn serve(respond: impl Fn(usize, &Request) -> Vec<u8> + Send + 'static) -> (String, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let requests: Requests = Arc::default();
+    let seen = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut range = None;
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                    range = value.trim().trim_end_matches('-').parse().ok();
+                }
+                line.clear();
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+            let request = Request { path, range };
+            let index = {
+                let mut seen = seen.lock().unwrap();
+                seen.push(request.clone());
+                seen.len() - 1
+            };
+            // the client may have given up on a dropped response already
+            let _ = stream.write_all(&respond(index, &request));
+        }
+    });
+    (base, requests)
+}
+
+/// A whole response with `status` (e.g. `404 Not Found`) and `body`
+pub fn response(status: &str, body: &[u8]) -> Vec<u8> {
+    let mut response =
+        format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Writes `content` to `path` under `root`, creating its directories
+pub fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// A temporary workspace holding `files`, by their path from its root
+pub fn workspace(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (path, content) in files {
+        write(dir.path(), path, content);
+    }
+    dir
+}
@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use space_pklr::commands::validate::{ValidateArgs, handle_validate};
+use space_pklr::config_validator::locate_setting;
+use space_pklr::{CliError, LoadedConfig, MoonConfig, SchemaFormat, validate_config, validate_config_file};
+
+/// The source text a problem's span covers
+fn spanned<'a>(content: &'a str, span: &miette::SourceSpan) -> &'a str {
+    &content[span.offset()..span.offset() + span.len()]
+}
+
+#[test]
+fn test_problems_point_at_settings() {
+    let content = r#"language: rust
+project:
+  channel: "general"
+tasks:
+  build:
+    command: ""
+"#;
+    let error = validate_config("moon.yml", content, MoonConfig::Project, &SchemaFormat::Yaml).unwrap_err();
+    let CliError::InvalidConfig { config_type, problems, .. } = error else {
+        panic!("expected an invalid config, got {error:?}");
+    };
+    assert_eq!(config_type, MoonConfig::Project);
+    assert_eq!(problems.len(), 2, "{problems:?}");
+
+    let channel = problems.iter().find(|problem| problem.path == "project.channel").unwrap();
+    assert!(channel.message.contains('#'));
+    assert_eq!(spanned(content, &channel.span), "channel");
+    assert_eq!(channel.span.offset(), content.find("channel").unwrap());
+
+    let command = problems.iter().find(|problem| problem.path == "tasks.build.command").unwrap();
+    assert_eq!(spanned(content, &command.span), "command");
+}
+
+#[test]
+fn test_unknown_settings_in_each_format() {
+    let sources = [
+        (SchemaFormat::Yaml, "tasks:\n  build:\n    command: make\n    colour: red\n"),
+        (SchemaFormat::Json, r#"{"tasks": {"build": {"command": "make", "colour": "red"}}}"#),
+        (SchemaFormat::Toml, "[tasks.build]\ncommand = \"make\"\ncolour = \"red\"\n"),
+        (SchemaFormat::Pkl, "tasks {\n  [\"build\"] {\n    command = \"make\"\n    colour = \"red\"\n  }\n}\n"),
+    ];
+    for (format, content) in sources {
+        let error = validate_config("moon", content, MoonConfig::Project, &format).unwrap_err();
+        let CliError::InvalidConfig { problems, .. } = error else {
+            panic!("expected an invalid {format} config, got {error:?}");
+        };
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("colour"), "{}", problems[0].message);
+        assert!(spanned(content, &problems[0].span).contains("colour"), "{format}: {:?}", problems[0].span);
+    }
+}
+
+#[test]
+fn test_locate_setting() {
+    let content = "tasks:\n  lint:\n    command: lint\n  build:\n    deps:\n      - lint\n";
+    let span = locate_setting(content, "tasks.build.deps[0]");
+    assert_eq!(spanned(content, &span), "deps");
+    assert_eq!(span.offset(), content.rfind("deps").unwrap());
+
+    // a missing setting points at its closest parent
+    let span = locate_setting(content, "tasks.build.script");
+    assert_eq!(span.offset(), content.find("build").unwrap());
+
+    assert_eq!(locate_setting(content, ".").len(), 0);
+}
+
+#[test]
+fn test_detect_config_type_from_path() {
+    let detect = |path: &str| MoonConfig::detect_from_path(Path::new(path));
+    assert_eq!(detect("apps/web/moon.yml"), Some(MoonConfig::Project));
+    assert_eq!(detect(".moon/workspace.yml"), Some(MoonConfig::Workspace));
+    assert_eq!(detect(".moon/toolchain.pkl"), Some(MoonConfig::Toolchain));
+    assert_eq!(detect(".moon/tasks.yml"), Some(MoonConfig::InheritedTasks));
+    assert_eq!(detect(".moon/tasks/node.yml"), Some(MoonConfig::InheritedTasks));
+    assert_eq!(detect("templates/app/template.yml"), Some(MoonConfig::Template));
+    assert_eq!(detect("scripts/tasks/build.yml"), None);
+    assert_eq!(detect("config.yml"), None);
+    // workspace, toolchain and tasks are only Moon's names inside `.moon/`
+    assert_eq!(detect("workspace.yml"), None);
+    assert_eq!(detect("ci/toolchain.yml"), None);
+    assert_eq!(detect("build/tasks.yml"), None);
+}
+
+#[tokio::test]
+async fn test_validate_config_file() {
+    let dir = std::env::temp_dir().join(format!("spklr-validate-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join(".moon")).unwrap();
+
+    let workspace = dir.join(".moon/workspace.yml");
+    std::fs::write(&workspace, "projects:\n  - apps/*\n").unwrap();
+    let config = validate_config_file(&workspace, None, None).await.unwrap();
+    assert!(matches!(config, LoadedConfig::Workspace(_)));
+
+    // without a telling name, the settings decide
+    let settings = dir.join("settings.yml");
+    std::fs::write(&settings, "projects: []\n").unwrap();
+    let config = validate_config_file(&settings, None, None).await.unwrap();
+    assert!(matches!(config, LoadedConfig::Workspace(_)));
+
+    let unknown = dir.join("list.yml");
+    std::fs::write(&unknown, "- projects\n").unwrap();
+    let error = validate_config_file(&unknown, None, None).await.unwrap_err();
+    assert!(matches!(error, CliError::UnknownConfigType { .. }));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_validate_command_counts_missing_files() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let workspace = dir.path().join("workspace.yml");
+    std::fs::write(&workspace, "projects:\n  - apps/*\n").unwrap();
+
+    // the missing file fails on its own, and the file after it is still checked
+    let args = ValidateArgs {
+        inputs: vec![dir.path().join("missing.yml"), workspace],
+        config_type: Some(MoonConfig::Workspace),
+        from: None,
+        workspace: None,
+    };
+    let error = handle_validate(args).await.unwrap_err();
+    assert!(matches!(error, CliError::ValidationFailed { failed: 1, total: 2 }), "{error:?}");
+}
@@ -0,0 +1,105 @@
+use schematic::Schematic;
+use space_pklr::custom_types::import_schematic_type_map;
+use space_pklr::{
+    CliError, MoonConfig, SchemaFormat, TemplateFormat, import_json_schema, render_moon_modules, render_moon_schema,
+    render_schema, render_template, resolve_root, type_map_of,
+};
+
+#[derive(Schematic)]
+#[allow(dead_code)]
+struct Tls {
+    cert: String,
+    key: Option<String>,
+}
+
+/// A server to deploy
+#[derive(Schematic)]
+#[allow(dead_code)]
+struct Server {
+    host: String,
+    port: u16,
+    tls: Tls,
+}
+
+#[test]
+fn test_render_rust_type() {
+    let types = type_map_of::<Server>();
+    assert_eq!(types.keys().last().map(String::as_str), Some("Server"));
+
+    let pkl = render_schema(types.clone(), None, &SchemaFormat::Pkl).unwrap();
+    assert!(pkl.contains("open module Server"), "{pkl}");
+    assert!(pkl.contains("port: UInt16"), "{pkl}");
+    assert!(pkl.contains("open class Tls {"), "{pkl}");
+
+    let json = render_schema(types.clone(), None, &SchemaFormat::Json).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["title"], "Server");
+    assert!(json["definitions"]["Tls"].is_object(), "{json}");
+
+    let typescript = render_schema(types, None, &SchemaFormat::Typescript).unwrap();
+    assert!(typescript.contains("export interface Server {"), "{typescript}");
+}
+
+#[test]
+fn test_resolve_root() {
+    let source = r##"{
+  "title": "Service",
+  "type": "object",
+  "properties": {
+    "targets": { "type": "array", "items": { "$ref": "#/$defs/Target" } }
+  },
+  "$defs": {
+    "Target": { "type": "object", "properties": { "host": { "type": "string" } } }
+  }
+}"##;
+    let types = import_json_schema(source).unwrap();
+
+    // references nested in fields count, so the root is the struct nothing uses
+    let (root, types) = resolve_root(types, None).unwrap();
+    assert_eq!(root, "Service");
+
+    // naming a root moves it to the end, where schematic's renderers look for it
+    let (root, types) = resolve_root(types, Some("Target")).unwrap();
+    assert_eq!(root, "Target");
+    assert_eq!(types.keys().last().map(String::as_str), Some("Target"));
+
+    let error = resolve_root(types, Some("Missing")).unwrap_err();
+    assert!(matches!(error, CliError::RootTypeNotFound { ref available, .. } if available.len() == 2));
+}
+
+#[test]
+fn test_render_serialized_type_map() {
+    let serialized = serde_json::to_string(&type_map_of::<Server>()).unwrap();
+    let types = import_schematic_type_map("server.json", &serialized).unwrap();
+
+    let yaml = render_template(types.clone(), None, &TemplateFormat::Yaml).unwrap();
+    assert!(yaml.contains("host: \"\""), "{yaml}");
+    assert!(yaml.contains("tls:"), "{yaml}");
+
+    assert!(render_template(types.clone(), None, &TemplateFormat::Typescript).is_err());
+    assert!(import_schematic_type_map("broken.json", "{ \"Server\": 1 }").is_err());
+}
+
+#[test]
+fn test_render_moon_schema_defaults() {
+    // VcsConfig is nested in WorkspaceConfig, so its defaults come from WorkspaceConfig's
+    let pkl = render_moon_schema(MoonConfig::Workspace, &SchemaFormat::Pkl).unwrap();
+    assert!(pkl.contains("remoteCandidates: Listing<String>? = new Listing { \"origin\"; \"upstream\" }"), "{pkl}");
+
+    let error = render_moon_schema(MoonConfig::All, &SchemaFormat::Pkl).unwrap_err();
+    assert!(matches!(error, CliError::Generic(_)), "{error:?}");
+}
+
+#[test]
+fn test_render_moon_modules() {
+    let modules = render_moon_modules(MoonConfig::All).unwrap();
+    for name in ["ProjectConfig.pkl", "WorkspaceConfig.pkl", "TaskConfig.pkl", "Common.pkl"] {
+        assert!(modules.contains_key(name), "{:?}", modules.keys());
+    }
+
+    // tasks are typed by the task root's module, not a class inside it
+    let project = &modules["ProjectConfig.pkl"];
+    assert!(project.contains("import \"TaskConfig.pkl\""), "{project}");
+    assert!(project.contains("tasks: Mapping<String, TaskConfig>"), "{project}");
+    assert!(!modules["TaskConfig.pkl"].contains("class TaskConfig"));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use std::fs;
+
+use moon_config::{InputPath, TaskArgs};
+use space_pklr::{CliError, EffectiveOptions, LoadedConfig, MoonConfig, resolve_effective_config};
+use tempfile::TempDir;
+
+use common::write;
+
+/// A workspace with inherited tasks for every project, for node projects, for `frontend` projects and for rust
+/// projects, and a typescript project tagged `frontend`
+fn workspace() -> TempDir {
+    common::workspace(&[
+        (
+            ".moon/tasks.yml",
+            "fileGroups:\n  sources: ['src/**/*']\nimplicitInputs: ['/.env']\ntasks:\n  lint:\n    command: lint\n",
+        ),
+        (
+            ".moon/tasks/node.yml",
+            "implicitInputs: ['package.json']\ntaskOptions:\n  cache: false\ntasks:\n  build:\n    command: tsc\n    args: ['--build']\n  test:\n    command: vitest\n",
+        ),
+        (".moon/tasks/tag-frontend.yml", "tasks:\n  lint:\n    command: eslint\n"),
+        (".moon/tasks/rust.yml", "tasks:\n  check:\n    command: cargo check\n"),
+        (
+            "apps/web/moon.yml",
+            r#"language: typescript
+tags: [frontend]
+workspace:
+  inheritedTasks:
+    exclude: [test]
+    rename:
+      lint: check-style
+fileGroups:
+  sources: ['app/**/*']
+tasks:
+  build:
+    args: ['--pretty']
+    options:
+      mergeArgs: prepend
+  dev:
+    command: vite
+"#,
+        ),
+    ])
+}
+
+fn files(effective: &space_pklr::EffectiveConfig, setting: &str) -> Vec<String> {
+    effective.sources[setting]
+        .iter()
+        .map(|file| file.display().to_string().rsplit_once(".tmp").map(|(_, rest)| rest.to_string()).unwrap_or_default())
+        .map(|file| file.split_once('/').unwrap().1.to_string())
+        .collect()
+}
+
+#[test]
+fn test_project_inherits_tasks() {
+    let dir = workspace();
+    let effective =
+        resolve_effective_config(&dir.path().join("apps/web/moon.yml"), MoonConfig::Project, &EffectiveOptions::default())
+            .unwrap();
+    let LoadedConfig::Project(project) = &effective.config else {
+        panic!("expected a project config");
+    };
+
+    let mut tasks: Vec<&str> = project.tasks.keys().map(|id| id.as_str()).collect();
+    tasks.sort();
+    assert_eq!(tasks, ["build", "check-style", "dev"]);
+
+    let build = &project.tasks[&moon_common::Id::raw("build")];
+    assert_eq!(build.command, TaskArgs::String("tsc".to_string()));
+    assert_eq!(build.args, TaskArgs::List(vec!["--pretty".to_string(), "--build".to_string()]));
+    assert_eq!(build.options.cache, Some(false));
+    let inputs: Vec<&str> = build.inputs.as_ref().unwrap().iter().map(InputPath::as_str).collect();
+    assert_eq!(inputs, [".env", "package.json"]);
+
+    let lint = &project.tasks[&moon_common::Id::raw("check-style")];
+    assert_eq!(lint.command, TaskArgs::String("eslint".to_string()));
+
+    // the project's file groups win
+    let sources: Vec<&str> = project.file_groups[&moon_common::Id::raw("sources")].iter().map(InputPath::as_str).collect();
+    assert_eq!(sources, ["app/**/*"]);
+
+    assert_eq!(files(&effective, "tasks.check-style"), [".moon/tasks.yml", ".moon/tasks/tag-frontend.yml"]);
+    assert_eq!(files(&effective, "tasks.build"), [".moon/tasks/node.yml", "apps/web/moon.yml"]);
+    assert_eq!(files(&effective, "tasks.dev"), ["apps/web/moon.yml"]);
+    assert_eq!(files(&effective, "language"), ["apps/web/moon.yml"]);
+
+    let explained = effective.explain_relative_to(&fs::canonicalize(dir.path()).unwrap());
+    assert!(explained.contains(".moon/tasks.yml → .moon/tasks/tag-frontend.yml"), "{explained}");
+    assert!(explained.ends_with("Anything not listed is a default."));
+}
+
+#[test]
+fn test_extends_chain_with_stand_in() {
+    let dir = workspace();
+    let root = dir.path();
+    write(root, "shared/remote.yml", "vcs:\n  defaultBranch: trunk\n  manager: git\n");
+    write(
+        root,
+        "shared/base.yml",
+        "extends: 'https://example.com/moon/workspace.yml'\nprojects: ['libs/*']\nvcs:\n  defaultBranch: main\n",
+    );
+    write(root, ".moon/workspace.yml", "extends: '../shared/base.yml'\nprojects: ['apps/*']\n");
+
+    let path = root.join(".moon/workspace.yml");
+    let error = resolve_effective_config(&path, MoonConfig::Workspace, &EffectiveOptions::default()).unwrap_err();
+    assert!(matches!(error, CliError::UnresolvedExtends { ref target, .. } if target.starts_with("https://")));
+
+    let options = EffectiveOptions {
+        stand_ins: [("https://example.com/moon/workspace.yml".to_string(), root.join("shared/remote.yml"))].into(),
+        ..Default::default()
+    };
+    let effective = resolve_effective_config(&path, MoonConfig::Workspace, &options).unwrap();
+    let LoadedConfig::Workspace(workspace) = &effective.config else {
+        panic!("expected a workspace config");
+    };
+    assert_eq!(workspace.vcs.default_branch, "main");
+    assert!(matches!(&workspace.projects, moon_config::WorkspaceProjects::Globs(globs) if globs == &["apps/*"]));
+    assert_eq!(effective.sources["vcs"].len(), 2);
+    assert_eq!(effective.sources["projects"].len(), 2);
+}
+
+#[test]
+fn test_extends_cycle() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "a.yml", "extends: './b.yml'\n");
+    write(dir.path(), "b.yml", "extends: './a.yml'\n");
+    let error = resolve_effective_config(&dir.path().join("a.yml"), MoonConfig::Toolchain, &EffectiveOptions::default())
+        .unwrap_err();
+    assert!(matches!(error, CliError::UnresolvedExtends { ref reason, .. } if reason.contains("round")), "{error:?}");
+}
+
+#[test]
+fn test_malformed_inherited_tasks() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), ".moon/tasks.yml", "taskOptions:\n  cache: false\ntasks:\n  lint: eslint\n");
+    write(dir.path(), "app/moon.yml", "language: typescript\n");
+    let error = resolve_effective_config(&dir.path().join("app/moon.yml"), MoonConfig::Project, &EffectiveOptions::default())
+        .unwrap_err();
+    assert!(matches!(error, CliError::ValidationError { .. }), "{error:?}");
+}
@@ -2,7 +2,6 @@
 use space_pklr::_rewrite::*;
 use space_pklr::pkl_tooling::*;
 use tempfile::TempDir;
-use tokio;
 
 #[tokio::test]
 async fn test_pkl_compatibility_validation() {
@@ -13,31 +12,20 @@ async fn test_pkl_compatibility_validation() {
         match report {
             Ok(compatibility_report) => {
                 println!("Pkl version: {}", compatibility_report.pkl_version);
-                println!(
-                    "Basic functionality: {}",
-                    compatibility_report.basic_functionality
-                );
-                println!(
-                    "Moon config integration: {}",
-                    compatibility_report.moon_config_integration
-                );
-                println!(
-                    "Extend/amend support: {}",
-                    compatibility_report.extend_amend_support
-                );
-                println!(
-                    "Schema generation: {}",
-                    compatibility_report.schema_generation
-                );
-
-                // In a development environment, basic functionality may not be fully implemented yet
-                if compatibility_report.basic_functionality {
-                    println!("✅ Basic Pkl functionality is working");
-                } else {
+                for result in &compatibility_report.features {
                     println!(
-                        "⚠️  Basic Pkl functionality not yet fully implemented (expected in development)"
+                        "{}: {}",
+                        result.feature,
+                        result.error.as_deref().unwrap_or("passed")
                     );
                 }
+
+                // the real checks need a working Pkl, which a development environment may not have
+                if compatibility_report.is_compatible() {
+                    println!("✅ Pkl CLI is compatible");
+                } else {
+                    println!("⚠️  Some compatibility checks failed");
+                }
             }
             Err(e) => {
                 println!("Compatibility validation failed: {}", e);
@@ -88,7 +76,7 @@ async fn test_enhanced_error_handling() {
     assert!(error_string.contains("File not found"));
 
     // Test validation error
-    let validation_err = validation_error(std::io::types::new(
+    let validation_err = validation_error(std::io::Error::new(
         std::io::ErrorKind::InvalidData,
         "Invalid YAML",
     ));
@@ -153,20 +141,20 @@ fn test_compatibility_report_structure() {
     let report = CompatibilityReport::new("0.28.0".to_string());
 
     assert_eq!(report.pkl_version, "0.28.0");
-    assert!(!report.basic_functionality);
-    assert!(!report.moon_config_integration);
-    assert!(!report.extend_amend_support);
-    assert!(!report.schema_generation);
+    assert!(report.features.is_empty());
     assert!(!report.is_compatible());
 
-    // Test with all features enabled
+    // Test with all features passing
     let mut full_report = CompatibilityReport::new("0.28.0".to_string());
-    full_report.basic_functionality = true;
-    full_report.moon_config_integration = true;
-    full_report.extend_amend_support = true;
-    full_report.schema_generation = true;
-
+    full_report.record("version", Ok(()));
+    full_report.record("schema:project", Ok(()));
+    full_report.record("facts", Ok(()));
     assert!(full_report.is_compatible());
+
+    full_report.record("load:project", Err("unknown field `foo`".to_string()));
+    assert!(!full_report.is_compatible());
+    let failed = full_report.feature("load:project").unwrap();
+    assert_eq!(failed.error.as_deref(), Some("unknown field `foo`"));
 }
 
 #[tokio::test]
@@ -186,8 +174,8 @@ async fn test_tracing_integration() {
 
 #[tokio::test]
 async fn test_schematic_integration_project_config() {
-    use space_pklr::_rewrite::*;
-    use tempfile::TempDir;
+    use space_pklr::LoadedConfig;
+    use space_pklr::types::MoonConfig;
 
     let temp_dir = TempDir::new().unwrap();
     let config_path = temp_dir.path().join("moon.yml");
@@ -222,29 +210,135 @@ tasks:
     assert!(content.contains("language: rust"));
     assert!(content.contains("type: library"));
     assert!(content.contains("cargo build"));
+
+    // Load it through schematic, which validates it and fills in defaults
+    let config = load_config_with_schematic(&config_path, MoonConfig::Project, None).await.unwrap();
+    let LoadedConfig::Project(project) = config else {
+        panic!("expected a project config");
+    };
+    assert_eq!(project.tasks.len(), 2);
+    assert_eq!(project.schema, "https://moonrepo.dev/schemas/project.json");
 }
 
 #[tokio::test]
 async fn test_real_config_roundtrip_conversion() {
-    use tempfile::TempDir;
+    use space_pklr::types::{MoonConfig, SchemaFormat};
 
     let temp_dir = TempDir::new().unwrap();
+    let yaml = r#"
+language: rust
+tags: [app]
+tasks:
+  build:
+    command: cargo build
+    inputs:
+      - "src/**/*"
+"#;
+
+    // YAML -> Pkl -> YAML, validating each step as a project config
+    let pkl = convert_config("moon.yml", yaml, MoonConfig::Project, SchemaFormat::Yaml, SchemaFormat::Pkl).unwrap();
+    assert!(pkl.contains("language = \"rust\""), "{pkl}");
+    let pkl_path = temp_dir.path().join("moon.pkl");
+    tokio::fs::write(&pkl_path, &pkl).await.unwrap();
+
+    let config = load_config_with_schematic(&pkl_path, MoonConfig::Project, None).await.unwrap();
+    let roundtrip = render_config_with_schematic(&config, SchemaFormat::Yaml).unwrap();
+    let yaml_path = temp_dir.path().join("moon_roundtrip.yml");
+    tokio::fs::write(&yaml_path, &roundtrip).await.unwrap();
+    assert!(roundtrip.contains("language: rust"), "{roundtrip}");
+    assert!(roundtrip.contains("cargo build"), "{roundtrip}");
+
+    // JSON and TOML too
+    let json = convert_config("moon.yml", yaml, MoonConfig::Project, SchemaFormat::Yaml, SchemaFormat::Json).unwrap();
+    let toml = convert_config("moon.json", &json, MoonConfig::Project, SchemaFormat::Json, SchemaFormat::Toml).unwrap();
+    assert!(toml.contains("language = \"rust\""), "{toml}");
+    let back = convert_config("moon.toml", &toml, MoonConfig::Project, SchemaFormat::Toml, SchemaFormat::Yaml).unwrap();
+    assert!(back.contains("language: rust"), "{back}");
+}
 
-    // Test with our example project config
-    let original_path = std::path::Path::new("examples/project.yml");
-    if original_path.exists() {
-        let pkl_path = temp_dir.path().join("project.pkl");
-        let yaml_path = temp_dir.path().join("project_roundtrip.yml");
+#[test]
+fn test_convert_project_keeps_its_settings() {
+    use space_pklr::types::{LoadedConfig, MoonConfig, SchemaFormat};
+
+    // `toolchain` has flattened settings: its plugins sit beside `default`, and so do a plugin's own settings
+    let yaml = r#"
+language: typescript
+tags: [app]
+dependsOn: [ui]
+toolchain:
+  default: node
+  typescript:
+    version: "5.8.0"
+    includeSharedTypes: true
+  go: false
+tasks:
+  build:
+    command: vite build
+    inputs: ["src/**/*"]
+    options:
+      cache: false
+"#;
+    let original = parse_config("moon.yml", yaml, MoonConfig::Project, &SchemaFormat::Yaml).unwrap();
+
+    let pkl = convert_config("moon.yml", yaml, MoonConfig::Project, SchemaFormat::Yaml, SchemaFormat::Pkl).unwrap();
+    assert!(pkl.starts_with("amends \"ProjectConfig.pkl\"\n"), "{pkl}");
+    assert!(!pkl.contains("schema") && !pkl.contains("plugins"), "{pkl}");
+    // settings of a struct are properties, and only maps have entries
+    assert!(pkl.contains("  typescript {\n") && pkl.contains("    includeSharedTypes = true\n"), "{pkl}");
+    assert!(pkl.contains("  [\"build\"] {\n"), "{pkl}");
+
+    for format in [SchemaFormat::Pkl, SchemaFormat::Yaml, SchemaFormat::Json, SchemaFormat::Toml] {
+        let converted = render_config_with_schematic(&original, format.clone()).unwrap();
+        let reloaded = parse_config("moon", &converted, MoonConfig::Project, &format).unwrap();
+        let (LoadedConfig::Project(original), LoadedConfig::Project(reloaded)) = (&original, &reloaded) else {
+            panic!("expected project configs");
+        };
+        assert_eq!(original, reloaded, "{format}:\n{converted}");
+    }
+}
 
-        // For now, just test that the example file exists and is readable
-        let content = tokio::fs::read_to_string(original_path).await.unwrap();
-        assert!(content.contains("language: rust"));
-        assert!(content.contains("type: library"));
+#[tokio::test]
+async fn test_inherited_tasks_roundtrip_conversion() {
+    use space_pklr::types::{LoadedConfig, MoonConfig, SchemaFormat};
+
+    let yaml = r#"
+fileGroups:
+  sources: ["src/**/*"]
+  configs: ["/tsconfig.json"]
+implicitInputs: ["package.json", "$NODE_ENV"]
+tasks:
+  lint:
+    command: eslint
+    inputs: ["@group(sources)"]
+"#;
+
+    let pkl = convert_config(".moon/tasks/node.yml", yaml, MoonConfig::InheritedTasks, SchemaFormat::Yaml, SchemaFormat::Pkl).unwrap();
+    assert!(pkl.contains("implicitInputs"), "{pkl}");
+    let config = parse_config(".moon/tasks/node.pkl", &pkl, MoonConfig::InheritedTasks, &SchemaFormat::Pkl).unwrap();
+    let LoadedConfig::InheritedTasks(tasks) = &config else {
+        panic!("expected an inherited tasks config");
+    };
+    let inputs: Vec<String> = tasks.implicit_inputs.iter().cloned().map(Into::into).collect();
+    assert_eq!(inputs, ["package.json", "$NODE_ENV"]);
+    let configs: Vec<String> = tasks.file_groups[&moon_common::Id::raw("configs")].iter().cloned().map(Into::into).collect();
+    assert_eq!(configs, ["/tsconfig.json"]);
+
+    let schema = generate_schema(MoonConfig::InheritedTasks, "json-schema").unwrap();
+    assert!(schema.contains("implicitInputs"), "{schema}");
+    let template = generate_template(MoonConfig::InheritedTasks, SchemaFormat::Yaml).unwrap();
+    assert!(template.contains("fileGroups"), "{template}");
+}
 
-        println!("✅ Example project config is valid and readable");
-    } else {
-        println!("⚠️ Example project config not found, skipping roundtrip test");
-    }
+#[tokio::test]
+async fn test_convert_reports_validation_errors() {
+    use space_pklr::types::{CliError, MoonConfig, SchemaFormat};
+
+    let invalid = "language: rust\ntasks: 3\n";
+    let error = convert_config("moon.yml", invalid, MoonConfig::Project, SchemaFormat::Yaml, SchemaFormat::Json);
+    assert!(matches!(error, Err(CliError::ValidationError { .. })));
+
+    let error = convert_config("moon.yml", "language: rust\n", MoonConfig::Project, SchemaFormat::Yaml, SchemaFormat::Typescript);
+    assert!(matches!(error, Err(CliError::UnsupportedFormat { .. })));
 }
 
 #[tokio::test]
@@ -0,0 +1,150 @@
+use schematic::schema::{LiteralValue, SchemaType};
+use space_pklr::import_json_schema;
+
+#[test]
+fn test_import_definitions_and_references() {
+    let source = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "Service Config",
+  "type": "object",
+  "required": ["name"],
+  "properties": {
+    "name": { "type": "string", "minLength": 1, "description": "Service name" },
+    "port": { "$ref": "#/$defs/Port" },
+    "level": { "enum": ["info", "debug", null], "default": "info" },
+    "tags": { "type": "array", "items": { "type": "string" }, "uniqueItems": true, "maxItems": 4 },
+    "env": { "type": "object", "additionalProperties": { "type": "string" } },
+    "owner": {
+      "type": "object",
+      "properties": { "email": { "type": ["string", "null"], "format": "email" } }
+    },
+    "point": { "type": "array", "prefixItems": [{ "type": "number" }, { "type": "number" }] }
+  },
+  "$defs": {
+    "Port": { "type": "integer", "minimum": 1, "exclusiveMaximum": 65536, "default": 8080 }
+  }
+}"##;
+    let types = import_json_schema(source).unwrap();
+
+    let SchemaType::Integer(port) = &types["Port"].ty else {
+        panic!("Port should be an integer");
+    };
+    assert_eq!((port.min, port.max_exclusive), (Some(1), Some(65536)));
+    assert_eq!(port.default, Some(LiteralValue::Int(8080)));
+
+    let SchemaType::Struct(service) = &types["ServiceConfig"].ty else {
+        panic!("the root should be a struct named after its title");
+    };
+    assert!(!service.fields["name"].optional);
+    assert!(service.fields["port"].optional);
+    assert_eq!(service.fields["name"].comment.as_deref(), Some("Service name"));
+    assert!(matches!(&service.fields["port"].schema.ty, SchemaType::Reference(name) if name == "Port"));
+
+    // `null` among the values makes a nullable union, the way schematic models it
+    let SchemaType::Union(level) = &service.fields["level"].schema.ty else {
+        panic!("level should be a nullable union");
+    };
+    let SchemaType::Enum(level) = &level.variants_types[0].ty else {
+        panic!("level should wrap an enum");
+    };
+    assert_eq!(level.values.len(), 2);
+    assert_eq!(level.default_index, Some(0));
+    assert!(service.fields["level"].nullable);
+
+    let SchemaType::Array(tags) = &service.fields["tags"].schema.ty else {
+        panic!("tags should be an array");
+    };
+    assert_eq!((tags.unique, tags.max_length), (Some(true), Some(4)));
+
+    assert!(matches!(service.fields["env"].schema.ty, SchemaType::Object(_)));
+    assert!(matches!(service.fields["point"].schema.ty, SchemaType::Tuple(_)));
+
+    // inline objects are hoisted into their own types
+    assert!(matches!(&service.fields["owner"].schema.ty, SchemaType::Reference(name) if name == "ServiceConfigOwner"));
+    let SchemaType::Struct(owner) = &types["ServiceConfigOwner"].ty else {
+        panic!("ServiceConfigOwner should be a struct");
+    };
+    assert!(owner.fields["email"].nullable);
+}
+
+#[test]
+fn test_import_combinators() {
+    let source = r##"{
+  "definitions": {
+    "Base": { "type": "object", "required": ["id"], "properties": { "id": { "type": "string" } } },
+    "Named": {
+      "allOf": [
+        { "$ref": "#/definitions/Base" },
+        { "properties": { "label": { "type": "string" } } }
+      ]
+    },
+    "Target": { "oneOf": [{ "type": "string" }, { "type": "array", "items": { "type": "string" } }] },
+    "Mode": { "const": "strict" }
+  }
+}"##;
+    let types = import_json_schema(source).unwrap();
+
+    // a document with only definitions has no root type
+    assert!(!types.contains_key("Root"));
+
+    let SchemaType::Struct(named) = &types["Named"].ty else {
+        panic!("allOf should merge into a struct");
+    };
+    assert!(!named.fields["id"].optional);
+    assert!(named.fields["label"].optional);
+
+    assert!(matches!(&types["Target"].ty, SchemaType::Union(union) if union.variants_types.len() == 2));
+    assert!(matches!(&types["Mode"].ty, SchemaType::Literal(_)));
+}
+
+#[test]
+fn test_import_escaped_references() {
+    // `~1` and `~0` stand for `/` and `~` in JSON pointers, for `allOf` members as much as anywhere
+    let source = r##"{
+  "definitions": {
+    "a/b": { "type": "object", "required": ["id"], "properties": { "id": { "type": "string" } } },
+    "c~d": { "type": "string" },
+    "Named": {
+      "allOf": [
+        { "$ref": "#/definitions/a~1b" },
+        { "properties": { "label": { "$ref": "#/definitions/c~0d" } } }
+      ]
+    }
+  }
+}"##;
+    let types = import_json_schema(source).unwrap();
+
+    let SchemaType::Struct(named) = &types["Named"].ty else {
+        panic!("allOf should merge into a struct");
+    };
+    assert!(!named.fields["id"].optional);
+    assert!(matches!(&named.fields["label"].schema.ty, SchemaType::Reference(name) if name == "c~d"));
+}
+
+#[test]
+fn test_import_inline_type_named_like_the_root() {
+    // the root is added last, so an inline object titled like it mustn't take its name
+    let source = r##"{
+  "title": "Service",
+  "type": "object",
+  "properties": {
+    "parent": { "title": "Service", "type": "object", "properties": { "url": { "type": "string" } } }
+  }
+}"##;
+    let types = import_json_schema(source).unwrap();
+
+    let SchemaType::Struct(service) = &types["Service"].ty else {
+        panic!("the root should be a struct named after its title");
+    };
+    assert!(matches!(&service.fields["parent"].schema.ty, SchemaType::Reference(name) if name == "Service2"));
+    assert!(matches!(&types["Service2"].ty, SchemaType::Struct(parent) if parent.fields.contains_key("url")));
+}
+
+#[test]
+fn test_import_reports_errors() {
+    let error = import_json_schema("{ \"type\": ").unwrap_err();
+    assert!(error.to_string().contains("Failed to import schema"));
+
+    let error = import_json_schema(r##"{ "properties": { "a": { "$ref": "#/$defs/Missing" } } }"##).unwrap_err();
+    assert!(error.to_string().contains("missing definition"));
+}
@@ -0,0 +1,126 @@
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use space_pklr::CliError;
+use space_pklr::pkl_tooling::{
+    PKL_RELEASE_PLATFORMS, extract_archive, extract_tar_gz_archive, extract_zip_archive, get_compatible_pkl_versions,
+    known_checksum, pkl_archive_name_for, verify_checksum,
+};
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+
+/// A tar.gz holding `files`, with paths written as given
+fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        // set directly, since `set_path` refuses `..`
+        header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o755);
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_cksum();
+        builder.append(&header, *contents).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, contents) in files {
+        writer.start_file(*path, SimpleFileOptions::default().unix_permissions(0o755)).unwrap();
+        writer.write_all(contents).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_extract_tar_gz_finds_nested_pkl() {
+    let dir = TempDir::new().unwrap();
+    let archive = tar_gz(&[("pkl-cli-0.28.0/README.md", b"hi"), ("pkl-cli-0.28.0/bin/pkl", b"#!/bin/sh\n")]);
+
+    let pkl = extract_tar_gz_archive(&archive, dir.path()).unwrap();
+    assert_eq!(pkl, dir.path().join("pkl-cli-0.28.0/bin/pkl"));
+    assert_eq!(std::fs::read(&pkl).unwrap(), b"#!/bin/sh\n");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&pkl).unwrap().permissions().mode() & 0o111, 0o111);
+    }
+}
+
+#[test]
+fn test_extract_zip_finds_nested_pkl() {
+    let dir = TempDir::new().unwrap();
+    let executable = if cfg!(windows) { "pkl-cli/pkl.exe" } else { "pkl-cli/pkl" };
+    let archive = zip(&[("pkl-cli/LICENSE", b"MIT"), (executable, b"binary")]);
+
+    // the archive's name picks how it's read, not the host
+    let pkl = extract_archive(&archive, "pkl-cli-windows-amd64.zip", dir.path()).unwrap();
+    assert_eq!(pkl, dir.path().join(executable));
+    assert_eq!(std::fs::read_to_string(dir.path().join("pkl-cli/LICENSE")).unwrap(), "MIT");
+}
+
+#[test]
+fn test_archives_cannot_escape_target_dir() {
+    let dir = TempDir::new().unwrap();
+    let target = dir.path().join("install");
+
+    let archive = tar_gz(&[("../evil", b"gotcha"), ("pkl", b"binary")]);
+    let error = extract_tar_gz_archive(&archive, &target).unwrap_err();
+    assert!(error.to_string().contains("outside"), "{error}");
+
+    let archive = zip(&[("../evil", b"gotcha"), ("pkl", b"binary")]);
+    let error = extract_zip_archive(&archive, &target).unwrap_err();
+    assert!(error.to_string().contains("outside"), "{error}");
+
+    assert!(!dir.path().join("evil").exists());
+}
+
+#[test]
+fn test_archive_without_pkl() {
+    let dir = TempDir::new().unwrap();
+    let archive = tar_gz(&[("docs/pkl.md", b"no binary here")]);
+    assert!(extract_archive(&archive, "pkl.tgz", dir.path()).is_err());
+    assert!(extract_archive(&archive, "pkl.rar", dir.path()).is_err());
+}
+
+#[test]
+fn test_verify_checksum() {
+    const HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    verify_checksum(b"hello", HELLO, "hello.txt").unwrap();
+    verify_checksum(b"hello", &HELLO.to_uppercase(), "hello.txt").unwrap();
+
+    let error = verify_checksum(b"hellO", HELLO, "hello.txt").unwrap_err();
+    match error.downcast_ref::<CliError>() {
+        Some(CliError::ChecksumMismatch { file, expected, actual }) => {
+            assert_eq!(file, "hello.txt");
+            assert_eq!(expected, HELLO);
+            assert_ne!(actual, HELLO);
+        }
+        _ => panic!("{error:?}"),
+    }
+}
+
+#[test]
+fn test_unknown_versions_have_no_checksum() {
+    assert_eq!(known_checksum("0.0.1", "pkl-cli-linux-amd64.tar.gz"), None);
+    assert_eq!(known_checksum("0.28.0", "pkl-cli-plan9-amd64.tar.gz"), None);
+}
+
+#[test]
+#[ignore = "the Pkl version workflow fills in src/pkl_checksums.txt with network access, then runs this"]
+fn test_compatible_versions_have_checksums() {
+    let mut missing = Vec::new();
+    for version in get_compatible_pkl_versions() {
+        for (os, arch) in PKL_RELEASE_PLATFORMS {
+            let archive_name = pkl_archive_name_for(os, arch).unwrap();
+            match known_checksum(version, &archive_name) {
+                Some(hash) => assert!(hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()), "{hash}"),
+                None => missing.push(format!("{version}/{archive_name}")),
+            }
+        }
+    }
+    assert!(missing.is_empty(), "no checksum for {}", missing.join(", "));
+}
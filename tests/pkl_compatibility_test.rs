@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use space_pklr::pkl_tooling::validate_pkl_compatibility;
+use space_pklr::{CompatibilityReport, PklCli, PklSource};
+use tempfile::TempDir;
+
+/// Stands in for Pkl: schemas evaluate, the template config doesn't, and the facts fail
+const FAKE_PKL: &str = r#"#!/bin/sh
+case "$1" in
+  --version) echo "Pkl 0.28.0 (Linux, native)" ;;
+  eval)
+    if [ "$2" = "-x" ]; then echo true; exit 0; fi
+    case "$4" in
+      */project.pkl)
+        grep -q '^amends "ProjectConfig.pkl"' "$4" || { echo "not amending the schema" >&2; exit 1; }
+        grep -q '^language = "rust"$' "$4" || { echo "no language property" >&2; exit 1; }
+        echo '{"language": "rust", "tags": ["app"]}' ;;
+      */template.pkl) printf '%s\n' '–– Pkl Error ––' 'Cannot find property `title`.' >&2; exit 1 ;;
+      *) echo '{}' ;;
+    esac ;;
+  test) echo "facts ❌ 0.0% pass [1/1 failed]"; exit 1 ;;
+  *) exit 2 ;;
+esac
+"#;
+
+#[tokio::test]
+async fn test_compatibility_suite_reports_each_feature() {
+    let dir = TempDir::new().unwrap();
+    let pkl = dir.path().join("pkl");
+    std::fs::write(&pkl, FAKE_PKL).unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&pkl, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let pkl_cli = PklCli {
+        path: pkl,
+        source: PklSource::Manual(dir.path().to_path_buf()),
+        version: Some("0.28.0".to_string()),
+    };
+
+    let report = validate_pkl_compatibility(&pkl_cli).await.unwrap();
+    let passed = |feature: &str| report.feature(feature).unwrap_or_else(|| panic!("no {feature} in {report:?}")).passed;
+    assert!(passed("version"));
+    for config_type in ["project", "workspace", "toolchain", "template", "task", "inherited-tasks"] {
+        assert!(passed(&format!("schema:{config_type}")), "{report:?}");
+    }
+    assert!(passed("amends:project"));
+    assert!(passed("load:project"), "{report:?}");
+
+    let template = report.feature("amends:template").unwrap();
+    assert_eq!(template.error.as_deref(), Some("–– Pkl Error ––\nCannot find property `title`."));
+    assert!(report.feature("load:template").is_none());
+    // failed facts are only reported on stdout
+    assert_eq!(report.feature("facts").unwrap().error.as_deref(), Some("facts ❌ 0.0% pass [1/1 failed]"));
+
+    assert!(!report.is_compatible());
+    let failures: Vec<&str> = report.failures().map(|result| result.feature.as_str()).collect();
+    assert_eq!(failures, ["amends:template", "facts"]);
+}
+
+#[test]
+fn test_compatibility_report_json() {
+    let mut report = CompatibilityReport::new("0.28.0".to_string());
+    report.record("version", Ok(()));
+    report.record("facts", Err("1 fact failed".to_string()));
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "pkl_version": "0.28.0",
+            "features": [
+                { "feature": "version", "passed": true },
+                { "feature": "facts", "passed": false, "error": "1 fact failed" },
+            ],
+        })
+    );
+    let read: CompatibilityReport = serde_json::from_value(json).unwrap();
+    assert_eq!(read.features, report.features);
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use moon_config::LanguageType;
+use serde::{Deserialize, Serialize};
+use space_pklr::{
+    CliError, LoadedConfig, MoonConfig, PklValue, from_pkl_str, load_pkl_config, parse_pcf, to_pkl_string,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Task {
+    command: String,
+    inputs: Vec<String>,
+    run_in_ci: bool,
+    retry_count: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Runner {
+    Local,
+    Remote { host: String },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Project {
+    tags: Vec<String>,
+    tasks: BTreeMap<String, Task>,
+    #[serde(rename = "class")]
+    class_name: Option<String>,
+    weights: Vec<f64>,
+    runners: Vec<Runner>,
+    #[serde(rename = "file-groups")]
+    file_groups: BTreeMap<String, Vec<String>>,
+}
+
+#[test]
+fn test_round_trip_through_serializer() {
+    let project = Project {
+        tags: vec!["app".to_string(), "say \"hi\"\n".to_string()],
+        tasks: BTreeMap::from([(
+            "build".to_string(),
+            Task {
+                command: "cargo build".to_string(),
+                inputs: vec!["src/**/*".to_string()],
+                run_in_ci: true,
+                retry_count: Some(2),
+            },
+        )]),
+        class_name: None,
+        weights: vec![1.0, -0.5],
+        runners: vec![Runner::Local, Runner::Remote { host: "ci".to_string() }],
+        file_groups: BTreeMap::new(),
+    };
+    let pkl = to_pkl_string(&project).unwrap();
+    let read: Project = from_pkl_str(&pkl).unwrap();
+    assert_eq!(read, project, "{pkl}");
+}
+
+#[test]
+fn test_parse_static_pkl() {
+    let source = r##"amends "package://example.com/moon@1.0.0#/Project.pkl"
+
+/// Doc comments and /* block comments */ are skipped
+name = "caf\u{e9}\tbar"
+raw = #"C:\dir "quoted""#
+hex = 0xFF; binary = 0b1010; big = 1_000_000
+negative = -3.5e2
+block = """
+    first
+      second
+    """
+servers {
+  new { host = "a"; port = 80 }
+}
+env = new Mapping { ["CI"] = "true" }
+empty = new Listing {}
+servers {
+  new { host = "b" }
+}
+`class` = null
+"##;
+    let document = parse_pcf("moon.pcf", source).unwrap();
+    assert_eq!(document.amends.as_deref(), Some("package://example.com/moon@1.0.0#/Project.pkl"));
+    let PklValue::Object(properties) = document.value else {
+        panic!("expected an object");
+    };
+    let get = |name: &str| properties.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone()).unwrap();
+
+    assert_eq!(get("name"), PklValue::String("café\tbar".to_string()));
+    assert_eq!(get("raw"), PklValue::String("C:\\dir \"quoted\"".to_string()));
+    assert_eq!(get("hex"), PklValue::Int(255));
+    assert_eq!(get("binary"), PklValue::Int(10));
+    assert_eq!(get("big"), PklValue::Int(1_000_000));
+    assert_eq!(get("negative"), PklValue::Float(-350.0));
+    assert_eq!(get("block"), PklValue::String("first\n  second".to_string()));
+    assert_eq!(get("empty"), PklValue::Listing(Vec::new()));
+    assert_eq!(get("class"), PklValue::Null);
+    assert_eq!(
+        get("env"),
+        PklValue::Mapping(vec![(PklValue::String("CI".to_string()), PklValue::String("true".to_string()))])
+    );
+
+    // amending a property again adds to it
+    let PklValue::Listing(servers) = get("servers") else {
+        panic!("expected a listing");
+    };
+    assert_eq!(servers.len(), 2);
+}
+
+#[test]
+fn test_parse_errors_point_at_source() {
+    let error = parse_pcf("moon.pcf", "name = \"app\"\nport = 8000 + 80\n").unwrap_err();
+    let CliError::PcfParseError { message, span, .. } = error else {
+        panic!("expected a parse error");
+    };
+    assert!(message.contains("property"), "{message}");
+    assert_eq!(span.offset(), 25);
+
+    assert!(matches!(parse_pcf("a.pcf", "name = \"\\(other)\""), Err(CliError::PcfParseError { .. })));
+    assert!(matches!(parse_pcf("a.pcf", "name = other.name"), Err(CliError::PcfParseError { .. })));
+    assert!(matches!(parse_pcf("a.pcf", "tags {\n  \"a\""), Err(CliError::PcfParseError { .. })));
+    assert!(matches!(parse_pcf("a.pcf", "\"element\""), Err(CliError::PcfParseError { .. })));
+}
+
+#[test]
+fn test_load_moon_pcf() {
+    let source = r#"amends "package://example.com/moon@1.0.0#/Project.pkl"
+
+language = "rust"
+tags { "app" }
+tasks {
+  ["build"] {
+    command = "cargo build"
+    inputs { "src/**/*" }
+  }
+}
+"#;
+    let LoadedConfig::Project(config) = load_pkl_config("moon.pcf", source, MoonConfig::Project).unwrap() else {
+        panic!("expected a project config");
+    };
+    assert_eq!(config.language, LanguageType::Rust);
+    assert_eq!(config.tags.len(), 1);
+    assert!(config.tasks.keys().any(|id| id.as_str() == "build"));
+    // defaults come from the config itself
+    assert_eq!(config.schema, "https://moonrepo.dev/schemas/project.json");
+
+    let invalid = load_pkl_config("moon.pcf", "tags = 3\n", MoonConfig::Project);
+    assert!(matches!(invalid, Err(CliError::ValidationError { .. })));
+}
@@ -0,0 +1,161 @@
+//! Downloads against a local HTTP stand-in that drops connections, fails, and may or may not support `Range`
+mod common;
+
+use std::time::Duration;
+
+use space_pklr::CliError;
+use space_pklr::pkl_tooling::{RetryPolicy, download_file};
+use tempfile::TempDir;
+
+use common::{Request, Requests, response};
+
+/// Serve each request with `respond`, as [`common::serve`] does. Returns the URL to download.
+fn serve(respond: impl Fn(usize, &Request) -> Vec<u8> + Send + 'static) -> (String, Requests) {
+    let (base, requests) = common::serve(respond);
+    (format!("{base}/pkl.tar.gz"), requests)
+}
+
+/// Where each request's `Range` started, `None` for whole-file requests
+fn ranges(requests: &Requests) -> Vec<Option<u64>> {
+    requests.lock().unwrap().iter().map(|request| request.range).collect()
+}
+
+/// The whole of `body`, or `sent` bytes of it before the connection drops
+fn ok(body: &[u8], sent: usize) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(&body[..sent.min(body.len())]);
+    response
+}
+
+fn partial(body: &[u8], from: u64) -> Vec<u8> {
+    let from = from as usize;
+    let mut response = format!(
+        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nConnection: close\r\n\r\n",
+        body.len() - from,
+        from,
+        body.len() - 1,
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(&body[from..]);
+    response
+}
+
+fn status(line: &str) -> Vec<u8> {
+    response(line, b"")
+}
+
+fn archive() -> Vec<u8> {
+    (0..100_000u32).map(|n| (n % 251) as u8).collect()
+}
+
+fn quick_retries(attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        attempts,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+    }
+}
+
+#[tokio::test]
+async fn test_dropped_downloads_resume_with_range() {
+    let body = archive();
+    let served = body.clone();
+    let (url, requests) = serve(move |index, request| match (index, request.range) {
+        (0, None) => ok(&served, 40_000),
+        (_, Some(from)) => partial(&served, from),
+        _ => status("500 Internal Server Error"),
+    });
+
+    let dir = TempDir::new().unwrap();
+    let destination = dir.path().join("downloads/pkl.tar.gz");
+    download_file(&url, &destination, &quick_retries(3)).await.unwrap();
+
+    assert_eq!(std::fs::read(&destination).unwrap(), body);
+    assert!(!dir.path().join("downloads/pkl.tar.gz.part").exists());
+    assert_eq!(ranges(&requests), [None, Some(40_000)]);
+}
+
+#[tokio::test]
+async fn test_partial_files_from_earlier_runs_are_resumed() {
+    let body = archive();
+    let served = body.clone();
+    let (url, requests) = serve(move |_, request| match request.range {
+        Some(from) => partial(&served, from),
+        None => ok(&served, served.len()),
+    });
+
+    let dir = TempDir::new().unwrap();
+    let destination = dir.path().join("pkl.tar.gz");
+    std::fs::write(dir.path().join("pkl.tar.gz.part"), &body[..500]).unwrap();
+    download_file(&url, &destination, &quick_retries(1)).await.unwrap();
+
+    assert_eq!(std::fs::read(&destination).unwrap(), body);
+    assert_eq!(ranges(&requests), [Some(500)]);
+}
+
+#[tokio::test]
+async fn test_servers_without_range_support_and_server_errors() {
+    let body = archive();
+    let served = body.clone();
+    // a 503, then a dropped connection, then the whole file again despite the `Range`
+    let (url, requests) = serve(move |index, _| match index {
+        0 => status("503 Service Unavailable"),
+        1 => ok(&served, 1_000),
+        _ => ok(&served, served.len()),
+    });
+
+    let dir = TempDir::new().unwrap();
+    let destination = dir.path().join("pkl.tar.gz");
+    download_file(&url, &destination, &quick_retries(3)).await.unwrap();
+
+    assert_eq!(std::fs::read(&destination).unwrap(), body);
+    assert_eq!(ranges(&requests), [None, None, Some(1_000)]);
+}
+
+#[tokio::test]
+async fn test_download_failures() {
+    // missing files aren't retried
+    let (url, requests) = serve(|_, _| status("404 Not Found"));
+    let dir = TempDir::new().unwrap();
+    let destination = dir.path().join("pkl.tar.gz");
+    let error = download_file(&url, &destination, &quick_retries(3)).await.unwrap_err();
+    assert!(
+        matches!(error.downcast_ref(), Some(CliError::PklInstallFailed { .. })),
+        "{error:?}"
+    );
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // transient ones are, until the attempts run out; what arrived is kept for next time
+    let body = archive();
+    let (url, requests) = serve(move |_, request| match request.range {
+        None => ok(&body, 2_000),
+        Some(_) => status("502 Bad Gateway"),
+    });
+    let error = download_file(&url, &destination, &quick_retries(3)).await.unwrap_err();
+    match error.downcast_ref() {
+        Some(CliError::NetworkError(message)) => assert!(message.contains("after 3 attempts"), "{message}"),
+        _ => panic!("{error:?}"),
+    }
+    assert_eq!(ranges(&requests), [None, Some(2_000), Some(2_000)]);
+    assert!(!destination.exists());
+    assert_eq!(
+        std::fs::metadata(dir.path().join("pkl.tar.gz.part")).unwrap().len(),
+        2_000
+    );
+}
+
+#[test]
+fn test_retry_backoff() {
+    let retry = RetryPolicy {
+        attempts: 5,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(300),
+    };
+    let backoffs: Vec<Duration> = (1..=4).map(|attempt| retry.backoff(attempt)).collect();
+    assert_eq!(backoffs, [100, 200, 300, 300].map(Duration::from_millis));
+}
@@ -0,0 +1,110 @@
+use miette::Diagnostic;
+use space_pklr::pkl_errors::{PklEvaluationError, PklStackFrame, parse_pkl_error};
+use space_pklr::{CliError, pkl_execution_error};
+use tempfile::TempDir;
+
+const MOON_PKL: &str = "amends \"Project.pkl\"\n\nlanguage = \"rust\"\nport: Int = \"8080\"\n";
+
+#[test]
+fn test_pkl_errors_point_at_the_module() {
+    let dir = TempDir::new().unwrap();
+    let module = dir.path().join("moon.pkl");
+    std::fs::write(&module, MOON_PKL).unwrap();
+
+    let stderr = format!(
+        "\u{1b}[31m–– Pkl Error ––\u{1b}[0m\n\
+         Expected value of type `Int`, but got type `String`.\n\
+         Value: \"8080\"\n\
+         \n\
+         4 | port: Int = \"8080\"\n\
+         \x20               ^^^^^^\n\
+         at moon#port (file://{path}, line 4)\n\
+         \n\
+         at moon (file://{path})\n\
+         \n\
+         Did you mean to write a number?\n",
+        path = module.display()
+    );
+    let error = pkl_execution_error("pkl eval moon.pkl", stderr, None);
+    let help = error.help().map(|help| help.to_string()).unwrap_or_default();
+
+    let CliError::PklEvaluationFailed(error) = error else {
+        panic!("expected an evaluation error");
+    };
+    let PklEvaluationError { command, message, frames, src, span, .. } = *error;
+    assert_eq!(command, "pkl eval moon.pkl");
+    assert_eq!(message, "Expected value of type `Int`, but got type `String`.\nValue: \"8080\"");
+    assert_eq!(frames.len(), 2);
+    assert_eq!(
+        frames[0],
+        PklStackFrame {
+            member: "moon#port".to_string(),
+            location: format!("file://{}", module.display()),
+            line: Some(4),
+            column: Some(13),
+            length: Some(6),
+            source_line: Some("port: Int = \"8080\"".to_string()),
+        }
+    );
+    assert_eq!(frames[1].line, None);
+
+    assert_eq!(src.unwrap().name(), module.display().to_string());
+    let span = span.unwrap();
+    assert_eq!(&MOON_PKL[span.offset()..span.offset() + span.len()], "\"8080\"");
+    assert!(help.contains("Did you mean to write a number?"), "{help}");
+    assert!(help.contains(&format!("at moon#port (file://{}:4:13)", module.display())), "{help}");
+}
+
+#[test]
+fn test_pkl_errors_decode_module_uris() {
+    // Pkl percent-encodes module URIs, so the module is only found once they're decoded
+    let dir = TempDir::new().unwrap();
+    let module = dir.path().join("my app #1").join("moon café.pkl");
+    std::fs::create_dir_all(module.parent().unwrap()).unwrap();
+    std::fs::write(&module, MOON_PKL).unwrap();
+
+    let stderr = format!(
+        "–– Pkl Error ––\nExpected value of type `Int`, but got type `String`.\n\n\
+         4 | port: Int = \"8080\"\n\
+         \x20               ^^^^^^\n\
+         at moon#port (file://{}/my%20app%20%231/moon%20caf%C3%A9.pkl, line 4)\n",
+        dir.path().display()
+    );
+    let CliError::PklEvaluationFailed(error) = pkl_execution_error("pkl eval", stderr, None) else {
+        panic!("expected an evaluation error");
+    };
+    assert_eq!(error.src.unwrap().name(), module.display().to_string());
+    let span = error.span.unwrap();
+    assert_eq!(&MOON_PKL[span.offset()..span.offset() + span.len()], "\"8080\"");
+}
+
+#[test]
+fn test_pkl_errors_without_the_module() {
+    // `path:line:col` frames, and a module that can't be read: the excerpt stands in for it
+    let stderr = "–– Pkl Error ––\nCannot find property `bar`.\n\n1 | foo = bar\n          ^^^\nat text#foo (/nowhere/text.pkl:1:7)\n";
+    let error = parse_pkl_error(stderr).unwrap();
+    assert_eq!(error.message, "Cannot find property `bar`.");
+    assert_eq!((error.frames[0].line, error.frames[0].column), (Some(1), Some(7)));
+
+    let CliError::PklEvaluationFailed(error) = error.into_cli_error("pkl eval", None) else {
+        panic!("expected an evaluation error");
+    };
+    let PklEvaluationError { src, span, .. } = *error;
+    assert_eq!(src.unwrap().name(), "/nowhere/text.pkl:1");
+    let span = span.unwrap();
+    assert_eq!((span.offset(), span.len()), (6, 3));
+
+    // no frames at all still gives the message
+    let error = parse_pkl_error("–– Pkl Error ––\nI/O error loading module `missing.pkl`.\n").unwrap();
+    assert!(error.frames.is_empty());
+    assert_eq!(error.message, "I/O error loading module `missing.pkl`.");
+}
+
+#[test]
+fn test_other_output_is_kept_as_is() {
+    assert_eq!(parse_pkl_error("Exception in thread \"main\" java.lang.OutOfMemoryError"), None);
+    assert_eq!(parse_pkl_error("–– Pkl Error ––\n\n"), None);
+
+    let error = pkl_execution_error("pkl eval", "segfault", Some("Check Pkl syntax".to_string()));
+    assert!(matches!(error, CliError::PklExecutionFailed { ref stderr, .. } if stderr == "segfault"), "{error:?}");
+}
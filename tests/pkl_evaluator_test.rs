@@ -0,0 +1,213 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use rmpv::Value;
+use space_pklr::{CliError, EvaluatorOptions, InMemoryModules, ModuleReader, PklEvaluator};
+use space_pklr::pkl_evaluator::PathElement;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+/// The server's end of the connection
+struct FakeServer {
+    stream: DuplexStream,
+    buffer: Vec<u8>,
+}
+
+impl FakeServer {
+    async fn receive(&mut self) -> (u64, Value) {
+        loop {
+            let mut cursor = Cursor::new(self.buffer.as_slice());
+            if let Ok(Value::Array(parts)) = rmpv::decode::read_value(&mut cursor) {
+                let used = cursor.position() as usize;
+                self.buffer.drain(..used);
+                return (parts[0].as_u64().unwrap(), parts[1].clone());
+            }
+            let mut chunk = [0; 1024];
+            let read = self.stream.read(&mut chunk).await.unwrap();
+            assert!(read > 0, "the client hung up");
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    async fn send(&mut self, code: u64, fields: Vec<(&str, Value)>) {
+        let fields = fields.into_iter().map(|(name, value)| (Value::from(name), value)).collect();
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &Value::Array(vec![Value::from(code), Value::Map(fields)])).unwrap();
+        self.stream.write_all(&bytes).await.unwrap();
+    }
+}
+
+fn get<'a>(fields: &'a Value, name: &str) -> &'a Value {
+    fields
+        .as_map()
+        .unwrap()
+        .iter()
+        .find(|(key, _)| key.as_str() == Some(name))
+        .map(|(_, value)| value)
+        .unwrap_or(&Value::Nil)
+}
+
+/// A string result, in Pkl's binary encoding
+fn text_result(text: &str) -> Value {
+    let mut bytes = Vec::new();
+    rmpv::encode::write_value(&mut bytes, &Value::from(text)).unwrap();
+    Value::Binary(bytes)
+}
+
+#[tokio::test]
+async fn test_evaluator_protocol() {
+    let (client, server) = tokio::io::duplex(64);
+    let mut server = FakeServer {
+        stream: server,
+        buffer: Vec::new(),
+    };
+
+    let server = tokio::spawn(async move {
+        let (code, request) = server.receive().await;
+        assert_eq!(code, 0x20);
+        let allowed: Vec<&str> = get(&request, "allowedModules").as_array().unwrap().iter().filter_map(Value::as_str).collect();
+        assert!(allowed.contains(&"spklr:") && allowed.contains(&"file:"), "{allowed:?}");
+        let readers = get(&request, "clientModuleReaders").as_array().unwrap();
+        assert_eq!(get(&readers[0], "scheme").as_str(), Some("spklr"));
+        assert_eq!(get(&request, "outputFormat").as_str(), Some("json"));
+        server
+            .send(0x21, vec![("requestId", get(&request, "requestId").clone()), ("evaluatorId", Value::from(7))])
+            .await;
+
+        // evaluating the module reads the one it amends, and lists the modules beside it
+        let (code, request) = server.receive().await;
+        assert_eq!(code, 0x23);
+        assert_eq!(get(&request, "evaluatorId").as_i64(), Some(7));
+        assert_eq!(get(&request, "moduleUri").as_str(), Some("spklr:/moon.pkl"));
+        assert_eq!(get(&request, "expr").as_str(), Some("output.text"));
+        let request_id = get(&request, "requestId").clone();
+
+        server
+            .send(0x25, vec![("evaluatorId", Value::from(7)), ("level", Value::from(1)), ("message", Value::from("careful"))])
+            .await;
+        server
+            .send(0x28, vec![("requestId", Value::from(100)), ("evaluatorId", Value::from(7)), ("uri", Value::from("spklr:/lib/base.pkl"))])
+            .await;
+        let (code, response) = server.receive().await;
+        assert_eq!(code, 0x29);
+        assert_eq!(get(&response, "requestId").as_i64(), Some(100));
+        assert_eq!(get(&response, "contents").as_str(), Some("language = \"rust\"\n"));
+
+        server
+            .send(0x28, vec![("requestId", Value::from(101)), ("evaluatorId", Value::from(7)), ("uri", Value::from("spklr:/missing.pkl"))])
+            .await;
+        let (code, response) = server.receive().await;
+        assert_eq!(code, 0x29);
+        assert!(get(&response, "error").as_str().unwrap().contains("missing.pkl"));
+
+        server
+            .send(0x2c, vec![("requestId", Value::from(102)), ("evaluatorId", Value::from(7)), ("uri", Value::from("spklr:/"))])
+            .await;
+        let (code, response) = server.receive().await;
+        assert_eq!(code, 0x2d);
+        let names: Vec<(&str, bool)> = get(&response, "pathElements")
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|element| (get(element, "name").as_str().unwrap(), get(element, "isDirectory").as_bool().unwrap()))
+            .collect();
+        assert_eq!(names, [("lib", true), ("moon.pkl", false)]);
+
+        server
+            .send(
+                0x24,
+                vec![("requestId", request_id), ("evaluatorId", Value::from(7)), ("result", text_result("{\"language\": \"rust\"}"))],
+            )
+            .await;
+
+        // errors come back as errors
+        let (code, request) = server.receive().await;
+        assert_eq!(code, 0x23);
+        assert_eq!(get(&request, "moduleText").as_str(), Some("foo = bar"));
+        server
+            .send(
+                0x24,
+                vec![
+                    ("requestId", get(&request, "requestId").clone()),
+                    ("evaluatorId", Value::from(7)),
+                    ("error", Value::from("Cannot find property `bar`.")),
+                ],
+            )
+            .await;
+
+        let (code, request) = server.receive().await;
+        assert_eq!(code, 0x22);
+        assert_eq!(get(&request, "evaluatorId").as_i64(), Some(7));
+    });
+
+    let modules = InMemoryModules::new("spklr")
+        .with_module("moon.pkl", "amends \"lib/base.pkl\"\n")
+        .with_module("lib/base.pkl", "language = \"rust\"\n");
+    let options = EvaluatorOptions {
+        module_readers: vec![Arc::new(modules.clone())],
+        ..Default::default()
+    };
+    let (reader, writer) = tokio::io::split(client);
+    let mut evaluator = PklEvaluator::connect(reader, writer, options).await.unwrap();
+
+    let json = evaluator.evaluate_module(&modules.uri("moon.pkl")).await.unwrap();
+    assert_eq!(json, "{\"language\": \"rust\"}");
+
+    let error = evaluator.evaluate_text("foo = bar").await.unwrap_err();
+    assert!(matches!(error, CliError::PklExecutionFailed { ref stderr, .. } if stderr.contains("`bar`")), "{error:?}");
+
+    evaluator.close().await.unwrap();
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_evaluate_file_encodes_its_uri() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let module = dir.path().join("my app #1").join("moon café.pkl");
+    std::fs::create_dir_all(module.parent().unwrap()).unwrap();
+    std::fs::write(&module, "language = \"rust\"\n").unwrap();
+
+    let (client, server) = tokio::io::duplex(64);
+    let mut server = FakeServer {
+        stream: server,
+        buffer: Vec::new(),
+    };
+    let server = tokio::spawn(async move {
+        let (_, request) = server.receive().await;
+        server
+            .send(0x21, vec![("requestId", get(&request, "requestId").clone()), ("evaluatorId", Value::from(1))])
+            .await;
+
+        let (code, request) = server.receive().await;
+        assert_eq!(code, 0x23);
+        let uri = get(&request, "moduleUri").as_str().unwrap().to_string();
+        server
+            .send(0x24, vec![("requestId", get(&request, "requestId").clone()), ("evaluatorId", Value::from(1)), ("result", text_result("{}"))])
+            .await;
+        server.receive().await;
+        uri
+    });
+
+    let (reader, writer) = tokio::io::split(client);
+    let mut evaluator = PklEvaluator::connect(reader, writer, EvaluatorOptions::default()).await.unwrap();
+    evaluator.evaluate_file(&module).await.unwrap();
+    evaluator.close().await.unwrap();
+
+    let uri = server.await.unwrap();
+    assert!(uri.starts_with("file:///"), "{uri}");
+    assert!(uri.ends_with("/my%20app%20%231/moon%20caf%C3%A9.pkl"), "{uri}");
+}
+
+#[test]
+fn test_in_memory_modules() {
+    let modules = InMemoryModules::new("mem").with_module("/a/b/c.pkl", "x = 1").with_module("a/d.pkl", "y = 2");
+    assert_eq!(modules.uri("a/d.pkl"), "mem:/a/d.pkl");
+    assert_eq!(modules.read("mem:///a/b/c.pkl").unwrap(), "x = 1");
+    assert!(modules.read("other:/a/d.pkl").is_err());
+    assert_eq!(
+        modules.list("mem:/a/").unwrap(),
+        [
+            PathElement { name: "b".to_string(), is_directory: true },
+            PathElement { name: "d.pkl".to_string(), is_directory: false },
+        ]
+    );
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use schematic::schema::{LiteralValue, SchemaType};
+use space_pklr::{import_pkl_file, import_pkl_schema};
+
+#[test]
+fn test_import_project_schema() {
+    let types = import_pkl_file(Path::new(".schema/Project.pkl")).unwrap();
+
+    // literal unions come back as enums
+    let SchemaType::Enum(scope) = &types["DependencyScope"].ty else {
+        panic!("DependencyScope should be an enum");
+    };
+    assert_eq!(scope.values.len(), 5);
+    assert!(scope.values.contains(&LiteralValue::String("peer".to_string())));
+
+    // classes come back as structs, with nullable and defaulted properties marked optional
+    let SchemaType::Struct(owners) = &types["OwnersConfig"].ty else {
+        panic!("OwnersConfig should be a struct");
+    };
+    assert!(owners.fields["defaultOwner"].optional);
+    assert!(owners.fields["optional"].optional);
+    assert!(!owners.fields["paths"].optional);
+    assert_eq!(owners.fields["optional"].schema.ty.get_default(), Some(&LiteralValue::Bool(false)));
+    assert!(matches!(owners.fields["customGroups"].schema.ty, SchemaType::Object(_)));
+
+    // module properties are collected under the module name
+    let SchemaType::Struct(project) = &types["Project"].ty else {
+        panic!("module properties should be collected into a Project struct");
+    };
+    assert!(project.fields.contains_key("$schema"));
+    assert!(matches!(&project.fields["language"].schema.ty, SchemaType::Reference(name) if name == "LanguageType"));
+
+    // backticked names keep their name
+    let SchemaType::Struct(task) = &types["TaskConfig"].ty else {
+        panic!("TaskConfig should be a struct");
+    };
+    assert!(task.fields.contains_key("extends"));
+}
+
+#[test]
+fn test_import_constraints_and_defaults() {
+    let source = r##"
+module Example
+
+/// A port number
+typealias Port = Int(isBetween(1, 65535))
+
+typealias Level = *"info" | "debug" | "trace"
+
+abstract class Base {
+  @Deprecated { message = "use `label`" }
+  name: String(!isEmpty)
+}
+
+class Service extends Base {
+  label: String(matches(Regex(#"^[a-z]+$"#)))
+  port: Port = 8080
+  ratio: Float(this >= 0.5, this < 1)
+  tags: Listing<String>(isDistinct, length <= 4) = new Listing { "a"; "b" }
+  hidden secret: String?
+  function greet(other: String): String = "hello \(other)"
+}
+
+service: Service
+"##;
+    let types = import_pkl_schema(source).unwrap();
+
+    let SchemaType::Integer(port) = &types["Port"].ty else {
+        panic!("Port should be an integer");
+    };
+    assert_eq!((port.min, port.max), (Some(1), Some(65535)));
+    assert_eq!(types["Port"].description.as_deref(), Some("A port number"));
+
+    let SchemaType::Enum(level) = &types["Level"].ty else {
+        panic!("Level should be an enum");
+    };
+    assert_eq!(level.default_index, Some(0));
+
+    let SchemaType::Struct(service) = &types["Service"].ty else {
+        panic!("Service should be a struct");
+    };
+    // inherited from Base
+    assert_eq!(service.fields["name"].deprecated.as_deref(), Some("use `label`"));
+    let SchemaType::String(name) = &service.fields["name"].schema.ty else {
+        panic!("name should be a string");
+    };
+    assert_eq!(name.min_length, Some(1));
+
+    let SchemaType::String(label) = &service.fields["label"].schema.ty else {
+        panic!("label should be a string");
+    };
+    assert_eq!(label.pattern.as_deref(), Some("^[a-z]+$"));
+
+    let SchemaType::Float(ratio) = &service.fields["ratio"].schema.ty else {
+        panic!("ratio should be a float");
+    };
+    assert_eq!((ratio.min, ratio.max_exclusive), (Some(0.5), Some(1.0)));
+
+    let SchemaType::Array(tags) = &service.fields["tags"].schema.ty else {
+        panic!("tags should be an array");
+    };
+    assert_eq!((tags.unique, tags.max_length), (Some(true), Some(4)));
+    assert!(service.fields["tags"].optional);
+
+    assert!(service.fields["secret"].hidden);
+    assert!(service.fields["secret"].nullable);
+    assert!(!service.fields.contains_key("greet"));
+
+    assert!(types.contains_key("Example"));
+}
+
+#[test]
+fn test_import_reports_syntax_errors() {
+    let error = import_pkl_schema("class Broken {\n  name: \n}\n").unwrap_err();
+    assert!(error.to_string().contains("expected a type"));
+}
@@ -0,0 +1,121 @@
+//! The direct installer end to end, against a local stand-in for the release mirror. The installer writes
+//! under the home directory and reads `SPKLR_PKL_MIRROR`, so everything runs in one test.
+#![cfg(unix)]
+
+mod common;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256};
+use space_pklr::CliError;
+use space_pklr::pkl_tooling::{
+    DEFAULT_PKL_MIRROR, PKL_MIRROR_ENV, PklInstallOptions, install_pkl, install_pkl_archive,
+    pkl_archive_name, pkl_download_url, validate_pkl_installation,
+};
+use tempfile::TempDir;
+
+use common::{Requests, response, serve};
+
+/// A release archive whose `pkl` reports `version`
+fn release_archive(version: &str) -> Vec<u8> {
+    let script = format!("#!/bin/sh\necho \"Pkl {version} (Linux, native)\"\n");
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(script.len() as u64);
+    header.set_mode(0o755);
+    builder.append_data(&mut header, "pkl-cli/bin/pkl", script.as_bytes()).unwrap();
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The paths the mirror was asked for
+fn paths(requests: &Requests) -> Vec<String> {
+    requests.lock().unwrap().iter().map(|request| request.path.clone()).collect()
+}
+
+#[tokio::test]
+async fn test_install_from_mirror_and_archive() {
+    let home = TempDir::new().unwrap();
+    let pkl_tools = home.path().join(".moon/tools/pkl");
+    // SAFETY: this is the only test in this binary, so nothing reads the environment concurrently
+    unsafe {
+        std::env::set_var("HOME", home.path());
+        std::env::remove_var(PKL_MIRROR_ENV);
+    }
+
+    let archive_name = pkl_archive_name().unwrap();
+    assert_eq!(
+        pkl_download_url(None, "0.28.0", &archive_name),
+        format!("{DEFAULT_PKL_MIRROR}/0.28.0/{archive_name}")
+    );
+    assert_eq!(pkl_download_url(Some("https://mirror.local/pkl/"), "0.28.0", "a.zip"), "https://mirror.local/pkl/0.28.0/a.zip");
+
+    let archive = release_archive("0.28.0");
+    let served = archive.clone();
+    let (mirror, requests) = serve(move |_, request| {
+        if request.path.ends_with(".tar.gz") {
+            response("200 OK", &served)
+        } else {
+            response("404 Not Found", b"")
+        }
+    });
+
+    // downloaded from the mirror, verified, extracted and runnable
+    let options = PklInstallOptions {
+        checksum: Some(sha256(&archive).to_uppercase()),
+        mirror: Some(format!("{mirror}/")),
+        ..Default::default()
+    };
+    let pkl_cli = install_pkl_archive("0.28.0", &options).await.unwrap();
+    assert_eq!(pkl_cli.path, pkl_tools.join("0.28.0/pkl-cli/bin/pkl"));
+    assert_eq!(pkl_cli.version.as_deref(), Some("0.28.0"));
+    assert!(validate_pkl_installation(&pkl_cli).await.unwrap());
+    assert_eq!(paths(&requests), [format!("/0.28.0/{archive_name}")]);
+    assert!(!pkl_tools.join(".downloads/0.28.0").join(&archive_name).exists());
+
+    // reinstalling swaps the new copy into place and leaves nothing else behind
+    let pkl_cli = install_pkl_archive("0.28.0", &options).await.unwrap();
+    assert!(validate_pkl_installation(&pkl_cli).await.unwrap());
+    let mut entries: Vec<String> = std::fs::read_dir(&pkl_tools)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    entries.sort();
+    assert_eq!(entries, [".downloads", "0.28.0"]);
+
+    // the environment variable picks the mirror too; a bad checksum installs nothing
+    unsafe { std::env::set_var(PKL_MIRROR_ENV, &mirror) };
+    let options = PklInstallOptions {
+        checksum: Some(sha256(b"something else")),
+        ..Default::default()
+    };
+    let error = install_pkl_archive("0.28.1", &options).await.unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(CliError::ChecksumMismatch { .. })), "{error:?}");
+    assert!(!pkl_tools.join("0.28.1").exists());
+    // nor is the bad download kept to resume
+    assert!(!pkl_tools.join(".downloads/0.28.1").join(&archive_name).exists());
+    assert_eq!(paths(&requests).last().unwrap(), &format!("/0.28.1/{archive_name}"));
+
+    // versions with no known checksum aren't downloaded at all
+    let error = install_pkl_archive("0.0.1", &PklInstallOptions::default()).await.unwrap_err();
+    assert!(error.to_string().contains("No known checksum"), "{error}");
+    assert_eq!(requests.lock().unwrap().len(), 3);
+
+    // a local archive goes through the same checks, without touching the network
+    let downloads = TempDir::new().unwrap();
+    let local = downloads.path().join(&archive_name);
+    let archive = release_archive("0.28.2");
+    std::fs::write(&local, &archive).unwrap();
+    let options = PklInstallOptions {
+        checksum: Some(sha256(&archive)),
+        from_archive: Some(local),
+        ..Default::default()
+    };
+    let pkl_cli = install_pkl(Some("0.28.2".to_string()), &options).await.unwrap();
+    assert_eq!(pkl_cli.path, pkl_tools.join("0.28.2/pkl-cli/bin/pkl"));
+    assert!(validate_pkl_installation(&pkl_cli).await.unwrap());
+    assert_eq!(requests.lock().unwrap().len(), 3);
+}
@@ -0,0 +1,117 @@
+#![cfg(unix)]
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use space_pklr::pkl_tooling::{OutputStream, execute_pkl_command_with_timeout, run_process};
+use space_pklr::{CliError, PklCli, PklSource};
+use tempfile::TempDir;
+
+fn sh(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", script]);
+    command
+}
+
+#[tokio::test]
+async fn test_run_process_streams_output() {
+    let mut lines = Vec::new();
+    let output = run_process(sh("echo one; echo warning >&2; printf two"), Duration::from_secs(10), |stream, line| {
+        lines.push((stream, line.to_string()))
+    })
+    .await
+    .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, "one\ntwo");
+    assert_eq!(output.stderr, "warning\n");
+    assert!(lines.contains(&(OutputStream::Stderr, "warning".to_string())));
+    let stdout: Vec<&str> = lines.iter().filter(|(stream, _)| *stream == OutputStream::Stdout).map(|(_, line)| line.as_str()).collect();
+    assert_eq!(stdout, ["one", "two"]);
+}
+
+#[tokio::test]
+async fn test_run_process_does_not_block_the_runtime() {
+    // the test runtime has one thread, which a blocking wait would hold for the whole second
+    let started = Instant::now();
+    let (output, ticked) = tokio::join!(run_process(sh("sleep 1"), Duration::from_secs(10), |_, _| {}), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        started.elapsed()
+    });
+    assert!(output.unwrap().status.success());
+    assert!(ticked < Duration::from_millis(500), "{ticked:?}");
+}
+
+#[tokio::test]
+async fn test_run_process_times_out() {
+    let started = Instant::now();
+    let error = run_process(sh("sleep 30"), Duration::from_millis(200), |_, _| {}).await.unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(10));
+    match error {
+        CliError::ProcessTimeout { command, elapsed } => {
+            assert_eq!(command, "sh -c sleep 30");
+            assert!(elapsed >= Duration::from_millis(200), "{elapsed:?}");
+        }
+        error => panic!("{error:?}"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_cancelled_process_is_killed() {
+    let dir = TempDir::new().unwrap();
+    let pid_file = dir.path().join("pid");
+    let script = format!("echo $$ > {}; exec sleep 30", pid_file.display());
+
+    tokio::select! {
+        _ = run_process(sh(&script), Duration::from_secs(60), |_, _| {}) => panic!("the process finished"),
+        _ = async {
+            while !pid_file.exists() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        } => {}
+    }
+
+    // gone, or at least dead and waiting to be reaped
+    let pid = std::fs::read_to_string(&pid_file).unwrap().trim().to_string();
+    let stat = std::path::Path::new("/proc").join(&pid).join("stat");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        match std::fs::read_to_string(&stat) {
+            Ok(stat) if !stat.contains(") Z") && Instant::now() < deadline => {
+                tokio::time::sleep(Duration::from_millis(20)).await
+            }
+            Ok(stat) => {
+                assert!(stat.contains(") Z"), "still running: {stat}");
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_execute_pkl_command_reports_failures() {
+    let dir = TempDir::new().unwrap();
+    let pkl = dir.path().join("pkl");
+    std::fs::write(&pkl, "#!/bin/sh\necho \"Cannot find module $2\" >&2\nexit 1\n").unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&pkl, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let pkl_cli = PklCli {
+        path: pkl.clone(),
+        source: PklSource::Manual(dir.path().to_path_buf()),
+        version: None,
+    };
+
+    let args = ["eval".to_string(), "missing.pkl".to_string()];
+    let error = execute_pkl_command_with_timeout(&pkl_cli, &args, Duration::from_secs(10)).await.unwrap_err();
+    match error.downcast_ref::<CliError>() {
+        Some(CliError::PklExecutionFailed { command, stderr, .. }) => {
+            assert_eq!(command, &format!("{} eval missing.pkl", pkl.display()));
+            assert!(stderr.contains("Cannot find module missing.pkl"), "{stderr}");
+        }
+        _ => panic!("{error:?}"),
+    }
+}
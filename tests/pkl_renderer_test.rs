@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+
+use schematic::Schematic;
+use schematic::schema::{
+    ArrayType, BooleanType, EnumType, IntegerType, LiteralValue, Schema, SchemaField, SchemaRenderer, SchemaType,
+    StringType, StructType, UnionType,
+};
+use space_pklr::types::moon::UnknownConfig;
+use space_pklr::custom_types::type_map_of;
+use space_pklr::{
+    LoadedConfig, ModuleLayout, PklSchemaOptions, PklSchemaRenderer, TupleTranslation, TypeMap, import_json_schema,
+};
+
+fn render(schemas: TypeMap, root: &str) -> String {
+    let mut renderer = PklSchemaRenderer::new(PklSchemaOptions {
+        config_name: LoadedConfig::Unknown(UnknownConfig {
+            name: Some(root.to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    renderer.render(schemas).unwrap()
+}
+
+#[test]
+fn test_render_json_schema_as_pkl() {
+    let source = r##"{
+  "title": "Service",
+  "description": "A deployable service",
+  "type": "object",
+  "required": ["name", "port"],
+  "properties": {
+    "name": { "type": "string", "minLength": 1, "description": "Service name" },
+    "port": { "$ref": "#/$defs/Port" },
+    "level": { "$ref": "#/$defs/Level" },
+    "tags": { "type": "array", "items": { "type": "string" }, "uniqueItems": true },
+    "env": { "type": "object", "additionalProperties": { "type": "string" } },
+    "owner": { "type": ["string", "null"], "pattern": "^[a-z]+$" },
+    "target": { "oneOf": [{ "type": "string" }, { "$ref": "#/$defs/Target" }] }
+  },
+  "$defs": {
+    "Port": { "type": "integer", "minimum": 1, "maximum": 65535, "default": 8080 },
+    "Level": { "enum": ["info", "debug"], "default": "info" },
+    "Target": {
+      "type": "object",
+      "required": ["host"],
+      "properties": { "host": { "type": "string" }, "class": { "type": "string" } }
+    }
+  }
+}"##;
+    let output = render(import_json_schema(source).unwrap(), "Service");
+
+    assert!(output.starts_with("/// A deployable service\nopen module Service\n"), "{output}");
+    assert!(output.contains("/// Service name\nname: String(!isEmpty)\n"), "{output}");
+    assert!(output.contains("port: Port\n"), "{output}");
+    assert!(output.contains("level: Level?"), "{output}");
+    assert!(output.contains("tags: Listing<String>(isDistinct)?"), "{output}");
+    assert!(output.contains("env: Mapping<String, String>?"), "{output}");
+    assert!(output.contains("owner: String(matches(Regex(#\"^[a-z]+$\"#)))?"), "{output}");
+    assert!(output.contains("target: (String | Target)?"), "{output}");
+
+    // other structs become classes, keywords get escaped
+    assert!(output.contains("open class Target {\n  `class`: String?\n\n  host: String\n}"), "{output}");
+
+    // named non-struct types become typealiases, with defaults marked
+    assert!(output.contains("typealias Port = Int(isBetween(1, 65535))"), "{output}");
+    assert!(output.contains("typealias Level = *\"info\" | \"debug\""), "{output}");
+}
+
+#[test]
+fn test_render_tuples() {
+    let source = r##"{
+  "title": "Shapes",
+  "type": "object",
+  "required": ["xyz", "mixed", "pair", "rgb"],
+  "properties": {
+    "xyz": { "type": "array", "prefixItems": [{ "type": "number" }, { "type": "number" }, { "type": "number" }] },
+    "mixed": { "type": "array", "prefixItems": [{ "type": "string" }, { "type": "integer" }, { "type": "boolean" }] },
+    "pair": { "type": "array", "prefixItems": [{ "type": "string" }, { "type": "integer" }] },
+    "rgb": { "$ref": "#/$defs/Rgb" }
+  },
+  "$defs": {
+    "Rgb": { "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "integer" }, { "type": "string" }] }
+  }
+}"##;
+    let output = render(import_json_schema(source).unwrap(), "Shapes");
+    assert!(output.contains("xyz: Listing<Number>(length == 3)\n"), "{output}");
+    assert!(
+        output.contains("mixed: Listing(length == 3 && this[0] is String && this[1] is Int && this[2] is Boolean)\n"),
+        "{output}"
+    );
+    assert!(output.contains("pair: Pair<String, Int>\n"), "{output}");
+    assert!(output.contains("typealias Rgb = Listing(length == 3 && this[0] is Int && this[1] is Int && this[2] is String)"), "{output}");
+
+    let mut renderer = PklSchemaRenderer::new(PklSchemaOptions {
+        config_name: LoadedConfig::Unknown(UnknownConfig::named("Shapes")),
+        tuple_translation: TupleTranslation::Class,
+        ..Default::default()
+    });
+    let output = renderer.render(import_json_schema(source).unwrap()).unwrap();
+    // fields render alphabetically, so `mixed` gets the first generated class
+    assert!(output.contains("mixed: Tuple0\n"), "{output}");
+    assert!(output.contains("xyz: Tuple1\n"), "{output}");
+    assert!(output.contains("pair: Pair<String, Int>\n"), "{output}");
+    assert!(output.contains("rgb: Rgb"), "{output}");
+    assert!(output.contains("open class Rgb {\n  _0: Int\n\n  _1: Int\n\n  _2: String\n}"), "{output}");
+    assert!(output.contains("open class Tuple0 {\n  _0: String\n\n  _1: Int\n\n  _2: Boolean\n}"), "{output}");
+}
+
+#[test]
+fn test_render_union_defaults() {
+    let retries = IntegerType {
+        default: Some(LiteralValue::Int(3)),
+        ..Default::default()
+    };
+    let task = StructType::new([
+        (
+            "cache".to_string(),
+            Schema::union(UnionType::from_schemas(
+                [
+                    Schema::boolean(BooleanType::default()),
+                    Schema::literal_value(LiteralValue::String("local".into())),
+                    Schema::literal_value(LiteralValue::String("remote".into())),
+                ],
+                Some(1),
+            )),
+        ),
+        (
+            "inputs".to_string(),
+            Schema::union(UnionType::from_schemas(
+                [
+                    Schema::string(StringType::default()),
+                    Schema::array(ArrayType::new(Schema::string(StringType::default()))),
+                ],
+                Some(1),
+            )),
+        ),
+        (
+            "retries".to_string(),
+            Schema::union(UnionType::from_schemas([Schema::integer(retries), Schema::string(StringType::default())], None)),
+        ),
+    ]);
+    let mut schemas = TypeMap::new();
+    schemas.insert("Task".to_string(), Schema::structure(task));
+    let output = render(schemas, "Task");
+
+    // the default's variant is marked, and its value spelled out
+    assert!(output.contains("cache: Boolean | *\"local\" | \"remote\" = \"local\"\n"), "{output}");
+    assert!(output.contains("inputs: String | *Listing<String> = new Listing {}\n"), "{output}");
+    assert!(output.contains("retries: *Int | String = 3\n"), "{output}");
+}
+
+#[derive(Schematic, serde::Serialize)]
+struct Target {
+    host: String,
+    port: u16,
+}
+
+#[derive(Schematic, serde::Serialize)]
+struct Workspace {
+    inputs: Vec<String>,
+    env: BTreeMap<String, String>,
+    targets: Vec<Target>,
+    outputs: Vec<String>,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            inputs: vec!["**/*".to_string(), "!dist".to_string()],
+            env: BTreeMap::from([("CI".to_string(), "true".to_string())]),
+            targets: vec![Target { host: "say \"hi\"".to_string(), port: 80 }],
+            outputs: Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn test_render_listing_and_mapping_defaults() {
+    let options = PklSchemaOptions {
+        config_name: LoadedConfig::Unknown(UnknownConfig::named("Workspace")),
+        ..Default::default()
+    };
+    let mut renderer = PklSchemaRenderer::new(options.with_default_values::<Workspace>("Workspace"));
+    let output = renderer.render(type_map_of::<Workspace>()).unwrap();
+
+    assert!(output.contains("inputs: Listing<String> = new Listing { \"**/*\"; \"!dist\" }\n"), "{output}");
+    assert!(output.contains("env: Mapping<String, String> = new Mapping { [\"CI\"] = \"true\" }\n"), "{output}");
+    assert!(
+        output.contains("targets: Listing<Target> = new Listing { new Target { host = \"say \\\"hi\\\"\"; port = 80 } }\n"),
+        "{output}"
+    );
+    // empty defaults are left to Pkl
+    assert!(output.contains("outputs: Listing<String>\n"), "{output}");
+}
+
+fn field(schema: Schema, optional: bool) -> SchemaField {
+    SchemaField { optional, ..SchemaField::new(schema) }
+}
+
+fn reference(name: &str) -> Schema {
+    Schema::new(SchemaType::Reference(name.to_string()))
+}
+
+fn named(name: &str, mut schema: Schema) -> (String, Schema) {
+    schema.name = Some(name.to_string());
+    (name.to_string(), schema)
+}
+
+/// A Project whose Task goes to its own module and whose Scope is shared by everything
+fn split_schemas() -> TypeMap {
+    let scope = EnumType::new([LiteralValue::String("build".to_string()), LiteralValue::String("production".to_string())]);
+    let owned = |first: &str| {
+        StructType::new([
+            (first.to_string(), field(Schema::string(StringType::default()), false)),
+            ("scope".to_string(), field(reference("Scope"), false)),
+        ])
+    };
+    TypeMap::from([
+        named("Scope", Schema::enumerable(scope)),
+        named("Dependency", Schema::structure(owned("id"))),
+        named("Task", Schema::structure(owned("command"))),
+        named(
+            "Project",
+            Schema::structure(StructType::new([
+                ("task".to_string(), field(reference("Task"), false)),
+                ("dependency".to_string(), field(reference("Dependency"), true)),
+                ("scope".to_string(), field(reference("Scope"), false)),
+            ])),
+        ),
+    ])
+}
+
+fn split_options() -> PklSchemaOptions {
+    PklSchemaOptions {
+        config_name: LoadedConfig::Unknown(UnknownConfig {
+            name: Some("Project".to_string()),
+            ..Default::default()
+        }),
+        module_layout: ModuleLayout::Split,
+        module_groups: [("tasks".to_string(), vec!["Task".to_string()])].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_render_split_modules() {
+    let mut renderer = PklSchemaRenderer::new(split_options());
+    let modules = renderer.render_modules(split_schemas()).unwrap();
+    assert_eq!(modules.keys().collect::<Vec<_>>(), ["Project.pkl", "Tasks.pkl", "Common.pkl"]);
+
+    // the root module imports its siblings and qualifies what it borrows from them
+    let project = &modules["Project.pkl"];
+    assert!(project.starts_with("open module Project\n\nimport \"Common.pkl\"\nimport \"Tasks.pkl\"\n"), "{project}");
+    assert!(project.contains("task: Tasks.Task\n"), "{project}");
+    assert!(project.contains("scope: Common.Scope\n"), "{project}");
+    // only the root uses Dependency, so it stays with the root
+    assert!(project.contains("open class Dependency {\n  id: String\n\n  scope: Common.Scope\n}"), "{project}");
+
+    let tasks = &modules["Tasks.pkl"];
+    assert!(tasks.starts_with("open module Tasks\n\nimport \"Common.pkl\"\n"), "{tasks}");
+    assert!(tasks.contains("open class Task {\n  command: String\n\n  scope: Common.Scope\n}"), "{tasks}");
+    assert!(!tasks.contains("Tasks.Task"), "{tasks}");
+
+    // Scope is shared, so it lands in the common module, which needs nothing from the others
+    let common = &modules["Common.pkl"];
+    assert!(common.contains("typealias Scope = \"build\" | \"production\""), "{common}");
+    assert!(!common.contains("import "), "{common}");
+    assert!(!common.contains("class "), "{common}");
+}
+
+#[test]
+fn test_render_single_module_layout() {
+    let options = PklSchemaOptions { module_layout: ModuleLayout::Single, ..split_options() };
+    let mut renderer = PklSchemaRenderer::new(options.clone());
+    let modules = renderer.render_modules(split_schemas()).unwrap();
+    let single = PklSchemaRenderer::new(options).render(split_schemas()).unwrap();
+
+    assert_eq!(modules.len(), 1);
+    assert_eq!(modules["Project.pkl"], single);
+    assert!(single.contains("task: Task\n") && !single.contains("import "), "{single}");
+}
+
+#[test]
+fn test_render_package_of_several_roots() {
+    let workspace = TypeMap::from([
+        named("Scope", split_schemas()["Scope"].clone()),
+        named(
+            "Workspace",
+            Schema::structure(StructType::new([
+                ("scope".to_string(), field(reference("Scope"), false)),
+                ("project".to_string(), field(reference("Project"), false)),
+            ])),
+        ),
+    ]);
+    let roots = vec![
+        (LoadedConfig::Unknown(UnknownConfig::named("Project")), split_schemas()),
+        (LoadedConfig::Unknown(UnknownConfig::named("Workspace")), workspace),
+    ];
+    let mut renderer = PklSchemaRenderer::new(split_options());
+    let modules = renderer.render_package(roots).unwrap();
+    assert_eq!(modules.keys().collect::<Vec<_>>(), ["Project.pkl", "Workspace.pkl", "Tasks.pkl", "Common.pkl"]);
+
+    let project = &modules["Project.pkl"];
+    assert!(project.contains("task: Tasks.Task\n") && project.contains("open class Dependency"), "{project}");
+
+    // both roots share the one common module
+    let workspace = &modules["Workspace.pkl"];
+    assert!(workspace.starts_with("open module Workspace\n\nimport \"Common.pkl\"\n"), "{workspace}");
+    assert!(workspace.contains("scope: Common.Scope\n"), "{workspace}");
+    // another root is referenced by its module, which is the type
+    assert!(workspace.contains("import \"Project.pkl\"\n") && workspace.contains("project: Project\n"), "{workspace}");
+    assert_eq!(modules["Common.pkl"].matches("typealias Scope =").count(), 1);
+}
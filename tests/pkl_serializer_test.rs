@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use space_pklr::{
+    PklSerializerOptions, PklValue, from_pkl_str, pkl_value_to_string, to_pkl_string, to_pkl_string_with_options, to_pkl_value,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Task {
+    command: String,
+    inputs: Vec<String>,
+    run_in_ci: bool,
+    retry_count: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Language {
+    Rust,
+}
+
+#[derive(Serialize)]
+struct Project {
+    language: Language,
+    tags: Vec<String>,
+    tasks: BTreeMap<String, Task>,
+    #[serde(rename = "class")]
+    class_name: Option<String>,
+    weights: Vec<f64>,
+    owners: Vec<BTreeMap<String, String>>,
+    #[serde(rename = "file-groups")]
+    file_groups: BTreeMap<String, Vec<String>>,
+}
+
+fn project() -> Project {
+    Project {
+        language: Language::Rust,
+        tags: vec!["app".to_string(), "say \"hi\"".to_string()],
+        tasks: BTreeMap::from([(
+            "build".to_string(),
+            Task {
+                command: "cargo build".to_string(),
+                inputs: vec!["src/**/*".to_string()],
+                run_in_ci: true,
+                retry_count: None,
+            },
+        )]),
+        class_name: Some("lib".to_string()),
+        weights: vec![1.0, 0.5],
+        owners: vec![BTreeMap::from([("team".to_string(), "core".to_string())])],
+        file_groups: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn test_serialize_struct_as_pkl() {
+    let output = to_pkl_string(&project()).unwrap();
+    assert_eq!(
+        output,
+        r#"language = "rust"
+tags {
+  "app"
+  "say \"hi\""
+}
+tasks {
+  ["build"] {
+    command = "cargo build"
+    inputs {
+      "src/**/*"
+    }
+    runInCi = true
+  }
+}
+`class` = "lib"
+weights {
+  1.0
+  0.5
+}
+owners {
+  new {
+    ["team"] = "core"
+  }
+}
+`file-groups` = new Mapping {}
+"#
+    );
+}
+
+#[test]
+fn test_serialize_with_amends() {
+    let options = PklSerializerOptions {
+        amends: Some("schemas/Project.pkl".to_string()),
+        indent: "    ".to_string(),
+    };
+    let output = to_pkl_string_with_options(&project(), &options).unwrap();
+    assert!(output.starts_with("amends \"schemas/Project.pkl\"\n\nlanguage = \"rust\"\n"), "{output}");
+    assert!(output.contains("tasks {\n    [\"build\"] {\n        command"), "{output}");
+}
+
+#[test]
+fn test_write_pkl_value_tree() {
+    // the same tree writes as Pkl, or as any other format through serde
+    let value = to_pkl_value(&project()).unwrap();
+    assert_eq!(pkl_value_to_string(value.clone(), &PklSerializerOptions::default()).unwrap(), to_pkl_string(&project()).unwrap());
+
+    let json = serde_json::to_value(&value).unwrap();
+    assert_eq!(json["tasks"]["build"]["inputs"], serde_json::json!(["src/**/*"]));
+    assert_eq!(json["weights"], serde_json::json!([1.0, 0.5]));
+    // unset fields were left out of the tree
+    assert!(json["tasks"]["build"].get("retryCount").is_none());
+    assert!(pkl_value_to_string(PklValue::Listing(vec![]), &PklSerializerOptions::default()).is_err());
+}
+
+#[test]
+fn test_serialize_values_and_errors() {
+    // top-level maps with string keys become properties
+    let value = serde_json::json!({ "name": "app", "ports": [80, 443], "meta": null });
+    let output = to_pkl_string(&value).unwrap();
+    assert!(output.contains("name = \"app\"\n"), "{output}");
+    assert!(output.contains("ports {\n  80\n  443\n}\n"), "{output}");
+    assert!(output.contains("meta = null\n"), "{output}");
+
+    assert!(to_pkl_string(&vec![1, 2]).is_err());
+    let keyed_by_list = BTreeMap::from([(vec![1], "nope")]);
+    assert!(to_pkl_string(&BTreeMap::from([("outer", keyed_by_list)])).is_err());
+}
+
+#[test]
+fn test_empty_collections_read_back_as_collections() {
+    // amending `exclude {}` would read back as an empty object, which a `Vec` field rejects
+    let value = serde_json::json!({ "exclude": [], "env": {}, "nested": { "inputs": [] } });
+    let output = to_pkl_string(&value).unwrap();
+    assert!(output.contains("exclude = new Listing {}\n"), "{output}");
+    assert!(output.contains("env = new Mapping {}\n"), "{output}");
+    assert!(output.contains("  [\"inputs\"] = new Listing {}\n"), "{output}");
+
+    #[derive(Deserialize)]
+    struct Nested {
+        inputs: Vec<String>,
+    }
+    #[derive(Deserialize)]
+    struct Config {
+        exclude: Vec<String>,
+        env: BTreeMap<String, String>,
+        nested: Nested,
+    }
+    let config: Config = from_pkl_str(&output).unwrap();
+    assert!(config.exclude.is_empty() && config.env.is_empty() && config.nested.inputs.is_empty());
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use space_pklr::CliError;
+use space_pklr::pkl_tooling::{get_compatible_pkl_versions, get_latest_compatible_pkl_version, untested_pkl_version_warning};
+use space_pklr::pkl_versions::{
+    PklVersions, VersionRequest, VersionSource, compare_versions, find_pinned_version, pin_version,
+};
+use tempfile::TempDir;
+
+fn install(tools_dir: &Path, version: &str) {
+    let bin = tools_dir.join(version).join("pkl-cli/bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::write(bin.join(if cfg!(windows) { "pkl.exe" } else { "pkl" }), "binary").unwrap();
+}
+
+#[test]
+fn test_installed_versions_default_and_uninstall() {
+    let dir = TempDir::new().unwrap();
+    let versions = PklVersions::new(dir.path());
+    assert!(versions.installed().unwrap().is_empty());
+
+    for version in ["0.28.9", "0.28.10", "0.27.2"] {
+        install(dir.path(), version);
+    }
+    std::fs::create_dir(dir.path().join("0.29.0")).unwrap(); // an interrupted install
+
+    let installed: Vec<String> = versions.installed().unwrap().into_iter().map(|pkl| pkl.version).collect();
+    assert_eq!(installed, ["0.28.10", "0.28.9", "0.27.2"]);
+
+    assert_eq!(versions.default_version().unwrap(), None);
+    let pkl = versions.set_default("0.28.9").unwrap();
+    assert!(pkl.path.ends_with("pkl-cli/bin/pkl") || pkl.path.ends_with("pkl-cli/bin/pkl.exe"));
+    assert_eq!(versions.default_version().unwrap().as_deref(), Some("0.28.9"));
+
+    let error = versions.set_default("0.29.0").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(CliError::PklVersionNotInstalled { version }) if version == "0.29.0"));
+
+    // only directories right under the tools directory are versions
+    assert!(versions.uninstall("..").is_err());
+    assert!(versions.uninstall("0.28.9/pkl-cli").is_err());
+    assert!(dir.path().exists());
+
+    versions.uninstall("0.28.10").unwrap();
+    assert_eq!(versions.default_version().unwrap().as_deref(), Some("0.28.9"));
+    versions.uninstall("0.28.9").unwrap();
+    assert!(!dir.path().join("0.28.9").exists());
+    assert_eq!(versions.default_version().unwrap(), None);
+}
+
+#[test]
+fn test_pinned_versions() {
+    let dir = TempDir::new().unwrap();
+    let project = dir.path().join("repo/packages/app");
+    std::fs::create_dir_all(&project).unwrap();
+    assert_eq!(find_pinned_version(&project).unwrap(), None);
+
+    std::fs::write(dir.path().join("repo/.prototools"), "node = \"20.0.0\"\npkl = \"0.28.1\"\n").unwrap();
+    let pinned = find_pinned_version(&project).unwrap().unwrap();
+    assert_eq!(pinned.version, "0.28.1");
+    assert_eq!(pinned.source, VersionSource::Pinned(dir.path().join("repo/.prototools")));
+
+    // `.pkl-version` wins within a directory, and nearer directories win over further ones
+    let file = pin_version(&dir.path().join("repo"), "0.28.2").unwrap();
+    assert_eq!(find_pinned_version(&project).unwrap().unwrap().source, VersionSource::Pinned(file));
+    std::fs::write(project.join(".prototools"), "node = \"22.0.0\"\n").unwrap();
+    assert_eq!(find_pinned_version(&project).unwrap().unwrap().version, "0.28.2");
+    std::fs::write(project.join(".prototools"), "pkl = \"0.27.2\"\n").unwrap();
+    assert_eq!(find_pinned_version(&project).unwrap().unwrap().version, "0.27.2");
+
+    // a pin beats the default
+    let versions = PklVersions::new(dir.path().join("tools"));
+    install(versions.tools_dir(), "0.28.0");
+    versions.set_default("0.28.0").unwrap();
+    assert_eq!(versions.requested_version(&project).unwrap().unwrap().version, "0.27.2");
+    let requested = versions.requested_version(dir.path()).unwrap().unwrap();
+    assert_eq!((requested.version.as_str(), requested.source), ("0.28.0", VersionSource::Default));
+}
+
+#[test]
+fn test_compare_versions() {
+    use std::cmp::Ordering;
+    assert_eq!(compare_versions("0.28.10", "0.28.9"), Ordering::Greater);
+    assert_eq!(compare_versions("0.9.0", "0.28.0"), Ordering::Less);
+    assert_eq!(compare_versions("0.28.0", "0.28.0"), Ordering::Equal);
+}
+
+#[test]
+fn test_version_requests() {
+    let request = |text: &str| text.parse::<VersionRequest>().unwrap();
+
+    // a complete version accepts later compatible ones, like Cargo
+    assert!(matches!(request("0.28.0"), VersionRequest::Version(_)));
+    assert!(request("0.28.0").matches("0.28.2"));
+    assert!(!request("0.28.1").matches("0.28.0"));
+    assert!(!request("0.28.0").matches("0.29.0"));
+    assert!(request("=0.28.0").matches("0.28.0") && !request("=0.28.0").matches("0.28.2"));
+
+    for range in ["0.28", "^0.28", ">=0.28, <0.29", "~0.28.1"] {
+        assert!(matches!(request(range), VersionRequest::Range(_)), "{range}");
+        assert!(request(range).matches("0.28.2"), "{range}");
+        assert!(!request(range).matches("0.27.2"), "{range}");
+    }
+    assert!(!request("0.28").matches("not a version"));
+
+    let latest = get_latest_compatible_pkl_version();
+    assert_eq!(request("latest-compatible"), VersionRequest::LatestCompatible);
+    assert!(request("latest-compatible").matches(latest));
+    assert_eq!(request("latest-compatible").to_string(), "latest-compatible");
+
+    let error = "newest".parse::<VersionRequest>().unwrap_err();
+    assert!(matches!(error, CliError::InvalidPklVersion { ref version, .. } if version == "newest"));
+}
+
+#[test]
+fn test_resolving_version_requests() {
+    let request = |text: &str| text.parse::<VersionRequest>().unwrap();
+    let latest = get_latest_compatible_pkl_version();
+    assert!(get_compatible_pkl_versions().contains(&latest));
+    assert!(get_compatible_pkl_versions().iter().all(|version| compare_versions(version, latest).is_le()));
+
+    // installed versions come first, then tested ones
+    assert_eq!(request("0.28").resolve(["0.28.0", "0.27.2"]).as_deref(), Some("0.28.0"));
+    assert_eq!(request("0.28").resolve([]).as_deref(), Some(latest));
+    assert_eq!(request("latest-compatible").resolve(["0.28.0"]).as_deref(), Some(latest));
+
+    // complete versions outside the matrix can still be installed; ranges need a known version
+    assert_eq!(request("0.26.3").resolve([]).as_deref(), Some("0.26.3"));
+    assert_eq!(request("=0.26.3").resolve([]).as_deref(), Some("0.26.3"));
+    assert_eq!(request("^0.26").resolve([]), None);
+
+    assert_eq!(untested_pkl_version_warning(latest), None);
+    assert!(untested_pkl_version_warning("0.26.3").unwrap().contains("0.26.3"));
+}
+
+#[test]
+fn test_complete_versions_install_themselves() {
+    let request = |text: &str| text.parse::<VersionRequest>().unwrap();
+
+    // a compatible 0.28.2 satisfies `0.28.0`, but installing `0.28.0` means 0.28.0
+    assert!(request("0.28.0").matches("0.28.2") && !request("0.28.0").installs("0.28.2"));
+    assert!(request("0.28.0").installs("v0.28.0") && request("=0.28.0").installs("0.28.0"));
+    assert_eq!(request("0.28.0").resolve(["0.28.2"]).as_deref(), Some("0.28.2"));
+    assert_eq!(request("0.28.0").install_version(["0.28.2"]).as_deref(), Some("0.28.0"));
+    assert_eq!(request("=0.28.0").install_version(["0.28.2"]).as_deref(), Some("0.28.0"));
+
+    // ranges still install the newest version they accept
+    assert!(request("0.28").installs("0.28.2"));
+    assert_eq!(request("0.28").install_version(["0.28.0", "0.28.2"]).as_deref(), Some("0.28.2"));
+}
+
+#[test]
+fn test_ranges_pick_installed_versions() {
+    let dir = TempDir::new().unwrap();
+    let project = dir.path().join("project");
+    std::fs::create_dir(&project).unwrap();
+    let versions = PklVersions::new(dir.path().join("tools"));
+    for version in ["0.27.2", "0.28.0", "0.28.1"] {
+        install(versions.tools_dir(), version);
+    }
+
+    let found = versions.find(&"^0.28".parse().unwrap()).unwrap().unwrap();
+    assert_eq!(found.version, "0.28.1");
+    assert_eq!(versions.find(&"0.29".parse().unwrap()).unwrap(), None);
+
+    pin_version(&project, "0.27").unwrap();
+    let pinned = find_pinned_version(&project).unwrap().unwrap();
+    assert_eq!(pinned.version, "0.27");
+    assert!(pinned.request().unwrap().matches("0.27.2"));
+
+    // a pin that isn't a version is refused, and one written by hand is reported with its file
+    assert!(pin_version(&project, "newest").is_err());
+    assert_eq!(find_pinned_version(&project).unwrap().unwrap().version, "0.27");
+    std::fs::write(project.join(".pkl-version"), "newest\n").unwrap();
+    let error = find_pinned_version(&project).unwrap().unwrap().request().unwrap_err();
+    assert!(format!("{error:?}").contains(".pkl-version"), "{error:?}");
+}
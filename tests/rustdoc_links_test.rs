@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod rustdoc_links_tests {
-    use crate::new_renderer::{PklSchemaRenderer, PklSchemaOptions};
+    use space_pklr::pkl_renderer::{PklSchemaRenderer, PklSchemaOptions};
     use indexmap::IndexMap;
     use schematic_types::*;
 
@@ -17,10 +17,10 @@ mod rustdoc_links_tests {
             deprecated: None,
             name: Some("Bar".to_string()),
             nullable: false,
-            optional: false,
             ty: SchemaType::Struct(Box::new(StructType {
-                fields: IndexMap::new(),
+                fields: Default::default(),
                 partial: false,
+                required: None,
             })),
         };
         schemas.insert("Bar".to_string(), bar_schema);
@@ -31,20 +31,18 @@ mod rustdoc_links_tests {
             deprecated: None,
             name: Some("Option".to_string()),
             nullable: false,
-            optional: false,
             ty: SchemaType::Enum(Box::new(EnumType {
-                name: "Option".to_string(),
                 values: vec![
                     LiteralValue::String("Some".to_string()),
                     LiteralValue::String("None".to_string()),
                 ],
                 variants: None,
-                default: None,
+                default_index: None,
             })),
         };
         schemas.insert("Option".to_string(), option_schema);
 
-        renderer.schemas = schemas;
+        renderer.load_schemas(schemas);
         renderer
     }
 
@@ -6,9 +6,9 @@ use regex::Regex;
 fn main() {
     println!("Testing improved Rust doc link regex patterns...\n");
 
-    // Define the improved regex patterns
+    // Define the improved regex patterns (the regex crate has no look-ahead, so `after` keeps the next character)
     let backtick_regex = Regex::new(r"\[`(?P<ref>[^`\]]+)`\]").unwrap();
-    let simple_regex = Regex::new(r"\[(?P<ref>[^\]`\(\)]+)\](?!\(|\[)").unwrap();
+    let simple_regex = Regex::new(r"\[(?P<ref>[^\]`\(\)]+)\](?P<after>[^\(\[]|$)").unwrap();
     let link_backticks_regex = Regex::new(r"\[(?P<text>[^\]]+)\]\(`(?P<ref>[^`\)]+)`\)").unwrap();
     let link_no_backticks_regex = Regex::new(r"\[(?P<text>[^\]]+)\]\((?P<ref>[^\)`]+)\)").unwrap();
     let reference_style_regex = Regex::new(r"\[(?P<text>[^\]]+)\]\[(?P<ref>[^\]]+)\]").unwrap();
@@ -95,7 +95,7 @@ fn test_complete_pipeline() {
 
 fn process_doc_references(text: &str) -> String {
     let backtick_regex = Regex::new(r"\[`(?P<ref>[^`\]]+)`\]").unwrap();
-    let simple_regex = Regex::new(r"\[(?P<ref>[^\]`\(\)]+)\](?!\(|\[)").unwrap();
+    let simple_regex = Regex::new(r"\[(?P<ref>[^\]`\(\)]+)\](?P<after>[^\(\[]|$)").unwrap();
     let link_backticks_regex = Regex::new(r"\[(?P<text>[^\]]+)\]\(`(?P<ref>[^`\)]+)`\)").unwrap();
     let link_no_backticks_regex = Regex::new(r"\[(?P<text>[^\]]+)\]\((?P<ref>[^\)`]+)\)").unwrap();
     let reference_style_regex = Regex::new(r"\[(?P<text>[^\]]+)\]\[(?P<ref>[^\]]+)\]").unwrap();
@@ -135,7 +135,7 @@ fn process_doc_references(text: &str) -> String {
     // Handle [reference] style - simple link
     result = simple_regex.replace_all(&result, |caps: &regex::Captures| {
         let reference = &caps["ref"];
-        format!("[{}]({})", reference, reference)
+        format!("[{}]({}){}", reference, reference, &caps["after"])
     }).to_string();
 
     // Remove reference definitions
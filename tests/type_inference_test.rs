@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use serde_json::json;
+use space_pklr::_rewrite::infer_config_type;
+use space_pklr::{Confidence, MoonConfig, SchemaFormat, UnknownConfig};
+
+#[test]
+fn test_infer_from_path() {
+    let inference = MoonConfig::infer(Some(Path::new("apps/web/moon.yml")), &json!({"projects": []})).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Project);
+    assert_eq!(inference.confidence, Confidence::Certain);
+
+    // the path wins without the content being read at all
+    let inference = infer_config_type(Path::new(".moon/tasks/node.yml"), "{ not yaml", &SchemaFormat::Yaml).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::InheritedTasks);
+
+    // outside `.moon/`, a `workspace.yml` is whatever its settings say
+    let inference = infer_config_type(Path::new("workspace.yml"), "language: rust\ntasks: {}\n", &SchemaFormat::Yaml).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Project);
+}
+
+#[test]
+fn test_infer_from_schema_url() {
+    let content = json!({"$schema": "https://moonrepo.dev/schemas/toolchain.json"});
+    let inference = MoonConfig::infer(Some(Path::new("config.yml")), &content).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Toolchain);
+    assert_eq!(inference.confidence, Confidence::Certain);
+}
+
+#[test]
+fn test_infer_from_settings() {
+    let inference = MoonConfig::infer_from_content(&json!({"projects": ["apps/*"], "vcs": {}})).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Workspace);
+    assert_eq!(inference.confidence, Confidence::High);
+    assert_eq!(inference.candidates, [MoonConfig::Workspace]);
+
+    let inference = MoonConfig::infer_from_content(&json!({"language": "rust", "tasks": {}})).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Project);
+    assert_eq!(inference.confidence, Confidence::High);
+
+    let inference = MoonConfig::infer_from_content(&json!({"command": "cargo", "deps": []})).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Task);
+}
+
+#[test]
+fn test_ambiguous_settings() {
+    // `env` and `platform` are project settings and task ones
+    let inference = MoonConfig::infer_from_content(&json!({"env": {"CI": "true"}, "platform": "node"})).unwrap();
+    assert_eq!(inference.confidence, Confidence::Low);
+    assert_eq!(inference.candidates, [MoonConfig::Project, MoonConfig::Task]);
+
+    // nothing fits: the closest type is still offered
+    let inference = MoonConfig::infer_from_content(&json!({"language": "rust", "nonsense": 1})).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Project);
+    assert_eq!(inference.confidence, Confidence::Low);
+
+    assert!(MoonConfig::infer_from_content(&json!(["tags"])).is_none());
+}
+
+#[test]
+fn test_infer_pkl_settings() {
+    let inference = infer_config_type(Path::new("ci.pkl"), "node { version = \"20.0.0\" }\n", &SchemaFormat::Pkl).unwrap();
+    assert_eq!(inference.config_type, MoonConfig::Toolchain);
+}
+
+#[test]
+fn test_unknown_config_type_hint() {
+    let config = UnknownConfig::new(json!({"projects": {"web": "apps/web"}})).with_type_hint();
+    assert_eq!(config.type_hint.as_deref(), Some("workspace"));
+
+    let config = UnknownConfig::new(json!("not a config")).with_type_hint();
+    assert_eq!(config.type_hint, None);
+}
+
+#[test]
+fn test_basenames() {
+    // every type `all_types` lists has one, as the `All` error suggests
+    for config_type in MoonConfig::all_types() {
+        assert!(config_type.basename().is_ok(), "{config_type}");
+    }
+    assert_eq!(MoonConfig::Task.basename().unwrap(), "tasks");
+    assert!(MoonConfig::All.basename().is_err());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use space_pklr::workspace::{
+    BatchAction, BatchOutput, expand_workspace_projects, find_project_configs, run_batch, summary_table,
+};
+use space_pklr::{CliError, MoonConfig, SchemaFormat, load_pkl_config_file};
+use tempfile::TempDir;
+
+use common::write;
+
+/// A workspace with three projects found by globs, one by source, one excluded and one without a config
+fn workspace() -> TempDir {
+    common::workspace(&[
+        (
+            ".moon/workspace.yml",
+            "projects:\n  globs:\n    - 'apps/*'\n    - 'packages/*/moon.yml'\n    - '!apps/legacy'\n  sources:\n    tools: tools/cli\n",
+        ),
+        ("apps/web/moon.yml", "language: typescript\ntasks:\n  build:\n    command: vite build\n"),
+        ("apps/api/moon.yml", "language: rust\nproject:\n  channel: general\n"),
+        ("apps/legacy/moon.yml", "language: javascript\n"),
+        ("apps/docs/README.md", "no config here\n"),
+        ("packages/ui/moon.yml", "tags: [ui]\n"),
+        ("packages/ui/node_modules/dep/moon.yml", "tags: [dep]\n"),
+        ("tools/cli/moon.pkl", "language = \"rust\"\n"),
+    ])
+}
+
+fn relative(root: &Path, paths: impl IntoIterator<Item = PathBuf>) -> Vec<String> {
+    paths
+        .into_iter()
+        .map(|path| path.strip_prefix(root).unwrap().display().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_find_project_configs() {
+    let dir = workspace();
+    let configs = find_project_configs(dir.path()).await.unwrap();
+    assert_eq!(
+        relative(dir.path(), configs),
+        ["apps/api/moon.yml", "apps/web/moon.yml", "packages/ui/moon.yml", "tools/cli/moon.pkl"]
+    );
+}
+
+#[test]
+fn test_expand_root_project() {
+    let dir = workspace();
+    let projects = moon_config::WorkspaceProjects::Globs(vec![".".to_string(), "packages/*".to_string()]);
+    let dirs = expand_workspace_projects(dir.path(), &projects).unwrap();
+    assert_eq!(dirs.into_iter().collect::<Vec<_>>(), [dir.path().to_path_buf(), dir.path().join("packages/ui")]);
+}
+
+#[tokio::test]
+async fn test_batches_larger_than_the_worker_pool() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    let projects = 4 * std::thread::available_parallelism().map_or(4, usize::from) + 1;
+    let configs: Vec<PathBuf> = (0..projects)
+        .map(|index| {
+            let config = format!("apps/app{index:03}/moon.yml");
+            write(root, &config, "language: rust\n");
+            root.join(config)
+        })
+        .collect();
+
+    let outcomes = run_batch(root, configs.clone(), BatchAction::Validate).await;
+    assert!(outcomes.iter().all(|outcome| outcome.result.is_ok()));
+    let order: Vec<PathBuf> = outcomes.into_iter().map(|outcome| root.join(outcome.config)).collect();
+    assert_eq!(order, configs);
+}
+
+#[tokio::test]
+async fn test_missing_workspace_config() {
+    let dir = TempDir::new().unwrap();
+    let error = find_project_configs(dir.path()).await.unwrap_err();
+    assert!(matches!(error, CliError::FileNotFound { .. }));
+}
+
+#[tokio::test]
+async fn test_validate_workspace() {
+    let dir = workspace();
+    let configs = find_project_configs(dir.path()).await.unwrap();
+    let outcomes = run_batch(dir.path(), configs, BatchAction::Validate).await;
+
+    let failed: Vec<_> = outcomes.iter().filter(|outcome| outcome.result.is_err()).collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].config, Path::new("apps/api/moon.yml"));
+    assert!(matches!(failed[0].result, Err(CliError::InvalidConfig { .. })));
+
+    let table = summary_table(&outcomes);
+    assert!(table.contains("apps/web/moon.yml"), "{table}");
+    assert!(table.ends_with("3 succeeded, 1 failed"), "{table}");
+}
+
+#[tokio::test]
+async fn test_convert_workspace_beside_originals() {
+    let dir = workspace();
+    let root = dir.path();
+    let configs = find_project_configs(root).await.unwrap();
+    let action = BatchAction::Convert { to: SchemaFormat::Pkl, output: BatchOutput::Beside, force: false };
+    let outcomes = run_batch(root, configs, action).await;
+
+    let written: Vec<_> = outcomes.iter().filter_map(|outcome| outcome.result.as_ref().ok().cloned().flatten()).collect();
+    assert_eq!(relative(root, written), ["apps/web/moon.pkl", "packages/ui/moon.pkl"]);
+    // a config that's already Pkl would overwrite itself
+    let tools = outcomes.iter().find(|outcome| outcome.config == Path::new("tools/cli/moon.pkl")).unwrap();
+    assert!(matches!(tools.result, Err(CliError::Generic(_))));
+
+    let web = load_pkl_config_file(&root.join("apps/web/moon.pkl"), MoonConfig::Project).unwrap();
+    assert_eq!(web.config_type_name(None), "project");
+}
+
+#[tokio::test]
+async fn test_convert_workspace_into_directory() {
+    let dir = workspace();
+    let root = dir.path();
+    let out = TempDir::new().unwrap();
+    let configs = vec![root.join("apps/web/moon.yml")];
+    let action = BatchAction::Convert {
+        to: SchemaFormat::Json,
+        output: BatchOutput::Directory(out.path().to_path_buf()),
+        force: false,
+    };
+    let outcomes = run_batch(root, configs, action).await;
+    assert_eq!(outcomes[0].result.as_ref().unwrap().as_deref(), Some(out.path().join("apps/web/moon.json").as_path()));
+    assert!(fs::read_to_string(out.path().join("apps/web/moon.json")).unwrap().contains("vite build"));
+}